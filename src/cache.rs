@@ -0,0 +1,480 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use crate::eit::EitEvent;
+use crate::scan::crc32_mpeg2;
+
+/// One cached EPG event. Title/description text isn't stored inline — it's
+/// interned in [`EpgCache::texts`] keyed by the CRC32 of the string, since
+/// identical descriptors are broadcast for every occurrence of a recurring
+/// programme and storing them once saves a lot of space over a week's guide.
+#[derive(Clone)]
+struct CachedEvent {
+    start_time: i64,
+    duration: u32,
+    running_status: u8,
+    table_id: u8,
+    version_number: u8,
+    name_hash: u32,
+    desc_hash: u32,
+    language: String,
+}
+
+/// Persistent, deduplicated EPG cache, modeled on enigma2's epgcache: events
+/// are keyed by `(service_id, event_id)` so repeated scans of the same
+/// multiplex merge into one growing guide instead of each run discarding the
+/// last one's events. Sections are deduplicated by the CRC32 of their raw
+/// bytes (EIT sections repeat constantly on the wire), and when two reads
+/// disagree about an event the one from the higher `(table_id,
+/// version_number)` wins, so a schedule table or a later version overwrites
+/// a stale present/following entry rather than the reverse.
+pub struct EpgCache {
+    events: BTreeMap<(u16, u16), CachedEvent>,
+    texts: BTreeMap<u32, (String, u32)>,
+    seen_section_crcs: HashSet<u32>,
+}
+
+impl EpgCache {
+    pub fn new() -> Self {
+        EpgCache {
+            events: BTreeMap::new(),
+            texts: BTreeMap::new(),
+            seen_section_crcs: HashSet::new(),
+        }
+    }
+
+    /// Load a cache previously written by [`EpgCache::save`], or an empty
+    /// cache if `path` doesn't exist yet (e.g. the very first run).
+    pub fn load(path: &Path) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(EpgCache::new());
+        }
+
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+
+        let mut cache = EpgCache::new();
+        for (idx, line) in content.lines().enumerate() {
+            let line_num = idx + 1;
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (kind, rest) = line
+                .split_once(' ')
+                .ok_or_else(|| format!("Line {line_num}: malformed entry"))?;
+            match kind {
+                "T" => cache.load_text_line(rest, line_num)?,
+                "E" => cache.load_event_line(rest, line_num)?,
+                other => return Err(format!("Line {line_num}: unknown entry kind '{other}'")),
+            }
+        }
+        Ok(cache)
+    }
+
+    fn load_text_line(&mut self, rest: &str, line_num: usize) -> Result<(), String> {
+        let mut fields = rest.splitn(3, ':');
+        let hash = fields
+            .next()
+            .ok_or_else(|| format!("Line {line_num}: missing text hash"))?;
+        let hash = u32::from_str_radix(hash, 16)
+            .map_err(|_| format!("Line {line_num}: invalid text hash '{hash}'"))?;
+        let refcount: u32 = fields
+            .next()
+            .ok_or_else(|| format!("Line {line_num}: missing refcount"))?
+            .parse()
+            .map_err(|_| format!("Line {line_num}: invalid refcount"))?;
+        let text = unescape_text(fields.next().unwrap_or(""));
+        self.texts.insert(hash, (text, refcount));
+        Ok(())
+    }
+
+    fn load_event_line(&mut self, rest: &str, line_num: usize) -> Result<(), String> {
+        let fields: Vec<&str> = rest.splitn(10, ':').collect();
+        if fields.len() < 10 {
+            return Err(format!(
+                "Line {line_num}: expected 10 fields, got {}",
+                fields.len()
+            ));
+        }
+        let service_id: u16 = parse_field(fields[0], "service_id", line_num)?;
+        let event_id: u16 = parse_field(fields[1], "event_id", line_num)?;
+        let start_time: i64 = parse_field(fields[2], "start_time", line_num)?;
+        let duration: u32 = parse_field(fields[3], "duration", line_num)?;
+        let running_status: u8 = parse_field(fields[4], "running_status", line_num)?;
+        let table_id = u8::from_str_radix(fields[5], 16)
+            .map_err(|_| format!("Line {line_num}: invalid table_id '{}'", fields[5]))?;
+        let version_number: u8 = parse_field(fields[6], "version_number", line_num)?;
+        let name_hash = u32::from_str_radix(fields[7], 16)
+            .map_err(|_| format!("Line {line_num}: invalid name_hash '{}'", fields[7]))?;
+        let desc_hash = u32::from_str_radix(fields[8], 16)
+            .map_err(|_| format!("Line {line_num}: invalid desc_hash '{}'", fields[8]))?;
+        let language = fields[9].to_string();
+
+        self.events.insert(
+            (service_id, event_id),
+            CachedEvent {
+                start_time,
+                duration,
+                running_status,
+                table_id,
+                version_number,
+                name_hash,
+                desc_hash,
+                language,
+            },
+        );
+        Ok(())
+    }
+
+    /// Persist the cache to `path`, creating its parent directory if needed.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create {}: {e}", parent.display()))?;
+            }
+        }
+
+        let mut content = String::new();
+        content.push_str("# epgrab EPG cache (epgrab::cache::EpgCache) - do not edit by hand\n");
+        for (hash, (text, refcount)) in &self.texts {
+            content.push_str(&format!("T {hash:08x}:{refcount}:{}\n", escape_text(text)));
+        }
+        for ((service_id, event_id), ev) in &self.events {
+            content.push_str(&format!(
+                "E {service_id}:{event_id}:{}:{}:{}:{:02x}:{}:{:08x}:{:08x}:{}\n",
+                ev.start_time,
+                ev.duration,
+                ev.running_status,
+                ev.table_id,
+                ev.version_number,
+                ev.name_hash,
+                ev.desc_hash,
+                ev.language,
+            ));
+        }
+
+        fs::write(path, content).map_err(|e| format!("Failed to write {}: {e}", path.display()))
+    }
+
+    fn intern_text(&mut self, text: &str) -> u32 {
+        let hash = crc32_mpeg2(text.as_bytes());
+        self.texts
+            .entry(hash)
+            .and_modify(|(_, refcount)| *refcount += 1)
+            .or_insert_with(|| (text.to_string(), 1));
+        hash
+    }
+
+    fn release_text(&mut self, hash: u32) {
+        if let std::collections::btree_map::Entry::Occupied(mut entry) = self.texts.entry(hash) {
+            let (_, refcount) = entry.get_mut();
+            *refcount -= 1;
+            if *refcount == 0 {
+                entry.remove();
+            }
+        }
+    }
+
+    /// Merge one newly-decoded EIT event into the cache. Returns `true` if
+    /// the cache changed (a new event, or an existing one upgraded to a
+    /// higher table/version); `false` if an existing entry already came from
+    /// an equal-or-higher `(table_id, version_number)` and this event was
+    /// discarded as stale.
+    fn insert_event(&mut self, event: &EitEvent, table_id: u8, version_number: u8) -> bool {
+        let key = (event.service_id, event.event_id);
+        if let Some(existing) = self.events.get(&key) {
+            if (existing.table_id, existing.version_number) >= (table_id, version_number) {
+                return false;
+            }
+            let (old_name_hash, old_desc_hash) = (existing.name_hash, existing.desc_hash);
+            self.release_text(old_name_hash);
+            self.release_text(old_desc_hash);
+        }
+
+        let name_hash = self.intern_text(&event.event_name);
+        let desc_hash = self.intern_text(&event.description);
+        self.events.insert(
+            key,
+            CachedEvent {
+                start_time: event.start_time,
+                duration: event.duration,
+                running_status: event.running_status,
+                table_id,
+                version_number,
+                name_hash,
+                desc_hash,
+                language: event.language.clone(),
+            },
+        );
+        true
+    }
+
+    /// `(table_id, version_number)` priority stamped on events merged via
+    /// [`EpgCache::ingest_external`], lower than any real EIT table id
+    /// (`0x4E`/`0x50`-`0x5F`) so a live scan always overrides an imported
+    /// guide on conflict, while still filling in events the cache doesn't
+    /// have yet.
+    pub const EXTERNAL_IMPORT_PRIORITY: (u8, u8) = (0x00, 0x00);
+
+    /// Merge one event from an external source (e.g. an imported XMLTV
+    /// guide, see [`crate::xmltv`]) that has no raw EIT section to derive a
+    /// CRC from. Returns `true` if the cache changed.
+    pub fn ingest_external(&mut self, event: &EitEvent) -> bool {
+        let (table_id, version_number) = Self::EXTERNAL_IMPORT_PRIORITY;
+        self.insert_event(event, table_id, version_number)
+    }
+
+    /// Merge every event parsed out of one raw EIT section into the cache,
+    /// skipping the whole section if its CRC32 matches one already processed
+    /// this run. Returns how many events were newly inserted or upgraded.
+    pub fn ingest_section(
+        &mut self,
+        raw_section: &[u8],
+        table_id: u8,
+        version_number: u8,
+        events: Vec<EitEvent>,
+    ) -> usize {
+        let crc = crc32_mpeg2(raw_section);
+        if !self.seen_section_crcs.insert(crc) {
+            return 0;
+        }
+
+        events
+            .iter()
+            .filter(|event| self.insert_event(event, table_id, version_number))
+            .count()
+    }
+
+    /// Drop every cached event that has already finished as of `now` (a Unix
+    /// timestamp), releasing its interned text. Call this after merging in a
+    /// run's events so the cache doesn't grow forever with programmes no
+    /// XMLTV consumer can use anymore.
+    pub fn evict_expired(&mut self, now: i64) {
+        let expired: Vec<(u16, u16)> = self
+            .events
+            .iter()
+            .filter(|(_, ev)| ev.start_time + ev.duration as i64 <= now)
+            .map(|(key, _)| *key)
+            .collect();
+
+        for key in expired {
+            if let Some(ev) = self.events.remove(&key) {
+                self.release_text(ev.name_hash);
+                self.release_text(ev.desc_hash);
+            }
+        }
+    }
+
+    /// Every distinct service_id currently carrying events in the cache, for
+    /// callers that want to report on services outside their known channel
+    /// list.
+    pub fn service_ids(&self) -> std::collections::BTreeSet<u16> {
+        self.events.keys().map(|(sid, _)| *sid).collect()
+    }
+
+    /// Reconstruct the cached events for one channel, sorted by start time,
+    /// for XMLTV generation.
+    pub fn events_for_service(&self, service_id: u16) -> Vec<EitEvent> {
+        let mut events: Vec<EitEvent> = self
+            .events
+            .iter()
+            .filter(|((sid, _), _)| *sid == service_id)
+            .map(|((sid, eid), ev)| EitEvent {
+                service_id: *sid,
+                event_id: *eid,
+                start_time: ev.start_time,
+                duration: ev.duration,
+                running_status: ev.running_status,
+                event_name: self.text(ev.name_hash),
+                description: self.text(ev.desc_hash),
+                language: ev.language.clone(),
+            })
+            .collect();
+        events.sort_by_key(|e| e.start_time);
+        events
+    }
+
+    fn text(&self, hash: u32) -> String {
+        self.texts
+            .get(&hash)
+            .map(|(text, _)| text.clone())
+            .unwrap_or_default()
+    }
+}
+
+impl Default for EpgCache {
+    fn default() -> Self {
+        EpgCache::new()
+    }
+}
+
+fn parse_field<T: std::str::FromStr>(s: &str, what: &str, line_num: usize) -> Result<T, String> {
+    s.parse().map_err(|_| format!("Line {line_num}: invalid {what} '{s}'"))
+}
+
+fn escape_text(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn unescape_text(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') => out.push('\n'),
+                Some('\\') => out.push('\\'),
+                Some(other) => {
+                    out.push('\\');
+                    out.push(other);
+                }
+                None => out.push('\\'),
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_event(service_id: u16, event_id: u16, start_time: i64, duration: u32) -> EitEvent {
+        EitEvent {
+            service_id,
+            event_id,
+            start_time,
+            duration,
+            running_status: 4,
+            event_name: "Show".to_string(),
+            description: "About the show".to_string(),
+            language: "eng".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_escape_unescape_roundtrip() {
+        let text = "Line one\nLine two \\ with backslash";
+        assert_eq!(unescape_text(&escape_text(text)), text);
+    }
+
+    #[test]
+    fn test_ingest_section_inserts_new_event() {
+        let mut cache = EpgCache::new();
+        let events = vec![sample_event(1, 100, 1000, 1800)];
+        let merged = cache.ingest_section(b"section-bytes", 0x4E, 1, events);
+        assert_eq!(merged, 1);
+        assert_eq!(cache.events_for_service(1).len(), 1);
+    }
+
+    #[test]
+    fn test_ingest_section_skips_duplicate_raw_section() {
+        let mut cache = EpgCache::new();
+        let events_a = vec![sample_event(1, 100, 1000, 1800)];
+        let events_b = vec![sample_event(1, 100, 1000, 1800)];
+        assert_eq!(cache.ingest_section(b"same-bytes", 0x4E, 1, events_a), 1);
+        assert_eq!(cache.ingest_section(b"same-bytes", 0x4E, 1, events_b), 0);
+    }
+
+    #[test]
+    fn test_higher_table_wins_over_present_following() {
+        let mut cache = EpgCache::new();
+        cache.ingest_section(b"pf", 0x4E, 1, vec![sample_event(1, 100, 1000, 1800)]);
+        let mut updated = sample_event(1, 100, 1000, 1800);
+        updated.event_name = "Updated Show".to_string();
+        cache.ingest_section(b"schedule", 0x50, 1, vec![updated]);
+
+        let events = cache.events_for_service(1);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event_name, "Updated Show");
+    }
+
+    #[test]
+    fn test_stale_lower_table_does_not_overwrite() {
+        let mut cache = EpgCache::new();
+        cache.ingest_section(b"schedule", 0x50, 1, vec![sample_event(1, 100, 1000, 1800)]);
+        let mut stale = sample_event(1, 100, 1000, 1800);
+        stale.event_name = "Stale".to_string();
+        cache.ingest_section(b"pf", 0x4E, 1, vec![stale]);
+
+        let events = cache.events_for_service(1);
+        assert_eq!(events[0].event_name, "Show");
+    }
+
+    #[test]
+    fn test_shared_text_is_interned_once() {
+        let mut cache = EpgCache::new();
+        cache.ingest_section(
+            b"a",
+            0x4E,
+            1,
+            vec![sample_event(1, 100, 1000, 1800), sample_event(1, 101, 2000, 1800)],
+        );
+        assert_eq!(cache.texts.len(), 2); // one name, one description, shared by both events
+        assert_eq!(cache.texts[&crc32_mpeg2(b"Show")].1, 2);
+    }
+
+    #[test]
+    fn test_evict_expired_removes_past_events_and_releases_text() {
+        let mut cache = EpgCache::new();
+        cache.ingest_section(b"a", 0x4E, 1, vec![sample_event(1, 100, 1000, 1800)]);
+        cache.evict_expired(1000 + 1800 + 1);
+        assert!(cache.events_for_service(1).is_empty());
+        assert!(cache.texts.is_empty());
+    }
+
+    #[test]
+    fn test_evict_expired_keeps_future_events() {
+        let mut cache = EpgCache::new();
+        cache.ingest_section(b"a", 0x4E, 1, vec![sample_event(1, 100, 1000, 1800)]);
+        cache.evict_expired(1000);
+        assert_eq!(cache.events_for_service(1).len(), 1);
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let mut cache = EpgCache::new();
+        cache.ingest_section(
+            b"a",
+            0x50,
+            2,
+            vec![sample_event(1, 100, 1000, 1800), sample_event(1, 101, 3000, 900)],
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("epg.dat");
+        cache.save(&path).unwrap();
+
+        let loaded = EpgCache::load(&path).unwrap();
+        let mut events = loaded.events_for_service(1);
+        events.sort_by_key(|e| e.event_id);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].event_name, "Show");
+        assert_eq!(events[1].start_time, 3000);
+    }
+
+    #[test]
+    fn test_load_missing_file_returns_empty_cache() {
+        let cache = EpgCache::load(Path::new("/nonexistent/epg.dat")).unwrap();
+        assert!(cache.events_for_service(1).is_empty());
+    }
+
+    #[test]
+    fn test_save_and_load_preserves_text_with_newline() {
+        let mut cache = EpgCache::new();
+        let mut event = sample_event(1, 100, 1000, 1800);
+        event.description = "Part one\nPart two".to_string();
+        cache.ingest_section(b"a", 0x4E, 1, vec![event]);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("epg.dat");
+        cache.save(&path).unwrap();
+
+        let loaded = EpgCache::load(&path).unwrap();
+        assert_eq!(loaded.events_for_service(1)[0].description, "Part one\nPart two");
+    }
+}
@@ -1,21 +1,256 @@
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 
+use crate::scan::{
+    dvbv5_to_zap_bandwidth, dvbv5_to_zap_fec, dvbv5_to_zap_guard, dvbv5_to_zap_hierarchy,
+    dvbv5_to_zap_inversion, dvbv5_to_zap_modulation, dvbv5_to_zap_polarization,
+    dvbv5_to_zap_transmission,
+};
+
+/// What a PMT elementary stream carries, beyond its raw `stream_type`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamKind {
+    Video,
+    Audio,
+    Subtitle,
+    Teletext,
+    Other,
+}
+
+/// One elementary stream listed in a service's PMT: a PID, its MPEG-TS
+/// `stream_type`, the kind of track it is (resolved from `stream_type` plus
+/// any descriptors that override it, e.g. AC-3 or a registration tag), and
+/// its language if a language/subtitling/teletext descriptor named one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ElementaryStream {
+    pub pid: u16,
+    pub stream_type: u8,
+    pub kind: StreamKind,
+    /// ISO-639 language code, or empty if no applicable descriptor was present.
+    pub language: String,
+}
+
+/// Generates a validated enum that round-trips to the canonical zap-format
+/// token named in each variant: `FromStr` accepts only the listed tokens
+/// (rejecting anything else with a clear error), and `Display` formats back
+/// to the exact same token.
+macro_rules! zap_token_enum {
+    ($name:ident { $($variant:ident => $token:literal),+ $(,)? }) => {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum $name {
+            $($variant),+
+        }
+
+        impl std::str::FromStr for $name {
+            type Err = String;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s {
+                    $($token => Ok($name::$variant),)+
+                    other => Err(format!(concat!("unknown ", stringify!($name), " token '{}'"), other)),
+                }
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                let token = match self {
+                    $($name::$variant => $token,)+
+                };
+                f.write_str(token)
+            }
+        }
+    };
+}
+
+zap_token_enum!(Inversion {
+    Off => "INVERSION_OFF",
+    On => "INVERSION_ON",
+    Auto => "INVERSION_AUTO",
+});
+
+zap_token_enum!(Bandwidth {
+    Mhz5 => "BANDWIDTH_5_MHZ",
+    Mhz6 => "BANDWIDTH_6_MHZ",
+    Mhz7 => "BANDWIDTH_7_MHZ",
+    Mhz8 => "BANDWIDTH_8_MHZ",
+    Mhz10 => "BANDWIDTH_10_MHZ",
+    Mhz1712 => "BANDWIDTH_1_712_MHZ",
+    Auto => "BANDWIDTH_AUTO",
+});
+
+// Forward error correction rate, shared by DVB-T's `fec_hp`/`fec_lp`.
+zap_token_enum!(Fec {
+    None => "FEC_NONE",
+    Half => "FEC_1_2",
+    TwoThirds => "FEC_2_3",
+    ThreeQuarters => "FEC_3_4",
+    FourFifths => "FEC_4_5",
+    FiveSixths => "FEC_5_6",
+    SixSevenths => "FEC_6_7",
+    SevenEighths => "FEC_7_8",
+    EightNinths => "FEC_8_9",
+    Auto => "FEC_AUTO",
+});
+
+// DVB-T's QAM constellation (DVB-S's QPSK/8PSK and ATSC's VSB tokens stay
+// free-form strings on their own `Tuning` variants for now).
+zap_token_enum!(Modulation {
+    Qam16 => "QAM_16",
+    Qam32 => "QAM_32",
+    Qam64 => "QAM_64",
+    Qam128 => "QAM_128",
+    Qam256 => "QAM_256",
+    QamAuto => "QAM_AUTO",
+});
+
+zap_token_enum!(TransmissionMode {
+    K1 => "TRANSMISSION_MODE_1K",
+    K2 => "TRANSMISSION_MODE_2K",
+    K4 => "TRANSMISSION_MODE_4K",
+    K8 => "TRANSMISSION_MODE_8K",
+    K16 => "TRANSMISSION_MODE_16K",
+    K32 => "TRANSMISSION_MODE_32K",
+    Auto => "TRANSMISSION_MODE_AUTO",
+});
+
+zap_token_enum!(GuardInterval {
+    Quarter => "GUARD_INTERVAL_1_4",
+    Eighth => "GUARD_INTERVAL_1_8",
+    Sixteenth => "GUARD_INTERVAL_1_16",
+    ThirtySecond => "GUARD_INTERVAL_1_32",
+    Auto => "GUARD_INTERVAL_AUTO",
+});
+
+zap_token_enum!(Hierarchy {
+    None => "HIERARCHY_NONE",
+    Alpha1 => "HIERARCHY_1",
+    Alpha2 => "HIERARCHY_2",
+    Alpha4 => "HIERARCHY_4",
+    Auto => "HIERARCHY_AUTO",
+});
+
+/// Delivery-system-specific tuning parameters. `Channel` keeps only what every
+/// delivery system shares (name, frequency, PIDs, service ID); everything
+/// that only makes sense for one system (DVB-T's hierarchy, DVB-S's LNB
+/// polarization, ...) lives in the matching variant here instead of being a
+/// string field on `Channel` that's empty/meaningless for the other systems.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tuning {
+    DvbT {
+        inversion: Inversion,
+        bandwidth: Bandwidth,
+        fec_hp: Fec,
+        fec_lp: Fec,
+        modulation: Modulation,
+        transmission_mode: TransmissionMode,
+        guard_interval: GuardInterval,
+        hierarchy: Hierarchy,
+    },
+    DvbC {
+        inversion: String,
+        symbol_rate: u64,
+        fec: String,
+        modulation: String,
+    },
+    DvbS {
+        /// "H"/"V" (or "L"/"R"); see [`crate::scan::dvbv5_to_zap_polarization`].
+        polarization: String,
+        symbol_rate: u64,
+        fec: String,
+        /// "QPSK" for DVB-S, "PSK_8" for DVB-S2; selects the delivery system.
+        modulation: String,
+        /// Orbital position of the satellite, e.g. "19.2E"; empty if unknown.
+        satellite_position: String,
+        /// DiSEqC 1.0 committed-switch port (0-3) selecting the LNB input.
+        diseqc_port: u8,
+    },
+    Atsc {
+        modulation: String,
+    },
+}
+
 #[allow(dead_code)]
+#[derive(Clone)]
 pub struct Channel {
     pub name: String,
     pub frequency: u64,
-    pub inversion: String,
-    pub bandwidth: String,
-    pub fec_hp: String,
-    pub fec_lp: String,
-    pub modulation: String,
-    pub transmission_mode: String,
-    pub guard_interval: String,
-    pub hierarchy: String,
     pub video_pid: u16,
     pub audio_pid: u16,
     pub service_id: u16,
+    pub tuning: Tuning,
+    /// Every elementary stream the PMT listed (video, audio, subtitles,
+    /// teletext); empty when the channel wasn't discovered via PMT scanning.
+    pub elementary_streams: Vec<ElementaryStream>,
+}
+
+/// Parse `s` as a `u16`, accepting a `0x`/`0X`-prefixed hex literal (as PIDs
+/// and service IDs are very commonly written in scan output and docs) or
+/// else plain decimal.
+fn parse_u16_auto(s: &str) -> Result<u16, std::num::ParseIntError> {
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u16::from_str_radix(hex, 16),
+        None => s.parse(),
+    }
+}
+
+/// Same as [`parse_u16_auto`], for `u64` fields such as frequency.
+fn parse_u64_auto(s: &str) -> Result<u64, std::num::ParseIntError> {
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => s.parse(),
+    }
+}
+
+/// Bare (unit-less) frequencies at or above this are assumed to already be
+/// in Hz; every real DVB-T/C/S/ATSC transponder frequency is well above it.
+const BARE_FREQUENCY_HZ_FLOOR: u64 = 100_000_000;
+/// Bare frequencies below this are assumed to be in MHz rather than kHz
+/// (typical transponder frequencies run from tens to tens of thousands of
+/// MHz, but only into the hundreds of thousands of kHz).
+const BARE_FREQUENCY_MHZ_CEILING: u64 = 100_000;
+
+/// Parse a channel-file frequency field, normalizing it to Hz. Accepts a
+/// bare number (optionally `0x`-prefixed hex, see [`parse_u64_auto`]) or one
+/// suffixed with `Hz`/`kHz`/`MHz`/`GHz` (case-insensitive, optional space
+/// before the unit) as satellite/cable tooling commonly writes, e.g.
+/// `"557 MHz"` or `"11727000kHz"`.
+///
+/// A bare number with no unit is assumed to already be Hz if it's at least
+/// [`BARE_FREQUENCY_HZ_FLOOR`], MHz if it's below
+/// [`BARE_FREQUENCY_MHZ_CEILING`], and kHz otherwise — terrestrial scan
+/// files write Hz, while satellite/cable tooling in the wild tends to write
+/// kHz or occasionally bare MHz, and the three ranges don't overlap for any
+/// real transponder frequency.
+fn parse_frequency(s: &str) -> Result<u64, String> {
+    let trimmed = s.trim();
+    let lower = trimmed.to_ascii_lowercase();
+
+    let (digits, multiplier) = if let Some(n) = lower.strip_suffix("ghz") {
+        (n, 1_000_000_000u64)
+    } else if let Some(n) = lower.strip_suffix("mhz") {
+        (n, 1_000_000u64)
+    } else if let Some(n) = lower.strip_suffix("khz") {
+        (n, 1_000u64)
+    } else if let Some(n) = lower.strip_suffix("hz") {
+        (n, 1u64)
+    } else {
+        let value = parse_u64_auto(trimmed).map_err(|e| format!("invalid frequency '{trimmed}': {e}"))?;
+        return Ok(if value >= BARE_FREQUENCY_HZ_FLOOR {
+            value
+        } else if value < BARE_FREQUENCY_MHZ_CEILING {
+            value * 1_000_000
+        } else {
+            value * 1_000
+        });
+    };
+
+    let digits = digits.trim();
+    let value: f64 = digits
+        .parse()
+        .map_err(|_| format!("invalid frequency '{trimmed}'"))?;
+    Ok((value * multiplier as f64).round() as u64)
 }
 
 pub fn parse_channels_conf(path: &Path) -> Result<Vec<Channel>, String> {
@@ -31,47 +266,674 @@ pub fn parse_channels_conf(path: &Path) -> Result<Vec<Channel>, String> {
         }
 
         let fields: Vec<&str> = line.split(':').collect();
-        if fields.len() != 13 {
-            return Err(format!(
-                "Line {}: expected 13 fields, got {}",
-                line_num + 1,
-                fields.len()
-            ));
-        }
-
-        let frequency = fields[1].parse::<u64>().map_err(|e| {
-            format!("Line {}: invalid frequency '{}': {e}", line_num + 1, fields[1])
-        })?;
-        let video_pid = fields[10].parse::<u16>().map_err(|e| {
-            format!("Line {}: invalid video PID '{}': {e}", line_num + 1, fields[10])
-        })?;
-        let audio_pid = fields[11].parse::<u16>().map_err(|e| {
-            format!("Line {}: invalid audio PID '{}': {e}", line_num + 1, fields[11])
-        })?;
-        let service_id = fields[12].parse::<u16>().map_err(|e| {
-            format!("Line {}: invalid service ID '{}': {e}", line_num + 1, fields[12])
-        })?;
-
-        channels.push(Channel {
-            name: fields[0].to_string(),
-            frequency,
+        channels.push(parse_zap_line(&fields, line_num + 1)?);
+    }
+
+    Ok(channels)
+}
+
+/// Dispatch one zap-style colon-separated line to the parser matching its
+/// delivery system, detected from field count and, where counts overlap,
+/// the shape of the fields themselves: DVB-T carries 13 fields including a
+/// `BANDWIDTH_*` token, DVB-C carries a numeric symbol-rate field, DVB-S
+/// opens with a bare `h`/`v` polarization letter, and ATSC has far fewer
+/// fields than any of the others (no FEC/bandwidth/symbol-rate parameters).
+fn parse_zap_line(fields: &[&str], line_num: usize) -> Result<Channel, String> {
+    match fields.len() {
+        13 => parse_zap_dvbt_line(fields, line_num),
+        9 => parse_zap_dvbc_line(fields, line_num),
+        8 => parse_zap_dvbs_line(fields, line_num),
+        6 => parse_zap_atsc_line(fields, line_num),
+        n => Err(format!(
+            "Line {line_num}: unrecognized channel line with {n} fields"
+        )),
+    }
+}
+
+fn parse_zap_dvbt_line(fields: &[&str], line_num: usize) -> Result<Channel, String> {
+    if !fields[3].starts_with("BANDWIDTH_") {
+        return Err(format!(
+            "Line {line_num}: expected a DVB-T line (13 fields with a BANDWIDTH_* token), got '{}'",
+            fields[3]
+        ));
+    }
+
+    let frequency = parse_frequency(fields[1]).map_err(|e| format!("Line {line_num}: {e}"))?;
+    let video_pid = parse_u16_auto(fields[10])
+        .map_err(|e| format!("Line {line_num}: invalid video PID '{}': {e}", fields[10]))?;
+    let audio_pid = parse_u16_auto(fields[11])
+        .map_err(|e| format!("Line {line_num}: invalid audio PID '{}': {e}", fields[11]))?;
+    let service_id = parse_u16_auto(fields[12])
+        .map_err(|e| format!("Line {line_num}: invalid service ID '{}': {e}", fields[12]))?;
+
+    Ok(Channel {
+        name: fields[0].to_string(),
+        frequency,
+        video_pid,
+        audio_pid,
+        service_id,
+        tuning: Tuning::DvbT {
+            inversion: parse_zap_token(fields[2], line_num)?,
+            bandwidth: parse_zap_token(fields[3], line_num)?,
+            fec_hp: parse_zap_token(fields[4], line_num)?,
+            fec_lp: parse_zap_token(fields[5], line_num)?,
+            modulation: parse_zap_token(fields[6], line_num)?,
+            transmission_mode: parse_zap_token(fields[7], line_num)?,
+            guard_interval: parse_zap_token(fields[8], line_num)?,
+            hierarchy: parse_zap_token(fields[9], line_num)?,
+        },
+        elementary_streams: Vec::new(),
+    })
+}
+
+/// Parse one colon-separated field as a validated zap-token enum, prefixing
+/// an unknown-token error with the line number like every other
+/// `parse_zap_*_line` field error.
+fn parse_zap_token<T: std::str::FromStr<Err = String>>(field: &str, line_num: usize) -> Result<T, String> {
+    field.parse().map_err(|e| format!("Line {line_num}: {e}"))
+}
+
+/// Parse a token some other internal conversion table (`crate::scan`'s
+/// `dvbv5_to_zap_*` helpers, `vdr_code_to_zap`) already produced. Every
+/// table it is used with only ever emits one of the canonical tokens the
+/// enum recognizes; modulation is parsed separately, since those tables
+/// also emit QPSK, which DVB-T's [`Modulation`] has no variant for.
+fn expect_zap_token<T: std::str::FromStr<Err = String>>(token: String) -> T {
+    token
+        .parse()
+        .expect("internal conversion tables only ever emit a canonical zap token")
+}
+
+/// `Name:Frequency:Inversion:SymbolRate:FEC:Modulation:VideoPid:AudioPid:ServiceId`
+fn parse_zap_dvbc_line(fields: &[&str], line_num: usize) -> Result<Channel, String> {
+    let frequency = parse_frequency(fields[1]).map_err(|e| format!("Line {line_num}: {e}"))?;
+    let symbol_rate = fields[3]
+        .parse::<u64>()
+        .map_err(|e| format!("Line {line_num}: invalid symbol rate '{}': {e}", fields[3]))?;
+    let video_pid = parse_u16_auto(fields[6])
+        .map_err(|e| format!("Line {line_num}: invalid video PID '{}': {e}", fields[6]))?;
+    let audio_pid = parse_u16_auto(fields[7])
+        .map_err(|e| format!("Line {line_num}: invalid audio PID '{}': {e}", fields[7]))?;
+    let service_id = parse_u16_auto(fields[8])
+        .map_err(|e| format!("Line {line_num}: invalid service ID '{}': {e}", fields[8]))?;
+
+    Ok(Channel {
+        name: fields[0].to_string(),
+        frequency,
+        video_pid,
+        audio_pid,
+        service_id,
+        tuning: Tuning::DvbC {
             inversion: fields[2].to_string(),
-            bandwidth: fields[3].to_string(),
-            fec_hp: fields[4].to_string(),
-            fec_lp: fields[5].to_string(),
-            modulation: fields[6].to_string(),
-            transmission_mode: fields[7].to_string(),
-            guard_interval: fields[8].to_string(),
-            hierarchy: fields[9].to_string(),
-            video_pid,
-            audio_pid,
-            service_id,
-        });
+            symbol_rate,
+            fec: fields[4].to_string(),
+            modulation: fields[5].to_string(),
+        },
+        elementary_streams: Vec::new(),
+    })
+}
+
+/// `Name:Frequency:Polarization:SymbolRate:FEC:VideoPid:AudioPid:ServiceId`
+fn parse_zap_dvbs_line(fields: &[&str], line_num: usize) -> Result<Channel, String> {
+    if !matches!(fields[2].to_ascii_lowercase().as_str(), "h" | "v" | "l" | "r") {
+        return Err(format!(
+            "Line {line_num}: expected a DVB-S line (8 fields with a h/v polarization), got '{}'",
+            fields[2]
+        ));
+    }
+
+    let frequency = parse_frequency(fields[1]).map_err(|e| format!("Line {line_num}: {e}"))?;
+    let symbol_rate = fields[3]
+        .parse::<u64>()
+        .map_err(|e| format!("Line {line_num}: invalid symbol rate '{}': {e}", fields[3]))?;
+    let video_pid = parse_u16_auto(fields[5])
+        .map_err(|e| format!("Line {line_num}: invalid video PID '{}': {e}", fields[5]))?;
+    let audio_pid = parse_u16_auto(fields[6])
+        .map_err(|e| format!("Line {line_num}: invalid audio PID '{}': {e}", fields[6]))?;
+    let service_id = parse_u16_auto(fields[7])
+        .map_err(|e| format!("Line {line_num}: invalid service ID '{}': {e}", fields[7]))?;
+
+    Ok(Channel {
+        name: fields[0].to_string(),
+        frequency,
+        video_pid,
+        audio_pid,
+        service_id,
+        tuning: Tuning::DvbS {
+            polarization: fields[2].to_ascii_uppercase(),
+            symbol_rate,
+            fec: fields[4].to_string(),
+            // Not present in the 8-field zap line format; QPSK is the
+            // universal default for pre-DVB-S2 szap-style channel lists.
+            modulation: "QPSK".to_string(),
+            satellite_position: String::new(),
+            diseqc_port: 0,
+        },
+        elementary_streams: Vec::new(),
+    })
+}
+
+/// `Name:Frequency:Modulation:VideoPid:AudioPid:ServiceId`
+fn parse_zap_atsc_line(fields: &[&str], line_num: usize) -> Result<Channel, String> {
+    let frequency = parse_frequency(fields[1]).map_err(|e| format!("Line {line_num}: {e}"))?;
+    let video_pid = parse_u16_auto(fields[3])
+        .map_err(|e| format!("Line {line_num}: invalid video PID '{}': {e}", fields[3]))?;
+    let audio_pid = parse_u16_auto(fields[4])
+        .map_err(|e| format!("Line {line_num}: invalid audio PID '{}': {e}", fields[4]))?;
+    let service_id = parse_u16_auto(fields[5])
+        .map_err(|e| format!("Line {line_num}: invalid service ID '{}': {e}", fields[5]))?;
+
+    Ok(Channel {
+        name: fields[0].to_string(),
+        frequency,
+        video_pid,
+        audio_pid,
+        service_id,
+        tuning: Tuning::Atsc {
+            modulation: fields[2].to_string(),
+        },
+        elementary_streams: Vec::new(),
+    })
+}
+
+/// Parse a channel list file, auto-detecting whether it's the legacy
+/// colon-separated zap format ([`parse_channels_conf`]) or the libdvbv5 INI
+/// format v4l-utils' `dvbv5-scan`/`dvbv5-zap` emit ([`parse_dvbv5_conf`]):
+/// sniffed by whether the first non-blank, non-comment line opens a
+/// `[Section]` header.
+pub fn parse_channel_list(path: &Path) -> Result<Vec<Channel>, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+
+    let is_dvbv5 = content
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .is_some_and(|line| line.starts_with('['));
+
+    if is_dvbv5 {
+        parse_dvbv5_conf(path)
+    } else {
+        parse_channels_conf(path)
+    }
+}
+
+/// Build a `Channel` from one `[Channel Name]` section's accumulated
+/// `KEY = VALUE` fields, converting dvbv5 vocabulary (`QAM/64`, `8K`, `1/8`,
+/// `NONE`, ...) into the zap-style strings the rest of the crate expects via
+/// the same `dvbv5_to_zap_*` helpers [`crate::scan::ScanEntry::to_channel`]
+/// uses. The section's `DELIVERY_SYSTEM` key selects which [`Tuning`]
+/// variant to build, defaulting to `DvbT` when absent (most dvbv5 files
+/// predate multi-system support and only ever carried terrestrial channels).
+fn dvbv5_conf_channel(name: &str, fields: &HashMap<String, String>) -> Result<Channel, String> {
+    let get = |key: &str| -> Result<&str, String> {
+        fields
+            .get(key)
+            .map(|s| s.as_str())
+            .ok_or_else(|| format!("Section [{name}]: missing {key}"))
+    };
+    let parse_u64 = |key: &str| -> Result<u64, String> {
+        let value = get(key)?;
+        parse_u64_auto(value).map_err(|e| format!("Section [{name}]: invalid {key} '{value}': {e}"))
+    };
+    let parse_u16 = |key: &str| -> Result<u16, String> {
+        let value = get(key)?;
+        parse_u16_auto(value).map_err(|e| format!("Section [{name}]: invalid {key} '{value}': {e}"))
+    };
+
+    let frequency = parse_frequency(get("FREQUENCY")?)
+        .map_err(|e| format!("Section [{name}]: {e}"))?;
+    let video_pid = parse_u16("VIDEO_PID")?;
+    let audio_pid = parse_u16("AUDIO_PID")?;
+    let service_id = parse_u16("SERVICE_ID")?;
+
+    let delivery = fields
+        .get("DELIVERY_SYSTEM")
+        .map(|s| s.to_ascii_uppercase())
+        .unwrap_or_default();
+
+    let tuning = if delivery.starts_with("DVBC") {
+        Tuning::DvbC {
+            inversion: dvbv5_to_zap_inversion(get("INVERSION")?),
+            symbol_rate: fields
+                .get("SYMBOL_RATE")
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0),
+            fec: dvbv5_to_zap_fec(get("CODE_RATE_HP")?),
+            modulation: dvbv5_to_zap_modulation(get("MODULATION")?),
+        }
+    } else if delivery.starts_with("DVBS") {
+        Tuning::DvbS {
+            polarization: fields
+                .get("POLARIZATION")
+                .map(|s| dvbv5_to_zap_polarization(s))
+                .unwrap_or_default(),
+            symbol_rate: fields
+                .get("SYMBOL_RATE")
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0),
+            fec: dvbv5_to_zap_fec(get("CODE_RATE_HP")?),
+            modulation: dvbv5_to_zap_modulation(get("MODULATION")?),
+            satellite_position: fields.get("SATELLITE").cloned().unwrap_or_default(),
+            diseqc_port: fields
+                .get("SAT_NUMBER")
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0),
+        }
+    } else if delivery == "ATSC" {
+        Tuning::Atsc {
+            modulation: dvbv5_to_zap_modulation(get("MODULATION")?),
+        }
+    } else {
+        let bandwidth_hz = parse_u64("BANDWIDTH_HZ")?;
+        Tuning::DvbT {
+            inversion: expect_zap_token(dvbv5_to_zap_inversion(get("INVERSION")?)),
+            bandwidth: expect_zap_token(dvbv5_to_zap_bandwidth(bandwidth_hz)),
+            fec_hp: expect_zap_token(dvbv5_to_zap_fec(get("CODE_RATE_HP")?)),
+            fec_lp: fields
+                .get("CODE_RATE_LP")
+                .map(|s| expect_zap_token(dvbv5_to_zap_fec(s)))
+                .unwrap_or(Fec::Auto),
+            // QPSK/VSB/8PSK have no DVB-T `Modulation` and fall back to QAM_AUTO.
+            modulation: dvbv5_to_zap_modulation(get("MODULATION")?)
+                .parse()
+                .unwrap_or(Modulation::QamAuto),
+            transmission_mode: expect_zap_token(dvbv5_to_zap_transmission(get("TRANSMISSION_MODE")?)),
+            guard_interval: expect_zap_token(dvbv5_to_zap_guard(get("GUARD_INTERVAL")?)),
+            hierarchy: expect_zap_token(dvbv5_to_zap_hierarchy(get("HIERARCHY")?)),
+        }
+    };
+
+    Ok(Channel {
+        name: name.to_string(),
+        frequency,
+        video_pid,
+        audio_pid,
+        service_id,
+        tuning,
+        elementary_streams: Vec::new(),
+    })
+}
+
+/// Parse the libdvbv5 INI-style channel list v4l-utils' `dvbv5-scan` and
+/// `dvbv5-zap` produce: one `[Channel Name]` section per channel followed by
+/// `KEY = VALUE` lines, blank lines separating sections, `#` lines as
+/// comments. Unlike [`parse_channels_conf`]'s fixed field order, keys may
+/// appear in any order within a section; a section missing a required key,
+/// or a line that isn't a comment, section header, or `KEY = VALUE` pair,
+/// is reported by section and key name.
+pub fn parse_dvbv5_conf(path: &Path) -> Result<Vec<Channel>, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+
+    let mut channels = Vec::new();
+    let mut current: Option<(String, HashMap<String, String>)> = None;
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if let Some(name) = line.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+            if let Some((name, fields)) = current.take() {
+                channels.push(dvbv5_conf_channel(&name, &fields)?);
+            }
+            current = Some((name.to_string(), HashMap::new()));
+            continue;
+        }
+
+        let Some((section_name, fields)) = current.as_mut() else {
+            return Err(format!("Line before any [Channel] section: '{line}'"));
+        };
+
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(format!("Section [{section_name}]: malformed line '{line}'"));
+        };
+
+        fields.insert(key.trim().to_string(), value.trim().to_string());
+    }
+
+    if let Some((name, fields)) = current {
+        channels.push(dvbv5_conf_channel(&name, &fields)?);
     }
 
     Ok(channels)
 }
 
+// --- VDR channels.conf format ---
+//
+// VDR stores one channel per line as
+//   Name;Provider:Frequency:Params:Source:SymbolRate:VPID:APID:TPID:CA:SID:NID:TID:RID
+// where the Params field packs the delivery parameters as single-letter codes
+// (e.g. `I999B8C23D0M64T8G8Y0`). This is the service_id-carrying channel list the
+// DVB community standardized on, so emitting it lets epgrab's scan output feed
+// VDR and related tooling without a separate conversion step.
+
+/// Map a canonical zap token to the numeric VDR parameter code, or `None` when
+/// the token has no VDR representation (the code is then omitted).
+fn zap_to_vdr_code(token: &str) -> Option<&'static str> {
+    Some(match token {
+        "INVERSION_OFF" => "0",
+        "INVERSION_ON" => "1",
+        "INVERSION_AUTO" => "999",
+        "BANDWIDTH_5_MHZ" => "5",
+        "BANDWIDTH_6_MHZ" => "6",
+        "BANDWIDTH_7_MHZ" => "7",
+        "BANDWIDTH_8_MHZ" => "8",
+        "BANDWIDTH_10_MHZ" => "10",
+        "BANDWIDTH_1_712_MHZ" => "1712",
+        "BANDWIDTH_AUTO" => "999",
+        "FEC_NONE" => "0",
+        "FEC_1_2" => "12",
+        "FEC_2_3" => "23",
+        "FEC_3_4" => "34",
+        "FEC_4_5" => "45",
+        "FEC_5_6" => "56",
+        "FEC_6_7" => "67",
+        "FEC_7_8" => "78",
+        "FEC_8_9" => "89",
+        "FEC_AUTO" => "999",
+        "QPSK" => "2",
+        "QAM_16" => "16",
+        "QAM_32" => "32",
+        "QAM_64" => "64",
+        "QAM_128" => "128",
+        "QAM_256" => "256",
+        "QAM_AUTO" => "998",
+        "TRANSMISSION_MODE_1K" => "1",
+        "TRANSMISSION_MODE_2K" => "2",
+        "TRANSMISSION_MODE_4K" => "4",
+        "TRANSMISSION_MODE_8K" => "8",
+        "TRANSMISSION_MODE_16K" => "16",
+        "TRANSMISSION_MODE_32K" => "32",
+        "TRANSMISSION_MODE_AUTO" => "999",
+        "GUARD_INTERVAL_1_4" => "4",
+        "GUARD_INTERVAL_1_8" => "8",
+        "GUARD_INTERVAL_1_16" => "16",
+        "GUARD_INTERVAL_1_32" => "32",
+        "GUARD_INTERVAL_AUTO" => "999",
+        "HIERARCHY_NONE" => "0",
+        "HIERARCHY_1" => "1",
+        "HIERARCHY_2" => "2",
+        "HIERARCHY_4" => "4",
+        "HIERARCHY_AUTO" => "999",
+        _ => return None,
+    })
+}
+
+/// Reverse of [`zap_to_vdr_code`] for the given parameter letter.
+fn vdr_code_to_zap(letter: char, code: &str) -> Option<String> {
+    let token = match (letter, code) {
+        ('I', "0") => "INVERSION_OFF",
+        ('I', "1") => "INVERSION_ON",
+        ('I', "999") => "INVERSION_AUTO",
+        ('B', "5") => "BANDWIDTH_5_MHZ",
+        ('B', "6") => "BANDWIDTH_6_MHZ",
+        ('B', "7") => "BANDWIDTH_7_MHZ",
+        ('B', "8") => "BANDWIDTH_8_MHZ",
+        ('B', "10") => "BANDWIDTH_10_MHZ",
+        ('B', "1712") => "BANDWIDTH_1_712_MHZ",
+        ('B', "999") => "BANDWIDTH_AUTO",
+        ('C' | 'D', "0") => "FEC_NONE",
+        ('C' | 'D', "12") => "FEC_1_2",
+        ('C' | 'D', "23") => "FEC_2_3",
+        ('C' | 'D', "34") => "FEC_3_4",
+        ('C' | 'D', "45") => "FEC_4_5",
+        ('C' | 'D', "56") => "FEC_5_6",
+        ('C' | 'D', "67") => "FEC_6_7",
+        ('C' | 'D', "78") => "FEC_7_8",
+        ('C' | 'D', "89") => "FEC_8_9",
+        ('C' | 'D', "999") => "FEC_AUTO",
+        ('M', "2") => "QPSK",
+        ('M', "16") => "QAM_16",
+        ('M', "32") => "QAM_32",
+        ('M', "64") => "QAM_64",
+        ('M', "128") => "QAM_128",
+        ('M', "256") => "QAM_256",
+        ('M', "998") => "QAM_AUTO",
+        ('T', "1") => "TRANSMISSION_MODE_1K",
+        ('T', "2") => "TRANSMISSION_MODE_2K",
+        ('T', "4") => "TRANSMISSION_MODE_4K",
+        ('T', "8") => "TRANSMISSION_MODE_8K",
+        ('T', "16") => "TRANSMISSION_MODE_16K",
+        ('T', "32") => "TRANSMISSION_MODE_32K",
+        ('T', "999") => "TRANSMISSION_MODE_AUTO",
+        ('G', "4") => "GUARD_INTERVAL_1_4",
+        ('G', "8") => "GUARD_INTERVAL_1_8",
+        ('G', "16") => "GUARD_INTERVAL_1_16",
+        ('G', "32") => "GUARD_INTERVAL_1_32",
+        ('G', "999") => "GUARD_INTERVAL_AUTO",
+        ('Y', "0") => "HIERARCHY_NONE",
+        ('Y', "1") => "HIERARCHY_1",
+        ('Y', "2") => "HIERARCHY_2",
+        ('Y', "4") => "HIERARCHY_4",
+        ('Y', "999") => "HIERARCHY_AUTO",
+        _ => return None,
+    };
+    Some(token.to_string())
+}
+
+/// Pack a DVB-T channel's parameters into a VDR Params field; other
+/// delivery systems have no VDR Params encoding here, so they serialize to
+/// an empty field (VDR tolerates an empty Params field as "all auto").
+fn vdr_params(tuning: &Tuning) -> String {
+    let Tuning::DvbT {
+        inversion,
+        bandwidth,
+        fec_hp,
+        fec_lp,
+        modulation,
+        transmission_mode,
+        guard_interval,
+        hierarchy,
+    } = tuning
+    else {
+        return String::new();
+    };
+
+    let mut s = String::new();
+    for (letter, token) in [
+        ('I', inversion.to_string()),
+        ('B', bandwidth.to_string()),
+        ('C', fec_hp.to_string()),
+        ('D', fec_lp.to_string()),
+        ('M', modulation.to_string()),
+        ('T', transmission_mode.to_string()),
+        ('G', guard_interval.to_string()),
+        ('Y', hierarchy.to_string()),
+    ] {
+        if let Some(code) = zap_to_vdr_code(&token) {
+            s.push(letter);
+            s.push_str(code);
+        }
+    }
+    s
+}
+
+/// Split a VDR Params string into (letter, numeric code) pairs.
+fn split_vdr_params(params: &str) -> Vec<(char, String)> {
+    let mut pairs = Vec::new();
+    let mut chars = params.chars().peekable();
+    while let Some(letter) = chars.next() {
+        if !letter.is_ascii_alphabetic() {
+            continue;
+        }
+        let mut code = String::new();
+        while let Some(&c) = chars.peek() {
+            if c.is_ascii_digit() {
+                code.push(c);
+                chars.next();
+            } else {
+                break;
+            }
+        }
+        pairs.push((letter.to_ascii_uppercase(), code));
+    }
+    pairs
+}
+
+/// Serialize a [`Channel`] as a single VDR `channels.conf` line. Source
+/// letter and symbol rate follow the channel's delivery system; only
+/// DVB-T's parameters currently have a VDR Params encoding (see
+/// [`vdr_params`]), so the other systems round-trip PIDs/service ID but not
+/// their tuning parameters.
+pub fn channel_to_vdr_line(ch: &Channel) -> String {
+    let (source, symbol_rate) = match &ch.tuning {
+        Tuning::DvbT { .. } => ('T', 0),
+        Tuning::DvbC { symbol_rate, .. } => ('C', *symbol_rate),
+        Tuning::DvbS { symbol_rate, .. } => ('S', *symbol_rate),
+        Tuning::Atsc { .. } => ('A', 0),
+    };
+
+    // Name;Provider:Frequency:Params:Source:SymbolRate:VPID:APID:TPID:CA:SID:NID:TID:RID
+    format!(
+        "{};{}:{}:{}:{source}:{symbol_rate}:{}:{}:0:0:{}:0:0:0",
+        ch.name,
+        "epgrab",
+        ch.frequency,
+        vdr_params(&ch.tuning),
+        ch.video_pid,
+        ch.audio_pid,
+        ch.service_id,
+    )
+}
+
+/// Parse a single VDR `channels.conf` line into a [`Channel`].
+pub fn parse_vdr_line(line: &str) -> Result<Channel, String> {
+    let fields: Vec<&str> = line.split(':').collect();
+    if fields.len() < 13 {
+        return Err(format!(
+            "VDR line: expected at least 13 colon-separated fields, got {}",
+            fields.len()
+        ));
+    }
+
+    let (name, _provider) = fields[0].split_once(';').unwrap_or((fields[0], ""));
+
+    let frequency = parse_frequency(fields[1]).map_err(|e| format!("VDR line: {e}"))?;
+    let video_pid = fields[5]
+        .split(['+', '='])
+        .next()
+        .unwrap_or(fields[5])
+        .parse::<u16>()
+        .map_err(|e| format!("VDR line: invalid VPID '{}': {e}", fields[5]))?;
+    let audio_pid = fields[6]
+        .split(['+', '=', ';'])
+        .next()
+        .unwrap_or(fields[6])
+        .parse::<u16>()
+        .map_err(|e| format!("VDR line: invalid APID '{}': {e}", fields[6]))?;
+    let service_id = fields[9]
+        .parse::<u16>()
+        .map_err(|e| format!("VDR line: invalid SID '{}': {e}", fields[9]))?;
+
+    let mut inversion = Inversion::Auto;
+    let mut bandwidth = Bandwidth::Auto;
+    let mut fec_hp = Fec::Auto;
+    let mut fec_lp = Fec::Auto;
+    let mut modulation = Modulation::QamAuto;
+    let mut transmission_mode = TransmissionMode::Auto;
+    let mut guard_interval = GuardInterval::Auto;
+    let mut hierarchy = Hierarchy::None;
+
+    for (letter, code) in split_vdr_params(fields[2]) {
+        let Some(token) = vdr_code_to_zap(letter, &code) else {
+            continue;
+        };
+        match letter {
+            'I' => inversion = expect_zap_token(token),
+            'B' => bandwidth = expect_zap_token(token),
+            'C' => fec_hp = expect_zap_token(token),
+            'D' => fec_lp = expect_zap_token(token),
+            // `M2` (QPSK) has no DVB-T `Modulation`; it keeps the default.
+            'M' => modulation = token.parse().unwrap_or(modulation),
+            'T' => transmission_mode = expect_zap_token(token),
+            'G' => guard_interval = expect_zap_token(token),
+            'Y' => hierarchy = expect_zap_token(token),
+            _ => {}
+        }
+    }
+
+    Ok(Channel {
+        name: name.to_string(),
+        frequency,
+        video_pid,
+        audio_pid,
+        service_id,
+        tuning: Tuning::DvbT {
+            inversion,
+            bandwidth,
+            fec_hp,
+            fec_lp,
+            modulation,
+            transmission_mode,
+            guard_interval,
+            hierarchy,
+        },
+        elementary_streams: Vec::new(),
+    })
+}
+
+/// Options controlling [`write_m3u`]'s output.
+pub struct M3uOptions {
+    /// URL template for each channel's stream entry. `{frequency}`,
+    /// `{service_id}`, `{video_pid}`, and `{audio_pid}` placeholders are
+    /// substituted with the channel's values before being written out.
+    pub url_template: String,
+    /// Emit a `tvg-id="<name>"` attribute on each `#EXTINF` line, derived
+    /// from the channel name, for players that match EPG data by tvg-id.
+    pub include_tvg_id: bool,
+    /// Emit a `group-title="<title>"` attribute on each `#EXTINF` line,
+    /// grouping every channel in the playlist under one title.
+    pub group_title: Option<String>,
+}
+
+impl Default for M3uOptions {
+    fn default() -> Self {
+        M3uOptions {
+            url_template: "dvb://{frequency}?service={service_id}&vpid={video_pid}&apid={audio_pid}".to_string(),
+            include_tvg_id: false,
+            group_title: None,
+        }
+    }
+}
+
+/// Substitute `template`'s `{frequency}`/`{service_id}`/`{video_pid}`/
+/// `{audio_pid}` placeholders with `ch`'s values.
+fn expand_m3u_url(template: &str, ch: &Channel) -> String {
+    template
+        .replace("{frequency}", &ch.frequency.to_string())
+        .replace("{service_id}", &ch.service_id.to_string())
+        .replace("{video_pid}", &ch.video_pid.to_string())
+        .replace("{audio_pid}", &ch.audio_pid.to_string())
+}
+
+/// Write `channels` out as a standard `#EXTM3U` playlist: a header line
+/// followed by an `#EXTINF:-1,<name>` / URL pair per channel, so the list
+/// can be handed directly to an IPTV player or transcoder. The stream URL
+/// is built from `options.url_template`; see [`M3uOptions`] for the
+/// optional `tvg-id`/`group-title` attributes.
+pub fn write_m3u(
+    channels: &[Channel],
+    options: &M3uOptions,
+    out: &mut impl std::io::Write,
+) -> std::io::Result<()> {
+    writeln!(out, "#EXTM3U")?;
+    for ch in channels {
+        let mut attrs = String::new();
+        if options.include_tvg_id {
+            attrs.push_str(&format!(" tvg-id=\"{}\"", ch.name));
+        }
+        if let Some(group_title) = &options.group_title {
+            attrs.push_str(&format!(" group-title=\"{group_title}\""));
+        }
+        writeln!(out, "#EXTINF:-1{attrs},{}", ch.name)?;
+        writeln!(out, "{}", expand_m3u_url(&options.url_template, ch))?;
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -83,6 +945,103 @@ mod tests {
         f
     }
 
+    // --- parse_frequency ---
+
+    #[test]
+    fn test_parse_frequency_bare_hz() {
+        assert_eq!(parse_frequency("557000000").unwrap(), 557000000);
+    }
+
+    #[test]
+    fn test_parse_frequency_bare_khz_heuristic() {
+        // Satellite tooling commonly writes transponder frequencies in bare
+        // kHz; below the Hz floor but above the MHz ceiling is assumed kHz.
+        assert_eq!(parse_frequency("11727000").unwrap(), 11727000000);
+    }
+
+    #[test]
+    fn test_parse_frequency_bare_mhz_heuristic() {
+        assert_eq!(parse_frequency("557").unwrap(), 557000000);
+    }
+
+    #[test]
+    fn test_parse_frequency_explicit_hz_suffix() {
+        assert_eq!(parse_frequency("557000000Hz").unwrap(), 557000000);
+        assert_eq!(parse_frequency("557000000 Hz").unwrap(), 557000000);
+    }
+
+    #[test]
+    fn test_parse_frequency_explicit_khz_suffix() {
+        assert_eq!(parse_frequency("557000kHz").unwrap(), 557000000);
+        assert_eq!(parse_frequency("557000 kHz").unwrap(), 557000000);
+    }
+
+    #[test]
+    fn test_parse_frequency_explicit_mhz_suffix() {
+        assert_eq!(parse_frequency("557MHz").unwrap(), 557000000);
+        assert_eq!(parse_frequency("557 MHz").unwrap(), 557000000);
+    }
+
+    #[test]
+    fn test_parse_frequency_explicit_ghz_suffix() {
+        assert_eq!(parse_frequency("11.727GHz").unwrap(), 11727000000);
+    }
+
+    #[test]
+    fn test_parse_frequency_case_insensitive_suffix() {
+        assert_eq!(parse_frequency("557mhz").unwrap(), 557000000);
+        assert_eq!(parse_frequency("557MHZ").unwrap(), 557000000);
+    }
+
+    #[test]
+    fn test_parse_frequency_hex_still_works() {
+        assert_eq!(parse_frequency("0x21534850").unwrap(), 0x21534850);
+    }
+
+    #[test]
+    fn test_parse_frequency_invalid() {
+        assert!(parse_frequency("notanumber").is_err());
+        assert!(parse_frequency("notanumberMHz").is_err());
+    }
+
+    /// Unwrap a channel's tuning as DVB-T, panicking with a useful message
+    /// if it parsed into a different variant.
+    fn as_dvbt(
+        ch: &Channel,
+    ) -> (
+        Inversion,
+        Bandwidth,
+        Fec,
+        Fec,
+        Modulation,
+        TransmissionMode,
+        GuardInterval,
+        Hierarchy,
+    ) {
+        match &ch.tuning {
+            Tuning::DvbT {
+                inversion,
+                bandwidth,
+                fec_hp,
+                fec_lp,
+                modulation,
+                transmission_mode,
+                guard_interval,
+                hierarchy,
+            } => (
+                *inversion,
+                *bandwidth,
+                *fec_hp,
+                *fec_lp,
+                *modulation,
+                *transmission_mode,
+                *guard_interval,
+                *hierarchy,
+            ),
+            other => panic!("expected Tuning::DvbT, got {other:?}"),
+        }
+    }
+
     #[test]
     fn test_parse_channels_conf_valid() {
         let content = "公視:557000000:INVERSION_AUTO:BANDWIDTH_6_MHZ:FEC_AUTO:FEC_AUTO:QAM_64:TRANSMISSION_MODE_8K:GUARD_INTERVAL_1_8:HIERARCHY_NONE:4097:4098:1";
@@ -91,19 +1050,93 @@ mod tests {
         assert_eq!(channels.len(), 1);
         assert_eq!(channels[0].name, "公視");
         assert_eq!(channels[0].frequency, 557000000);
-        assert_eq!(channels[0].inversion, "INVERSION_AUTO");
-        assert_eq!(channels[0].bandwidth, "BANDWIDTH_6_MHZ");
-        assert_eq!(channels[0].fec_hp, "FEC_AUTO");
-        assert_eq!(channels[0].fec_lp, "FEC_AUTO");
-        assert_eq!(channels[0].modulation, "QAM_64");
-        assert_eq!(channels[0].transmission_mode, "TRANSMISSION_MODE_8K");
-        assert_eq!(channels[0].guard_interval, "GUARD_INTERVAL_1_8");
-        assert_eq!(channels[0].hierarchy, "HIERARCHY_NONE");
+        let (inversion, bandwidth, fec_hp, fec_lp, modulation, transmission_mode, guard_interval, hierarchy) =
+            as_dvbt(&channels[0]);
+        assert_eq!(inversion.to_string(), "INVERSION_AUTO");
+        assert_eq!(bandwidth.to_string(), "BANDWIDTH_6_MHZ");
+        assert_eq!(fec_hp.to_string(), "FEC_AUTO");
+        assert_eq!(fec_lp.to_string(), "FEC_AUTO");
+        assert_eq!(modulation.to_string(), "QAM_64");
+        assert_eq!(transmission_mode.to_string(), "TRANSMISSION_MODE_8K");
+        assert_eq!(guard_interval.to_string(), "GUARD_INTERVAL_1_8");
+        assert_eq!(hierarchy.to_string(), "HIERARCHY_NONE");
         assert_eq!(channels[0].video_pid, 4097);
         assert_eq!(channels[0].audio_pid, 4098);
         assert_eq!(channels[0].service_id, 1);
     }
 
+    #[test]
+    fn test_parse_channels_conf_rejects_unknown_token() {
+        let content = "CH1:557000000:INVERSION_AUTO:BANDWIDTH_6_MHZ:FEC_AUTO:FEC_AUTO:QAM_99:TRANSMISSION_MODE_8K:GUARD_INTERVAL_1_8:HIERARCHY_NONE:100:101:1";
+        let f = write_temp_file(content);
+        let err = match parse_channels_conf(f.path()) {
+            Ok(_) => panic!("expected unknown token to be rejected"),
+            Err(e) => e,
+        };
+        assert!(err.contains("QAM_99"));
+    }
+
+    #[test]
+    fn test_parse_channels_conf_dvbc_line() {
+        let content = "CableCH:330000000:INVERSION_AUTO:6900000:FEC_AUTO:QAM_256:100:101:1";
+        let f = write_temp_file(content);
+        let channels = parse_channels_conf(f.path()).unwrap();
+        assert_eq!(channels.len(), 1);
+        match &channels[0].tuning {
+            Tuning::DvbC {
+                inversion,
+                symbol_rate,
+                fec,
+                modulation,
+            } => {
+                assert_eq!(inversion, "INVERSION_AUTO");
+                assert_eq!(*symbol_rate, 6900000);
+                assert_eq!(fec, "FEC_AUTO");
+                assert_eq!(modulation, "QAM_256");
+            }
+            other => panic!("expected Tuning::DvbC, got {other:?}"),
+        }
+        assert_eq!(channels[0].video_pid, 100);
+        assert_eq!(channels[0].audio_pid, 101);
+        assert_eq!(channels[0].service_id, 1);
+    }
+
+    #[test]
+    fn test_parse_channels_conf_dvbs_line() {
+        let content = "SatCH:11727000:v:27500:FEC_3_4:200:201:2";
+        let f = write_temp_file(content);
+        let channels = parse_channels_conf(f.path()).unwrap();
+        assert_eq!(channels.len(), 1);
+        match &channels[0].tuning {
+            Tuning::DvbS {
+                polarization,
+                symbol_rate,
+                fec,
+                ..
+            } => {
+                assert_eq!(polarization, "V");
+                assert_eq!(*symbol_rate, 27500);
+                assert_eq!(fec, "FEC_3_4");
+            }
+            other => panic!("expected Tuning::DvbS, got {other:?}"),
+        }
+        assert_eq!(channels[0].service_id, 2);
+    }
+
+    #[test]
+    fn test_parse_channels_conf_atsc_line() {
+        let content = "AtscCH:563000000:8VSB:300:301:3";
+        let f = write_temp_file(content);
+        let channels = parse_channels_conf(f.path()).unwrap();
+        assert_eq!(channels.len(), 1);
+        match &channels[0].tuning {
+            Tuning::Atsc { modulation } => assert_eq!(modulation, "8VSB"),
+            other => panic!("expected Tuning::Atsc, got {other:?}"),
+        }
+        assert_eq!(channels[0].video_pid, 300);
+        assert_eq!(channels[0].service_id, 3);
+    }
+
     #[test]
     fn test_parse_channels_conf_multiple() {
         let content = "\
@@ -143,6 +1176,24 @@ CH1:557000000:INVERSION_AUTO:BANDWIDTH_6_MHZ:FEC_AUTO:FEC_AUTO:QAM_64:TRANSMISSI
         assert!(parse_channels_conf(f.path()).is_err());
     }
 
+    #[test]
+    fn test_parse_channels_conf_accepts_hex_pids_and_service_id() {
+        let content = "CH1:557000000:INVERSION_AUTO:BANDWIDTH_6_MHZ:FEC_AUTO:FEC_AUTO:QAM_64:TRANSMISSION_MODE_8K:GUARD_INTERVAL_1_8:HIERARCHY_NONE:0x1001:0x1002:0x1";
+        let f = write_temp_file(content);
+        let channels = parse_channels_conf(f.path()).unwrap();
+        assert_eq!(channels[0].video_pid, 0x1001);
+        assert_eq!(channels[0].audio_pid, 0x1002);
+        assert_eq!(channels[0].service_id, 0x1);
+    }
+
+    #[test]
+    fn test_parse_channels_conf_accepts_hex_frequency() {
+        let content = "CH1:0x21534850:INVERSION_AUTO:BANDWIDTH_6_MHZ:FEC_AUTO:FEC_AUTO:QAM_64:TRANSMISSION_MODE_8K:GUARD_INTERVAL_1_8:HIERARCHY_NONE:100:101:1";
+        let f = write_temp_file(content);
+        let channels = parse_channels_conf(f.path()).unwrap();
+        assert_eq!(channels[0].frequency, 0x21534850);
+    }
+
     #[test]
     fn test_parse_channels_conf_invalid_frequency() {
         let content = "CH1:notanumber:INVERSION_AUTO:BANDWIDTH_6_MHZ:FEC_AUTO:FEC_AUTO:QAM_64:TRANSMISSION_MODE_8K:GUARD_INTERVAL_1_8:HIERARCHY_NONE:100:101:1";
@@ -161,4 +1212,301 @@ CH1:557000000:INVERSION_AUTO:BANDWIDTH_6_MHZ:FEC_AUTO:FEC_AUTO:QAM_64:TRANSMISSI
     fn test_parse_channels_conf_nonexistent_file() {
         assert!(parse_channels_conf(Path::new("/nonexistent/path")).is_err());
     }
+
+    // --- VDR format ---
+
+    fn sample_channel() -> Channel {
+        Channel {
+            name: "公視".to_string(),
+            frequency: 557000000,
+            video_pid: 4097,
+            audio_pid: 4098,
+            service_id: 1,
+            tuning: Tuning::DvbT {
+                inversion: Inversion::Auto,
+                bandwidth: Bandwidth::Mhz8,
+                fec_hp: Fec::TwoThirds,
+                fec_lp: Fec::None,
+                modulation: Modulation::Qam64,
+                transmission_mode: TransmissionMode::K8,
+                guard_interval: GuardInterval::Eighth,
+                hierarchy: Hierarchy::None,
+            },
+            elementary_streams: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_channel_to_vdr_line() {
+        let line = channel_to_vdr_line(&sample_channel());
+        assert_eq!(
+            line,
+            "公視;epgrab:557000000:I999B8C23D0M64T8G8Y0:T:0:4097:4098:0:0:1:0:0:0"
+        );
+    }
+
+    #[test]
+    fn test_parse_vdr_line() {
+        let ch = parse_vdr_line(
+            "公視;epgrab:557000000:I999B8C23D0M64T8G8Y0:T:0:4097:4098:0:0:1:0:0:0",
+        )
+        .unwrap();
+        assert_eq!(ch.name, "公視");
+        assert_eq!(ch.frequency, 557000000);
+        let (_, bandwidth, fec_hp, _, modulation, _, _, _) = as_dvbt(&ch);
+        assert_eq!(bandwidth.to_string(), "BANDWIDTH_8_MHZ");
+        assert_eq!(fec_hp.to_string(), "FEC_2_3");
+        assert_eq!(modulation.to_string(), "QAM_64");
+        assert_eq!(ch.service_id, 1);
+        assert_eq!(ch.video_pid, 4097);
+    }
+
+    #[test]
+    fn test_vdr_roundtrip() {
+        let orig = sample_channel();
+        let line = channel_to_vdr_line(&orig);
+        let parsed = parse_vdr_line(&line).unwrap();
+        assert_eq!(parsed.name, orig.name);
+        assert_eq!(parsed.frequency, orig.frequency);
+        assert_eq!(parsed.tuning, orig.tuning);
+        assert_eq!(parsed.service_id, orig.service_id);
+    }
+
+    #[test]
+    fn test_parse_vdr_line_too_few_fields() {
+        assert!(parse_vdr_line("Name;Prov:557000000:T").is_err());
+    }
+
+    #[test]
+    fn test_parse_dvbv5_conf_valid() {
+        let content = "\
+[Das Erste]
+\tDELIVERY_SYSTEM = DVBT
+\tFREQUENCY = 557000000
+\tBANDWIDTH_HZ = 6000000
+\tCODE_RATE_HP = AUTO
+\tCODE_RATE_LP = AUTO
+\tMODULATION = QAM/64
+\tTRANSMISSION_MODE = 8K
+\tGUARD_INTERVAL = 1/8
+\tHIERARCHY = NONE
+\tINVERSION = AUTO
+\tVIDEO_PID = 4097
+\tAUDIO_PID = 4098
+\tSERVICE_ID = 1
+";
+        let f = write_temp_file(content);
+        let channels = parse_dvbv5_conf(f.path()).unwrap();
+        assert_eq!(channels.len(), 1);
+        assert_eq!(channels[0].name, "Das Erste");
+        assert_eq!(channels[0].frequency, 557000000);
+        let (inversion, bandwidth, _, _, modulation, transmission_mode, guard_interval, hierarchy) =
+            as_dvbt(&channels[0]);
+        assert_eq!(bandwidth.to_string(), "BANDWIDTH_6_MHZ");
+        assert_eq!(modulation.to_string(), "QAM_64");
+        assert_eq!(transmission_mode.to_string(), "TRANSMISSION_MODE_8K");
+        assert_eq!(guard_interval.to_string(), "GUARD_INTERVAL_1_8");
+        assert_eq!(hierarchy.to_string(), "HIERARCHY_NONE");
+        assert_eq!(inversion.to_string(), "INVERSION_AUTO");
+        assert_eq!(channels[0].video_pid, 4097);
+        assert_eq!(channels[0].audio_pid, 4098);
+        assert_eq!(channels[0].service_id, 1);
+    }
+
+    #[test]
+    fn test_parse_dvbv5_conf_multiple_sections() {
+        let content = "\
+[CH1]
+FREQUENCY = 557000000
+BANDWIDTH_HZ = 6000000
+CODE_RATE_HP = AUTO
+MODULATION = QAM/64
+TRANSMISSION_MODE = 8K
+GUARD_INTERVAL = 1/8
+HIERARCHY = NONE
+INVERSION = AUTO
+VIDEO_PID = 100
+AUDIO_PID = 101
+SERVICE_ID = 1
+
+[CH2]
+FREQUENCY = 563000000
+BANDWIDTH_HZ = 6000000
+CODE_RATE_HP = AUTO
+MODULATION = QAM/64
+TRANSMISSION_MODE = 8K
+GUARD_INTERVAL = 1/8
+HIERARCHY = NONE
+INVERSION = AUTO
+VIDEO_PID = 200
+AUDIO_PID = 201
+SERVICE_ID = 2
+";
+        let f = write_temp_file(content);
+        let channels = parse_dvbv5_conf(f.path()).unwrap();
+        assert_eq!(channels.len(), 2);
+        assert_eq!(channels[0].name, "CH1");
+        assert_eq!(channels[1].name, "CH2");
+    }
+
+    #[test]
+    fn test_parse_dvbv5_conf_dvbs_section() {
+        let content = "\
+[Astra 19.2E]
+DELIVERY_SYSTEM = DVBS2
+FREQUENCY = 11727000
+SYMBOL_RATE = 27500000
+CODE_RATE_HP = 3/4
+MODULATION = PSK/8
+POLARIZATION = VERTICAL
+SATELLITE = 19.2E
+SAT_NUMBER = 0
+VIDEO_PID = 100
+AUDIO_PID = 101
+SERVICE_ID = 1
+";
+        let f = write_temp_file(content);
+        let channels = parse_dvbv5_conf(f.path()).unwrap();
+        assert_eq!(channels.len(), 1);
+        match &channels[0].tuning {
+            Tuning::DvbS {
+                polarization,
+                symbol_rate,
+                fec,
+                modulation,
+                satellite_position,
+                ..
+            } => {
+                assert_eq!(polarization, "V");
+                assert_eq!(*symbol_rate, 27500000);
+                assert_eq!(fec, "FEC_3_4");
+                assert_eq!(modulation, "PSK_8");
+                assert_eq!(satellite_position, "19.2E");
+            }
+            other => panic!("expected Tuning::DvbS, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_parse_dvbv5_conf_atsc_section() {
+        let content = "\
+[ATSC CH]
+DELIVERY_SYSTEM = ATSC
+FREQUENCY = 563000000
+MODULATION = VSB/8
+VIDEO_PID = 200
+AUDIO_PID = 201
+SERVICE_ID = 2
+";
+        let f = write_temp_file(content);
+        let channels = parse_dvbv5_conf(f.path()).unwrap();
+        assert_eq!(channels.len(), 1);
+        match &channels[0].tuning {
+            Tuning::Atsc { modulation } => assert_eq!(modulation, "8VSB"),
+            other => panic!("expected Tuning::Atsc, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_parse_dvbv5_conf_missing_key_names_section() {
+        let content = "\
+[CH1]
+FREQUENCY = 557000000
+";
+        let f = write_temp_file(content);
+        let err = match parse_dvbv5_conf(f.path()) {
+            Err(e) => e,
+            Ok(_) => panic!("expected an error"),
+        };
+        assert!(err.contains("[CH1]"));
+        assert!(err.contains("VIDEO_PID"));
+    }
+
+    #[test]
+    fn test_parse_channel_list_dispatches_to_dvbv5() {
+        let content = "\
+[CH1]
+FREQUENCY = 557000000
+BANDWIDTH_HZ = 6000000
+CODE_RATE_HP = AUTO
+MODULATION = QAM/64
+TRANSMISSION_MODE = 8K
+GUARD_INTERVAL = 1/8
+HIERARCHY = NONE
+INVERSION = AUTO
+VIDEO_PID = 100
+AUDIO_PID = 101
+SERVICE_ID = 1
+";
+        let f = write_temp_file(content);
+        let channels = parse_channel_list(f.path()).unwrap();
+        assert_eq!(channels.len(), 1);
+        assert_eq!(channels[0].name, "CH1");
+    }
+
+    #[test]
+    fn test_parse_channel_list_dispatches_to_zap() {
+        let content = "CH1:557000000:INVERSION_AUTO:BANDWIDTH_6_MHZ:FEC_AUTO:FEC_AUTO:QAM_64:TRANSMISSION_MODE_8K:GUARD_INTERVAL_1_8:HIERARCHY_NONE:100:101:1";
+        let f = write_temp_file(content);
+        let channels = parse_channel_list(f.path()).unwrap();
+        assert_eq!(channels.len(), 1);
+        assert_eq!(channels[0].name, "CH1");
+    }
+
+    // --- M3U export ---
+
+    #[test]
+    fn test_write_m3u_basic() {
+        let channels = vec![sample_channel()];
+        let mut out = Vec::new();
+        write_m3u(&channels, &M3uOptions::default(), &mut out).unwrap();
+        let content = String::from_utf8(out).unwrap();
+        assert_eq!(
+            content,
+            "#EXTM3U\n#EXTINF:-1,公視\ndvb://557000000?service=1&vpid=4097&apid=4098\n"
+        );
+    }
+
+    #[test]
+    fn test_write_m3u_with_tvg_id_and_group_title() {
+        let channels = vec![sample_channel()];
+        let mut out = Vec::new();
+        let options = M3uOptions {
+            include_tvg_id: true,
+            group_title: Some("Terrestrial".to_string()),
+            ..M3uOptions::default()
+        };
+        write_m3u(&channels, &options, &mut out).unwrap();
+        let content = String::from_utf8(out).unwrap();
+        assert_eq!(
+            content,
+            "#EXTM3U\n#EXTINF:-1 tvg-id=\"公視\" group-title=\"Terrestrial\",公視\ndvb://557000000?service=1&vpid=4097&apid=4098\n"
+        );
+    }
+
+    #[test]
+    fn test_write_m3u_custom_url_template() {
+        let channels = vec![sample_channel()];
+        let mut out = Vec::new();
+        let options = M3uOptions {
+            url_template: "http://example.com/stream?freq={frequency}&sid={service_id}".to_string(),
+            ..M3uOptions::default()
+        };
+        write_m3u(&channels, &options, &mut out).unwrap();
+        let content = String::from_utf8(out).unwrap();
+        assert!(content.contains("http://example.com/stream?freq=557000000&sid=1"));
+    }
+
+    #[test]
+    fn test_write_m3u_multiple_channels() {
+        let mut second = sample_channel();
+        second.name = "CH2".to_string();
+        second.service_id = 2;
+        let channels = vec![sample_channel(), second];
+        let mut out = Vec::new();
+        write_m3u(&channels, &M3uOptions::default(), &mut out).unwrap();
+        let content = String::from_utf8(out).unwrap();
+        assert_eq!(content.matches("#EXTINF").count(), 2);
+        assert!(content.contains("service=2"));
+    }
 }
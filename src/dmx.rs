@@ -1,7 +1,12 @@
+use std::collections::HashMap;
 use std::fs::OpenOptions;
+use std::io::Read;
 use std::os::unix::io::AsRawFd;
+use std::time::Duration;
 
 pub const DMX_FILTER_SIZE: usize = 16;
+pub const DMX_CHECK_CRC: u32 = 0x01;
+pub const DMX_ONESHOT: u32 = 0x02;
 pub const DMX_IMMEDIATE_START: u32 = 4;
 
 #[repr(C)]
@@ -19,10 +24,210 @@ pub struct DmxSctFilterParams {
     pub flags: u32,
 }
 
+// dmx_input_t
+pub const DMX_IN_FRONTEND: u32 = 0;
+
+// dmx_output_t
+pub const DMX_OUT_TAP: u32 = 1;
+pub const DMX_OUT_TS_TAP: u32 = 2;
+
+// dmx_pes_type_t
+pub const DMX_PES_VIDEO: u32 = 0;
+pub const DMX_PES_AUDIO: u32 = 1;
+pub const DMX_PES_OTHER: u32 = 5;
+
+#[repr(C)]
+pub struct DmxPesFilterParams {
+    pub pid: u16,
+    pub input: u32,
+    pub output: u32,
+    pub pes_type: u32,
+    pub flags: u32,
+}
+
+/// `dmx_input_t`: where the demux reads its stream from.
+pub enum DmxInput {
+    /// Live from the tuned frontend.
+    Frontend,
+    /// From a stream previously written to `/dev/dvb/adapterN/dvr0`.
+    Dvr,
+}
+
+impl DmxInput {
+    fn as_raw(&self) -> u32 {
+        match self {
+            DmxInput::Frontend => DMX_IN_FRONTEND,
+            DmxInput::Dvr => 1,
+        }
+    }
+}
+
+/// `dmx_output_t`: where a PES filter's matching data is delivered.
+pub enum DmxOutput {
+    /// Sent straight to the hardware A/V decoder, bypassing userspace.
+    Decoder,
+    /// Depacketized elementary stream, read back from the demux fd itself
+    /// (see [`open_demux_pes`]).
+    Tap,
+    /// Whole, untouched TS packets for this PID, also read from the demux fd
+    /// (see [`open_demux_ts`]).
+    TsTap,
+    /// Whole TS packets multiplexed with every other active filter's
+    /// output, read from `/dev/dvb/adapterN/dvr0` instead of the demux fd.
+    TsDemuxTap,
+}
+
+impl DmxOutput {
+    fn as_raw(&self) -> u32 {
+        match self {
+            DmxOutput::Decoder => 0,
+            DmxOutput::Tap => DMX_OUT_TAP,
+            DmxOutput::TsTap => DMX_OUT_TS_TAP,
+            DmxOutput::TsDemuxTap => 3,
+        }
+    }
+}
+
+/// `dmx_pes_type_t`: which elementary stream within the program a PES filter
+/// picks out. Values line up with the pre-existing `DMX_PES_*` constants.
+pub enum DmxPesType {
+    Video,
+    Audio,
+    Teletext,
+    Subtitle,
+    Pcr,
+    Other,
+}
+
+impl DmxPesType {
+    fn as_raw(&self) -> u32 {
+        match self {
+            DmxPesType::Video => DMX_PES_VIDEO,
+            DmxPesType::Audio => DMX_PES_AUDIO,
+            DmxPesType::Teletext => 2,
+            DmxPesType::Subtitle => 3,
+            DmxPesType::Pcr => 4,
+            DmxPesType::Other => DMX_PES_OTHER,
+        }
+    }
+}
+
+nix::ioctl_none!(dmx_start, b'o', 41);
+nix::ioctl_none!(dmx_stop, b'o', 42);
 nix::ioctl_write_ptr!(dmx_set_filter, b'o', 43, DmxSctFilterParams);
+nix::ioctl_write_ptr!(dmx_set_pes_filter, b'o', 44, DmxPesFilterParams);
+nix::ioctl_write_int!(dmx_set_buffer_size, b'o', 45);
 
-/// Open the demux device and set a section filter for the given PID.
-pub fn open_demux_with_filter(adapter: u32, pid: u16) -> Result<std::fs::File, String> {
+/// Default kernel-side section buffer size, applied to every filter opened
+/// through [`open_demux_with_filter`] or [`open_demux_with_matcher`]. Busy
+/// PIDs carrying many sections per second (e.g. EIT schedule, see
+/// [`crate::eit`]) can overflow the kernel's default buffer between reads and
+/// silently drop sections; callers expecting that need a larger explicit
+/// size instead (see [`open_demux_with_filter_and_buffer_size`]).
+pub const DMX_DEFAULT_BUFFER_SIZE: usize = 256 * 1024;
+
+/// Builds a `DmxFilter` one section-byte offset at a time, so callers don't
+/// have to hand-assemble the `filter`/`mask` arrays themselves.
+///
+/// Kernel semantics per offset `i`: the byte passes when
+/// `(section[i] & mask[i]) == (filter[i] & mask[i])`. The kernel also
+/// supports inverting individual bytes into a not-equal test via `mode`, but
+/// nothing in this codebase needs that yet, so `mode` is left zeroed; add it
+/// back here if a caller comes along that does.
+pub struct SectionMatcher {
+    filter: [u8; DMX_FILTER_SIZE],
+    mask: [u8; DMX_FILTER_SIZE],
+}
+
+impl SectionMatcher {
+    pub fn new() -> Self {
+        SectionMatcher {
+            filter: [0u8; DMX_FILTER_SIZE],
+            mask: [0u8; DMX_FILTER_SIZE],
+        }
+    }
+
+    /// Match section byte `offset` against `value` under `mask`.
+    pub fn byte(mut self, offset: usize, value: u8, mask: u8) -> Self {
+        self.filter[offset] = value;
+        self.mask[offset] = mask;
+        self
+    }
+
+    fn into_dmx_filter(self) -> DmxFilter {
+        DmxFilter {
+            filter: self.filter,
+            mask: self.mask,
+            mode: [0u8; DMX_FILTER_SIZE],
+        }
+    }
+}
+
+impl Default for SectionMatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Section-filter behavior beyond PID/table-id matching: how long the kernel
+/// should wait for a match, the buffer it holds delivered sections in, and
+/// which `DMX_SET_FILTER` flags to enable.
+pub struct DmxFilterOptions {
+    /// Kernel-side section buffer size, passed to `DMX_SET_BUFFER_SIZE`.
+    pub buffer_size: usize,
+    /// How long the kernel waits for a matching section before giving up
+    /// with no data. `Duration::ZERO` means no timeout (wait forever).
+    pub timeout: Duration,
+    /// `DMX_CHECK_CRC`: have the kernel validate each section's trailing
+    /// CRC-32 and drop sections that fail, instead of delivering them for
+    /// userspace to check (as [`crate::scan::crc32_mpeg2`] does today).
+    pub check_crc: bool,
+    /// `DMX_ONESHOT`: stop after the first matching section instead of
+    /// continuing to deliver every one that matches. Ideal for reading a
+    /// PAT/PMT/SDT once rather than looping until `read_all_sections`
+    /// assembles every segment of a multi-section table.
+    pub oneshot: bool,
+    /// `DMX_IMMEDIATE_START`: start delivering data as soon as the filter is
+    /// set, rather than waiting for an explicit start. [`DemuxSession`]
+    /// disables this so several filters can be configured before any of
+    /// them is released to run, via [`DemuxSession::start`].
+    pub immediate_start: bool,
+}
+
+impl Default for DmxFilterOptions {
+    fn default() -> Self {
+        DmxFilterOptions {
+            buffer_size: DMX_DEFAULT_BUFFER_SIZE,
+            timeout: Duration::ZERO,
+            check_crc: false,
+            oneshot: false,
+            immediate_start: true,
+        }
+    }
+}
+
+impl DmxFilterOptions {
+    fn flags(&self) -> u32 {
+        let mut flags = 0;
+        if self.immediate_start {
+            flags |= DMX_IMMEDIATE_START;
+        }
+        if self.check_crc {
+            flags |= DMX_CHECK_CRC;
+        }
+        if self.oneshot {
+            flags |= DMX_ONESHOT;
+        }
+        flags
+    }
+}
+
+fn open_demux_sct(
+    adapter: u32,
+    pid: u16,
+    filter: DmxFilter,
+    options: &DmxFilterOptions,
+) -> Result<std::fs::File, String> {
     let path = format!("/dev/dvb/adapter{adapter}/demux0");
     let demux_file = OpenOptions::new()
         .read(true)
@@ -32,20 +237,546 @@ pub fn open_demux_with_filter(adapter: u32, pid: u16) -> Result<std::fs::File, S
 
     let fd = demux_file.as_raw_fd();
 
+    // Must be set before DMX_SET_FILTER: the kernel sizes the section buffer
+    // at filter-start time, so a later resize wouldn't apply retroactively.
+    unsafe {
+        dmx_set_buffer_size(fd, options.buffer_size)
+            .map_err(|e| format!("DMX_SET_BUFFER_SIZE failed: {e}"))?;
+    }
+
     let params = DmxSctFilterParams {
         pid,
-        filter: DmxFilter {
-            filter: [0u8; DMX_FILTER_SIZE],
-            mask: [0u8; DMX_FILTER_SIZE],
-            mode: [0u8; DMX_FILTER_SIZE],
+        filter,
+        timeout: options.timeout.as_millis() as u32,
+        flags: options.flags(),
+    };
+
+    unsafe {
+        dmx_set_filter(fd, &params).map_err(|e| format!("DMX_SET_FILTER failed: {e}"))?;
+    }
+
+    Ok(demux_file)
+}
+
+/// Build the [`SectionMatcher`] shared by [`open_demux_with_filter`] and
+/// [`open_demux_with_filter_and_buffer_size`], so the table-id/table-id-ext
+/// shorthand has one definition regardless of which buffer size the caller
+/// wants.
+fn table_id_matcher(table_id: Option<u8>, table_id_ext: Option<u16>) -> SectionMatcher {
+    let mut matcher = SectionMatcher::new();
+
+    if let Some(id) = table_id {
+        matcher = matcher.byte(0, id, 0xFF);
+    }
+
+    if let Some(ext) = table_id_ext {
+        let [hi, lo] = ext.to_be_bytes();
+        matcher = matcher.byte(3, hi, 0xFF).byte(4, lo, 0xFF);
+    }
+
+    matcher
+}
+
+/// Open the demux device and set a section filter for the given PID.
+///
+/// `table_id`, if given, is pushed down to the kernel: filter byte 0 is
+/// matched against it exactly, so only sections with that `table_id` wake
+/// up the reader. `table_id_ext`, if given, additionally matches filter
+/// bytes 3-4 against the section's `table_id_extension` (the PMT's
+/// `program_number` / the EIT's `service_id`), letting the kernel isolate
+/// one service's sections on a PID that multiplexes several. Pass `None`
+/// for either to leave the corresponding bytes unfiltered (`mask = 0`),
+/// e.g. when a PID carries more than one `table_id` of interest. For
+/// anything beyond a straight equality match on those two fields (arbitrary
+/// offsets), use [`open_demux_with_matcher`] instead.
+pub fn open_demux_with_filter(
+    adapter: u32,
+    pid: u16,
+    table_id: Option<u8>,
+    table_id_ext: Option<u16>,
+) -> Result<std::fs::File, String> {
+    open_demux_with_options(
+        adapter,
+        pid,
+        table_id,
+        table_id_ext,
+        DmxFilterOptions::default(),
+    )
+}
+
+/// Same as [`open_demux_with_filter`], but with an explicit kernel-side
+/// section buffer size instead of [`DMX_DEFAULT_BUFFER_SIZE`]. Use this for
+/// PIDs that emit sections faster than the caller can drain them between
+/// reads — e.g. the EIT schedule PID, see [`crate::eit`] — to avoid the
+/// kernel dropping sections once its default buffer fills up.
+pub fn open_demux_with_filter_and_buffer_size(
+    adapter: u32,
+    pid: u16,
+    table_id: Option<u8>,
+    table_id_ext: Option<u16>,
+    buffer_size: usize,
+) -> Result<std::fs::File, String> {
+    open_demux_with_options(
+        adapter,
+        pid,
+        table_id,
+        table_id_ext,
+        DmxFilterOptions {
+            buffer_size,
+            ..Default::default()
         },
-        timeout: 0,
+    )
+}
+
+/// Open the demux device and set a section filter for the given PID, with
+/// full control over [`DmxFilterOptions`] (buffer size, timeout, CRC
+/// checking, one-shot delivery) alongside the table-id/table-id-extension
+/// shorthand. [`open_demux_with_filter`] and
+/// [`open_demux_with_filter_and_buffer_size`] are thin convenience wrappers
+/// over this for the common cases.
+pub fn open_demux_with_options(
+    adapter: u32,
+    pid: u16,
+    table_id: Option<u8>,
+    table_id_ext: Option<u16>,
+    options: DmxFilterOptions,
+) -> Result<std::fs::File, String> {
+    let matcher = table_id_matcher(table_id, table_id_ext);
+    open_demux_sct(adapter, pid, matcher.into_dmx_filter(), &options)
+}
+
+/// Open the demux device with a caller-built [`SectionMatcher`], for filters
+/// [`open_demux_with_filter`]'s table-id/table-id-extension shorthand can't
+/// express — e.g. matching against an arbitrary section byte offset instead
+/// of just `table_id`/`table_id_extension`.
+pub fn open_demux_with_matcher(
+    adapter: u32,
+    pid: u16,
+    matcher: SectionMatcher,
+) -> Result<std::fs::File, String> {
+    open_demux_sct(
+        adapter,
+        pid,
+        matcher.into_dmx_filter(),
+        &DmxFilterOptions::default(),
+    )
+}
+
+/// Open one demux filter per requested `table_id` on `pid`, each delivering
+/// only sections of that exact table ID.
+///
+/// The section-filter model can only express "match" via per-byte
+/// bitmasking against a single `filter`/`mask` pair (see
+/// [`open_demux_with_filter`]), so there's no way to OR together an
+/// arbitrary set of table IDs in one hardware filter — each needs its own.
+/// Returns an error if more IDs are requested than there are bytes in
+/// [`DMX_FILTER_SIZE`] to hand out filters for.
+pub fn open_demux_with_table_ids(
+    adapter: u32,
+    pid: u16,
+    table_ids: &[u8],
+) -> Result<Vec<std::fs::File>, String> {
+    if table_ids.len() > DMX_FILTER_SIZE {
+        return Err(format!(
+            "Requested {} table IDs but at most {DMX_FILTER_SIZE} filters can be opened per PID",
+            table_ids.len()
+        ));
+    }
+
+    table_ids
+        .iter()
+        .map(|&table_id| open_demux_with_filter(adapter, pid, Some(table_id), None))
+        .collect()
+}
+
+/// Open the demux device in PES mode for the given PID, routing the raw
+/// elementary stream onto the device itself (`DMX_OUT_TAP`) so it can be
+/// read back with plain `read()` calls, as recording mode does.
+pub fn open_demux_pes(adapter: u32, pid: u16, pes_type: u32) -> Result<std::fs::File, String> {
+    let path = format!("/dev/dvb/adapter{adapter}/demux0");
+    let demux_file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open {path}: {e}"))?;
+
+    let fd = demux_file.as_raw_fd();
+
+    let params = DmxPesFilterParams {
+        pid,
+        input: DMX_IN_FRONTEND,
+        output: DMX_OUT_TAP,
+        pes_type,
         flags: DMX_IMMEDIATE_START,
     };
 
     unsafe {
-        dmx_set_filter(fd, &params).map_err(|e| format!("DMX_SET_FILTER failed: {e}"))?;
+        dmx_set_pes_filter(fd, &params).map_err(|e| format!("DMX_SET_PES_FILTER failed: {e}"))?;
+    }
+
+    Ok(demux_file)
+}
+
+/// Open the demux device in raw TS passthrough mode (`DMX_OUT_TS_TAP`) for the
+/// given PID: unlike [`open_demux_pes`], which hands back a depacketized
+/// elementary stream, this delivers whole, untouched 188-byte TS packets
+/// (still carrying their original PID and continuity counter), suitable for
+/// re-muxing into a standalone transport stream alongside synthesized PAT/PMT
+/// packets.
+pub fn open_demux_ts(adapter: u32, pid: u16) -> Result<std::fs::File, String> {
+    let path = format!("/dev/dvb/adapter{adapter}/demux0");
+    let demux_file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open {path}: {e}"))?;
+
+    let fd = demux_file.as_raw_fd();
+
+    let params = DmxPesFilterParams {
+        pid,
+        input: DMX_IN_FRONTEND,
+        output: DMX_OUT_TS_TAP,
+        pes_type: DMX_PES_OTHER,
+        flags: DMX_IMMEDIATE_START,
+    };
+
+    unsafe {
+        dmx_set_pes_filter(fd, &params).map_err(|e| format!("DMX_SET_PES_FILTER failed: {e}"))?;
     }
 
     Ok(demux_file)
 }
+
+/// Open the demux device with a `DMX_SET_PES_FILTER` filter, for callers that
+/// need the full [`DmxOutput`] range rather than [`open_demux_pes`]'s fixed
+/// `Tap` output or [`open_demux_ts`]'s fixed `TsTap`. Input is always
+/// [`DmxInput::Frontend`], matching every other filter constructor in this
+/// module — live capture, not DVR loopback.
+///
+/// `DmxOutput::TsTap`/`TsDemuxTap` deliver whole, untouched TS packets;
+/// `TsDemuxTap` multiplexes them with every other active filter's output and
+/// delivers them via `/dev/dvb/adapterN/dvr0` instead of the demux fd, so
+/// this returns the `dvr0` file in that case. `Tap` and `TsTap` are read
+/// back from the demux fd returned here; for `Decoder` the demux fd is
+/// returned too, but its data goes to the hardware decoder instead.
+pub fn open_demux_with_pes_filter(
+    adapter: u32,
+    pid: u16,
+    pes_type: DmxPesType,
+    output: DmxOutput,
+) -> Result<std::fs::File, String> {
+    let demux_path = format!("/dev/dvb/adapter{adapter}/demux0");
+    let demux_file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(&demux_path)
+        .map_err(|e| format!("Failed to open {demux_path}: {e}"))?;
+
+    let fd = demux_file.as_raw_fd();
+
+    let params = DmxPesFilterParams {
+        pid,
+        input: DmxInput::Frontend.as_raw(),
+        output: output.as_raw(),
+        pes_type: pes_type.as_raw(),
+        flags: DMX_IMMEDIATE_START,
+    };
+
+    unsafe {
+        dmx_set_pes_filter(fd, &params).map_err(|e| format!("DMX_SET_PES_FILTER failed: {e}"))?;
+    }
+
+    if matches!(output, DmxOutput::TsDemuxTap) {
+        let dvr_path = format!("/dev/dvb/adapter{adapter}/dvr0");
+        OpenOptions::new()
+            .read(true)
+            .open(&dvr_path)
+            .map_err(|e| format!("Failed to open {dvr_path}: {e}"))
+    } else {
+        Ok(demux_file)
+    }
+}
+
+/// Handle returned by [`DemuxSession::add_section_filter`], used later to
+/// [`DemuxSession::remove_filter`] it or pick which filter to
+/// [`DemuxSession::read_section`] from.
+pub type FilterId = usize;
+
+/// Owns every section filter opened for one EPG grab, so the caller doesn't
+/// have to track a `File`/`RawFd` per PID by hand. Filters added via
+/// [`add_section_filter`](Self::add_section_filter) sit idle (no
+/// `DMX_IMMEDIATE_START`) until [`start`](Self::start) issues `DMX_START` on
+/// every one of them at once, so e.g. EIT, SDT, and TDT/TOT filters can all
+/// be configured up front and then released together for one coherent EPG
+/// snapshot instead of racing each other as they're set up one at a time.
+pub struct DemuxSession {
+    adapter: u32,
+    next_id: FilterId,
+    filters: HashMap<FilterId, std::fs::File>,
+}
+
+impl DemuxSession {
+    pub fn new(adapter: u32) -> Self {
+        DemuxSession {
+            adapter,
+            next_id: 0,
+            filters: HashMap::new(),
+        }
+    }
+
+    /// Open a new section filter for `pid` matching `matcher`, idle until
+    /// [`start`](Self::start) is called. Returns a [`FilterId`] identifying
+    /// it for later [`remove_filter`](Self::remove_filter)/
+    /// [`read_section`](Self::read_section) calls.
+    pub fn add_section_filter(
+        &mut self,
+        pid: u16,
+        matcher: SectionMatcher,
+    ) -> Result<FilterId, String> {
+        self.add_section_filter_with_buffer_size(pid, matcher, DMX_DEFAULT_BUFFER_SIZE)
+    }
+
+    /// Same as [`add_section_filter`](Self::add_section_filter), but with an
+    /// explicit kernel-side section buffer size instead of
+    /// [`DMX_DEFAULT_BUFFER_SIZE`] — see
+    /// [`open_demux_with_filter_and_buffer_size`] for when that matters.
+    pub fn add_section_filter_with_buffer_size(
+        &mut self,
+        pid: u16,
+        matcher: SectionMatcher,
+        buffer_size: usize,
+    ) -> Result<FilterId, String> {
+        let options = DmxFilterOptions {
+            buffer_size,
+            immediate_start: false,
+            ..Default::default()
+        };
+        let file = open_demux_sct(self.adapter, pid, matcher.into_dmx_filter(), &options)?;
+        let id = self.next_id;
+        self.next_id += 1;
+        self.filters.insert(id, file);
+        Ok(id)
+    }
+
+    /// Close the filter identified by `id` and stop tracking it.
+    pub fn remove_filter(&mut self, id: FilterId) -> Result<(), String> {
+        self.filters
+            .remove(&id)
+            .map(|_| ())
+            .ok_or_else(|| format!("No filter with id {id}"))
+    }
+
+    /// Raw fd of the filter identified by `id`, for polling with `poll(2)`
+    /// ahead of [`read_section`](Self::read_section).
+    pub fn filter_fd(&self, id: FilterId) -> Result<std::os::unix::io::RawFd, String> {
+        self.filters
+            .get(&id)
+            .map(|file| file.as_raw_fd())
+            .ok_or_else(|| format!("No filter with id {id}"))
+    }
+
+    /// Issue `DMX_START` on every tracked filter, releasing data that was
+    /// held back while filters were still being configured.
+    pub fn start(&mut self) -> Result<(), String> {
+        for file in self.filters.values() {
+            unsafe {
+                dmx_start(file.as_raw_fd()).map_err(|e| format!("DMX_START failed: {e}"))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Issue `DMX_STOP` on every tracked filter.
+    pub fn stop(&mut self) -> Result<(), String> {
+        for file in self.filters.values() {
+            unsafe {
+                dmx_stop(file.as_raw_fd()).map_err(|e| format!("DMX_STOP failed: {e}"))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Read one complete section from `id`'s filter into `buf` (cleared and
+    /// repopulated), via a single `read()` call.
+    ///
+    /// DVB section-filter devices are record-oriented: one `read()` delivers
+    /// at most one complete section, and anything beyond what the caller's
+    /// buffer can hold is discarded — a second `read()` starts the *next*
+    /// section rather than continuing the first. So unlike a byte stream,
+    /// this can't be split into a 3-byte header read followed by a
+    /// `section_length`-sized body read; both must come out of the same
+    /// call, exactly like [`crate::scan::read_all_sections`]. The section
+    /// header's `section_length` (12 bits, the low nibble of byte 1 plus all
+    /// of byte 2) gives the exact byte count following it, which is used
+    /// only to trim the buffer down to the section actually delivered.
+    /// Returns the total section length, header included.
+    pub fn read_section(&mut self, id: FilterId, buf: &mut Vec<u8>) -> Result<usize, String> {
+        let file = self
+            .filters
+            .get_mut(&id)
+            .ok_or_else(|| format!("No filter with id {id}"))?;
+
+        let mut raw = [0u8; 4096];
+        let n = file
+            .read(&mut raw)
+            .map_err(|e| format!("Failed to read section: {e}"))?;
+        let bounds = section_bounds(&raw[..n])?;
+
+        buf.clear();
+        buf.extend_from_slice(&raw[bounds]);
+        Ok(buf.len())
+    }
+}
+
+/// Find the byte range (header included) that one complete section occupies
+/// within `raw` — the bytes a single `read()` of a demux fd actually
+/// returned. Split out of [`DemuxSession::read_section`] so the
+/// length-driven reassembly math can be unit-tested without a real `/dev/dvb`
+/// fd to read from.
+fn section_bounds(raw: &[u8]) -> Result<std::ops::Range<usize>, String> {
+    let n = raw.len();
+    if n < 3 {
+        return Err(format!("Section read returned only {n} bytes, too short for a header"));
+    }
+
+    let section_length = (((raw[1] & 0x0F) as usize) << 8) | raw[2] as usize;
+    let section_end = 3 + section_length;
+    if section_end > n {
+        return Err(format!(
+            "Section claims length {section_length} but read only returned {n} bytes"
+        ));
+    }
+
+    Ok(0..section_end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // --- SectionMatcher ---
+
+    #[test]
+    fn test_section_matcher_byte_sets_filter_and_mask() {
+        let matcher = SectionMatcher::new().byte(0, 0x42, 0xFF);
+        let filter = matcher.into_dmx_filter();
+        assert_eq!(filter.filter[0], 0x42);
+        assert_eq!(filter.mask[0], 0xFF);
+        assert_eq!(filter.mode[0], 0);
+    }
+
+    #[test]
+    fn test_section_matcher_byte_composes_multiple_offsets() {
+        let matcher = SectionMatcher::new()
+            .byte(0, 0x4E, 0xFF)
+            .byte(3, 0x12, 0xFF)
+            .byte(4, 0x34, 0xFF);
+        let filter = matcher.into_dmx_filter();
+        assert_eq!(filter.filter[0], 0x4E);
+        assert_eq!(filter.filter[3], 0x12);
+        assert_eq!(filter.filter[4], 0x34);
+        assert_eq!(filter.mask[1], 0);
+    }
+
+    #[test]
+    fn test_section_matcher_default_is_unfiltered() {
+        let filter = SectionMatcher::new().into_dmx_filter();
+        assert_eq!(filter.mask, [0u8; DMX_FILTER_SIZE]);
+        assert_eq!(filter.mode, [0u8; DMX_FILTER_SIZE]);
+    }
+
+    // --- table_id_matcher ---
+
+    #[test]
+    fn test_table_id_matcher_none_leaves_everything_unfiltered() {
+        let filter = table_id_matcher(None, None).into_dmx_filter();
+        assert_eq!(filter.mask, [0u8; DMX_FILTER_SIZE]);
+    }
+
+    #[test]
+    fn test_table_id_matcher_table_id_only() {
+        let filter = table_id_matcher(Some(0x42), None).into_dmx_filter();
+        assert_eq!(filter.filter[0], 0x42);
+        assert_eq!(filter.mask[0], 0xFF);
+        assert_eq!(filter.mask[3], 0);
+        assert_eq!(filter.mask[4], 0);
+    }
+
+    #[test]
+    fn test_table_id_matcher_table_id_and_ext() {
+        let filter = table_id_matcher(Some(0x4E), Some(0x1234)).into_dmx_filter();
+        assert_eq!(filter.filter[0], 0x4E);
+        assert_eq!(filter.filter[3], 0x12);
+        assert_eq!(filter.filter[4], 0x34);
+        assert_eq!(filter.mask[0], 0xFF);
+        assert_eq!(filter.mask[3], 0xFF);
+        assert_eq!(filter.mask[4], 0xFF);
+    }
+
+    // --- DmxFilterOptions::flags ---
+
+    #[test]
+    fn test_dmx_filter_options_default_flags_is_immediate_start_only() {
+        let options = DmxFilterOptions::default();
+        assert_eq!(options.flags(), DMX_IMMEDIATE_START);
+    }
+
+    #[test]
+    fn test_dmx_filter_options_flags_compose() {
+        let options = DmxFilterOptions {
+            immediate_start: true,
+            check_crc: true,
+            oneshot: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            options.flags(),
+            DMX_IMMEDIATE_START | DMX_CHECK_CRC | DMX_ONESHOT
+        );
+    }
+
+    #[test]
+    fn test_dmx_filter_options_flags_none_set() {
+        let options = DmxFilterOptions {
+            immediate_start: false,
+            check_crc: false,
+            oneshot: false,
+            ..Default::default()
+        };
+        assert_eq!(options.flags(), 0);
+    }
+
+    // --- section_bounds ---
+
+    #[test]
+    fn test_section_bounds_exact_length() {
+        let mut raw = vec![0x4E, 0x00, 0x02, 0xAA, 0xBB];
+        raw.resize(4096, 0);
+        let bounds = section_bounds(&raw).unwrap();
+        assert_eq!(bounds, 0..5);
+    }
+
+    #[test]
+    fn test_section_bounds_trims_trailing_garbage_from_next_section() {
+        // A real read() returns one section followed by whatever was left in
+        // the kernel's last buffer slot; section_bounds must trim to just the
+        // section_length-declared span, not the whole read.
+        let mut raw = vec![0x4E, 0x00, 0x02, 0xAA, 0xBB, 0xFF, 0xFF, 0xFF];
+        raw.truncate(8);
+        let bounds = section_bounds(&raw).unwrap();
+        assert_eq!(bounds, 0..5);
+    }
+
+    #[test]
+    fn test_section_bounds_rejects_header_too_short() {
+        assert!(section_bounds(&[0x4E, 0x00]).is_err());
+    }
+
+    #[test]
+    fn test_section_bounds_rejects_truncated_body() {
+        // section_length claims 2 bytes follow the header, but the read only
+        // returned the 3-byte header itself.
+        assert!(section_bounds(&[0x4E, 0x00, 0x02]).is_err());
+    }
+}
@@ -2,6 +2,8 @@ use std::fs;
 use std::io::{BufRead, BufReader};
 use std::path::Path;
 
+use crate::tuner::{self, FrontendType};
+
 const USB_IDS_PATHS: &[&str] = &[
     "/usr/share/misc/usb.ids",
     "/usr/share/hwdata/usb.ids",
@@ -13,6 +15,16 @@ pub struct DvbDevice {
     pub device_id: String,
     pub vendor_name: Option<String>,
     pub product_name: Option<String>,
+    pub tuner_type: Option<FrontendType>,
+}
+
+/// Extract the adapter number from a `dvbN.frontendM` entry name, e.g.
+/// `"dvb0.frontend0"` -> `Some(0)`.
+fn adapter_number(adapter_name: &str) -> Option<u32> {
+    adapter_name
+        .strip_prefix("dvb")
+        .and_then(|s| s.split('.').next())
+        .and_then(|s| s.parse().ok())
 }
 
 fn find_usb_parent(path: &Path) -> Option<(String, String)> {
@@ -105,12 +117,17 @@ pub fn detect_devices() -> Vec<DvbDevice> {
 
         if let Some((vendor_id, device_id)) = find_usb_parent(&real_path) {
             let (vendor_name, product_name) = lookup_usb_names(&vendor_id, &device_id);
+            let tuner_type = adapter_number(&name_str)
+                .and_then(|adapter| tuner::Tuner::open(adapter).ok())
+                .and_then(|t| t.info().ok())
+                .map(|info| info.fe_type);
             devices.push(DvbDevice {
                 adapter_name: name_str,
                 vendor_id,
                 device_id,
                 vendor_name,
                 product_name,
+                tuner_type,
             });
         }
     }
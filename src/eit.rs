@@ -1,14 +1,20 @@
-use std::io::Read;
-use std::os::unix::io::AsRawFd;
 use std::time::Instant;
 
 use nix::poll::{poll, PollFd, PollFlags, PollTimeout};
 
+use crate::cache::EpgCache;
 use crate::dmx;
+use crate::{debug, warn};
 
 // EIT PID
 const EIT_PID: u16 = 0x12;
 
+/// Kernel-side section buffer for the EIT demux filter. The EIT PID carries
+/// schedule data for the whole multiplex at a high section rate, so it gets
+/// a larger buffer than [`dmx::DMX_DEFAULT_BUFFER_SIZE`] to avoid dropping
+/// sections when `read_events` can't drain it between polls.
+const EIT_DEMUX_BUFFER_SIZE: usize = 1024 * 1024;
+
 // EIT table IDs
 const EIT_PRESENT_FOLLOWING_ACTUAL: u8 = 0x4E;
 const EIT_SCHEDULE_ACTUAL_MIN: u8 = 0x50;
@@ -16,6 +22,8 @@ const EIT_SCHEDULE_ACTUAL_MAX: u8 = 0x5F;
 
 // Short event descriptor tag
 const SHORT_EVENT_DESCRIPTOR: u8 = 0x4D;
+// Extended event descriptor tag
+const EXTENDED_EVENT_DESCRIPTOR: u8 = 0x4E;
 
 #[allow(dead_code)]
 pub struct EitEvent {
@@ -153,6 +161,38 @@ fn parse_short_event_descriptor(data: &[u8]) -> (String, String, String) {
     (language, event_name, description)
 }
 
+/// Parse an extended-event descriptor's item text, returning the
+/// descriptor_number (for reassembling text split across several
+/// descriptors) and the decoded text.
+fn parse_extended_event_descriptor(data: &[u8]) -> (u8, String) {
+    // descriptor_number:4, last_descriptor_number:4
+    // ISO_639_language_code: 3 bytes
+    // length_of_items: 1 byte, items: length_of_items bytes
+    // text_length: 1 byte, text: text_length bytes
+    if data.len() < 5 {
+        return (0, String::new());
+    }
+
+    let descriptor_number = (data[0] >> 4) & 0x0F;
+    let items_length = data[4] as usize;
+    let text_length_offset = 5 + items_length;
+
+    if data.len() < text_length_offset + 1 {
+        return (descriptor_number, String::new());
+    }
+
+    let text_length = data[text_length_offset] as usize;
+    let text_offset = text_length_offset + 1;
+
+    let text = if data.len() >= text_offset + text_length {
+        decode_dvb_text(&data[text_offset..text_offset + text_length])
+    } else {
+        String::new()
+    };
+
+    (descriptor_number, text)
+}
+
 fn parse_eit_event(data: &[u8], service_id: u16) -> Result<(EitEvent, usize), String> {
     if data.len() < 12 {
         return Err("Event data too short".to_string());
@@ -181,6 +221,7 @@ fn parse_eit_event(data: &[u8], service_id: u16) -> Result<(EitEvent, usize), St
     let mut language = String::new();
     let mut event_name = String::new();
     let mut description = String::new();
+    let mut extended_parts: Vec<(u8, String)> = Vec::new();
 
     let desc_data = &data[12..12 + descriptors_length];
     let mut pos = 0;
@@ -190,16 +231,30 @@ fn parse_eit_event(data: &[u8], service_id: u16) -> Result<(EitEvent, usize), St
         if pos + 2 + len > desc_data.len() {
             break;
         }
+        let desc_bytes = &desc_data[pos + 2..pos + 2 + len];
         if tag == SHORT_EVENT_DESCRIPTOR {
-            let desc_bytes = &desc_data[pos + 2..pos + 2 + len];
             let (lang, name, desc) = parse_short_event_descriptor(desc_bytes);
             language = lang;
             event_name = name;
             description = desc;
+        } else if tag == EXTENDED_EVENT_DESCRIPTOR {
+            extended_parts.push(parse_extended_event_descriptor(desc_bytes));
         }
         pos += 2 + len;
     }
 
+    if !extended_parts.is_empty() {
+        extended_parts.sort_by_key(|(number, _)| *number);
+        let extended_text: String = extended_parts.into_iter().map(|(_, text)| text).collect();
+        if !extended_text.is_empty() {
+            description = if description.is_empty() {
+                extended_text
+            } else {
+                format!("{description}\n{extended_text}")
+            };
+        }
+    }
+
     Ok((
         EitEvent {
             service_id,
@@ -255,7 +310,7 @@ fn parse_eit_section(buf: &[u8]) -> Result<(u16, Vec<EitEvent>), String> {
                 pos += consumed;
             }
             Err(e) => {
-                eprintln!("Warning: failed to parse EIT event at offset {pos}: {e}");
+                warn!("failed to parse EIT event at offset {pos}: {e}");
                 break;
             }
         }
@@ -505,6 +560,39 @@ mod tests {
         assert_eq!(desc, "");
     }
 
+    // --- parse_extended_event_descriptor ---
+
+    #[test]
+    fn test_parse_extended_event_descriptor_valid() {
+        let data = [
+            0x00, // descriptor_number=0, last_descriptor_number=0
+            b'e', b'n', b'g', // language
+            0,    // length_of_items = 0
+            5,    // text_length
+            b'H', b'e', b'l', b'l', b'o', // text (default encoding)
+        ];
+        let (number, text) = parse_extended_event_descriptor(&data);
+        assert_eq!(number, 0);
+        assert_eq!(text, "Hello");
+    }
+
+    #[test]
+    fn test_parse_extended_event_descriptor_too_short() {
+        let data = [0x00, b'e', b'n']; // missing language byte + length_of_items
+        let (_, text) = parse_extended_event_descriptor(&data);
+        assert_eq!(text, "");
+    }
+
+    #[test]
+    fn test_parse_extended_event_descriptor_number() {
+        let data = [
+            0x21, // descriptor_number=2, last_descriptor_number=1
+            b'e', b'n', b'g', 0, 0,
+        ];
+        let (number, _) = parse_extended_event_descriptor(&data);
+        assert_eq!(number, 2);
+    }
+
     // --- parse_eit_event ---
 
     #[test]
@@ -575,6 +663,38 @@ mod tests {
         assert_eq!(consumed, 12 + full_desc_len);
     }
 
+    #[test]
+    fn test_parse_eit_event_with_extended_descriptor() {
+        let mjd: u16 = 51544;
+        let mjd_bytes = mjd.to_be_bytes();
+
+        // short event: language "eng", name "Test", text "Short"
+        let short_content = [
+            b'e', b'n', b'g', 4, b'T', b'e', b's', b't', 5, b'S', b'h', b'o', b'r', b't',
+        ];
+        // extended event: descriptor_number=0, language "eng", no items, text "More"
+        let extended_content = [0x00, b'e', b'n', b'g', 0, 4, b'M', b'o', b'r', b'e'];
+
+        let mut data = vec![
+            0x00, 0x43, // event_id
+            mjd_bytes[0], mjd_bytes[1], 0x10, 0x00, 0x00, // start_time
+            0x00, 0x30, 0x00, // duration: 30m
+        ];
+        let descriptors_length = 2 + short_content.len() + 2 + extended_content.len();
+        data.push((descriptors_length >> 8) as u8 & 0x0F); // running_status=0
+        data.push(descriptors_length as u8);
+        data.push(SHORT_EVENT_DESCRIPTOR);
+        data.push(short_content.len() as u8);
+        data.extend_from_slice(&short_content);
+        data.push(EXTENDED_EVENT_DESCRIPTOR);
+        data.push(extended_content.len() as u8);
+        data.extend_from_slice(&extended_content);
+
+        let (event, _) = parse_eit_event(&data, 1).unwrap();
+        assert_eq!(event.event_name, "Test");
+        assert_eq!(event.description, "Short\nMore");
+    }
+
     #[test]
     fn test_parse_eit_event_rejects_unreasonable_duration() {
         let mjd: u16 = 51544;
@@ -629,24 +749,85 @@ mod tests {
         data[7] = 5; // last_section_number = 5, invalid for 0x4E
         assert!(parse_eit_section(&data).is_err());
     }
+
+    // --- group_events_by_service ---
+
+    fn sample_event(service_id: u16, event_id: u16) -> EitEvent {
+        EitEvent {
+            service_id,
+            event_id,
+            start_time: 0,
+            duration: 0,
+            running_status: 0,
+            event_name: String::new(),
+            description: String::new(),
+            language: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_group_events_by_service_splits_by_service_id() {
+        let events = vec![
+            sample_event(1, 100),
+            sample_event(2, 200),
+            sample_event(1, 101),
+        ];
+        let grouped = group_events_by_service(events);
+        assert_eq!(grouped.len(), 2);
+        assert_eq!(grouped[&1].len(), 2);
+        assert_eq!(grouped[&2].len(), 1);
+        assert_eq!(grouped[&1][0].event_id, 100);
+        assert_eq!(grouped[&1][1].event_id, 101);
+    }
+
+    #[test]
+    fn test_group_events_by_service_empty() {
+        let grouped = group_events_by_service(Vec::new());
+        assert!(grouped.is_empty());
+    }
+}
+
+/// Group a flat list of events by the service_id (channel) they belong to,
+/// so downstream XMLTV generation can look up one channel's events without
+/// scanning the whole list per channel.
+pub fn group_events_by_service(events: Vec<EitEvent>) -> std::collections::BTreeMap<u16, Vec<EitEvent>> {
+    let mut grouped: std::collections::BTreeMap<u16, Vec<EitEvent>> = std::collections::BTreeMap::new();
+    for event in events {
+        grouped.entry(event.service_id).or_default().push(event);
+    }
+    grouped
 }
 
 pub struct EitReader {
-    demux_file: std::fs::File,
+    session: dmx::DemuxSession,
+    filter_id: dmx::FilterId,
 }
 
 impl EitReader {
     /// Open the demux device and set up the EIT section filter.
     pub fn open(adapter: u32) -> Result<Self, String> {
-        let demux_file = dmx::open_demux_with_filter(adapter, EIT_PID)?;
-        Ok(EitReader { demux_file })
+        // The EIT PID carries both present/following (0x4E) and schedule
+        // (0x50-0x5F) tables, so no single kernel-side table_id filter
+        // covers everything we want; the userspace check below stays in
+        // charge of picking matching sections out of the unfiltered PID.
+        // Left unfiltered, this PID is also one of the busiest on the
+        // multiplex, so it gets a larger-than-default kernel buffer to avoid
+        // overflowing between reads.
+        let mut session = dmx::DemuxSession::new(adapter);
+        let filter_id = session.add_section_filter_with_buffer_size(
+            EIT_PID,
+            dmx::SectionMatcher::new(),
+            EIT_DEMUX_BUFFER_SIZE,
+        )?;
+        session.start()?;
+        Ok(EitReader { session, filter_id })
     }
 
     /// Read EIT sections for the given timeout duration.
     pub fn read_events(&mut self, timeout_secs: u64) -> Result<Vec<EitEvent>, String> {
-        let fd = self.demux_file.as_raw_fd();
+        let fd = self.session.filter_fd(self.filter_id)?;
         let mut all_events = Vec::new();
-        let mut section_buf = [0u8; 4096];
+        let mut section_buf = Vec::new();
         let start = Instant::now();
         let timeout = std::time::Duration::from_secs(timeout_secs);
         let mut seen_sections: std::collections::HashSet<(u16, u8, u8)> = std::collections::HashSet::new();
@@ -677,10 +858,10 @@ impl EitReader {
                 continue; // keep trying until overall timeout
             }
 
-            let n = match self.demux_file.read(&mut section_buf) {
-                Ok(n) => n,
-                Err(_) => continue,
-            };
+            if self.session.read_section(self.filter_id, &mut section_buf).is_err() {
+                continue;
+            }
+            let n = section_buf.len();
 
             if n < 18 {
                 continue;
@@ -721,4 +902,72 @@ impl EitReader {
         all_events.sort_by_key(|e| e.start_time);
         Ok(all_events)
     }
+
+    /// Like [`EitReader::read_events`], but merges events directly into a
+    /// persistent [`EpgCache`] instead of returning them. Sections are
+    /// deduplicated by the CRC32 of their raw bytes rather than by
+    /// `(service_id, table_id, section_number)`, so a version bump that
+    /// reuses the same section number still gets reprocessed while identical
+    /// repeats of the same content (the common case — EIT sections repeat
+    /// constantly on the wire) are skipped without reparsing. Returns how
+    /// many events were newly inserted or upgraded in the cache.
+    pub fn read_into_cache(&mut self, timeout_secs: u64, cache: &mut EpgCache) -> Result<usize, String> {
+        let fd = self.session.filter_fd(self.filter_id)?;
+        let mut merged = 0;
+        let mut section_buf = Vec::new();
+        let start = Instant::now();
+        let timeout = std::time::Duration::from_secs(timeout_secs);
+
+        while start.elapsed() < timeout {
+            let remaining_ms = timeout
+                .checked_sub(start.elapsed())
+                .unwrap_or_default()
+                .as_millis() as i32;
+
+            if remaining_ms <= 0 {
+                break;
+            }
+
+            let poll_ms = remaining_ms.min(5000);
+            let poll_fd = PollFd::new(
+                unsafe { std::os::unix::io::BorrowedFd::borrow_raw(fd) },
+                PollFlags::POLLIN,
+            );
+            let poll_timeout = PollTimeout::try_from(poll_ms).unwrap_or(PollTimeout::NONE);
+            let nfds = poll(&mut [poll_fd], poll_timeout)
+                .map_err(|e| format!("poll failed: {e}"))?;
+
+            if nfds == 0 {
+                continue;
+            }
+
+            if self.session.read_section(self.filter_id, &mut section_buf).is_err() {
+                continue;
+            }
+            let n = section_buf.len();
+
+            if n < 18 {
+                continue;
+            }
+
+            let table_id = section_buf[0];
+            let is_pf = table_id == EIT_PRESENT_FOLLOWING_ACTUAL;
+            let is_sched = (EIT_SCHEDULE_ACTUAL_MIN..=EIT_SCHEDULE_ACTUAL_MAX).contains(&table_id);
+            if !is_pf && !is_sched {
+                continue;
+            }
+
+            let version_number = (section_buf[5] >> 1) & 0x1F;
+            if let Ok((sid, events)) = parse_eit_section(&section_buf) {
+                let inserted = cache.ingest_section(&section_buf, table_id, version_number, events);
+                debug!(
+                    "section table_id={table_id:#04x} version={version_number} sid={sid} {} bytes -> {inserted} new/updated",
+                    n,
+                );
+                merged += inserted;
+            }
+        }
+
+        Ok(merged)
+    }
 }
@@ -0,0 +1,268 @@
+// --- Live HLS tune-and-stream sessions ---
+//
+// Turns a tuned channel into a live HLS media playlist: a background thread
+// runs `remux::remux_to_hls` against the channel's PIDs, cutting numbered
+// `seg<N>.ts` files to disk, while `SegmentWindow` tracks the sliding set of
+// recently cut segments that `to_media_playlist` renders on demand. The HTTP
+// server (`main.rs`) owns one `LiveSession` per actively-watched service id
+// and reads from it without blocking on the capture thread.
+
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+use crate::channel::Channel;
+use crate::error;
+use crate::remux;
+use crate::tuner;
+
+/// Segments kept in the live sliding window, matching `ffmpeg`'s usual
+/// `-hls_list_size` default: long enough to ride out a brief client stall
+/// without turning the playlist into a DVR-style archive.
+const WINDOW_SIZE: usize = 6;
+const MIN_SEGMENT_SECS: f64 = 2.0;
+const MAX_SEGMENT_SECS: f64 = 4.0;
+
+/// One cut segment in a live HLS sliding window: its sequence number and
+/// wall-clock duration, used to render `#EXTINF` lines.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Segment {
+    pub index: u64,
+    pub duration_secs: f64,
+}
+
+/// Fixed-size sliding window of the most recently cut segments for a live
+/// HLS stream: once `capacity` segments are held, pushing another drops the
+/// oldest and the media sequence advances to match.
+pub struct SegmentWindow {
+    capacity: usize,
+    segments: VecDeque<Segment>,
+}
+
+impl SegmentWindow {
+    pub fn new(capacity: usize) -> Self {
+        SegmentWindow {
+            capacity: capacity.max(1),
+            segments: VecDeque::new(),
+        }
+    }
+
+    /// Add a newly cut segment, dropping the oldest if the window is full.
+    /// Returns the dropped segment's index, if any, so the caller can remove
+    /// its file from disk.
+    pub fn push(&mut self, segment: Segment) -> Option<u64> {
+        self.segments.push_back(segment);
+        if self.segments.len() > self.capacity {
+            self.segments.pop_front().map(|s| s.index)
+        } else {
+            None
+        }
+    }
+
+    fn media_sequence(&self) -> u64 {
+        self.segments.front().map(|s| s.index).unwrap_or(0)
+    }
+
+    fn target_duration_secs(&self) -> u64 {
+        self.segments
+            .iter()
+            .map(|s| s.duration_secs.ceil() as u64)
+            .max()
+            .unwrap_or(1)
+            .max(1)
+    }
+
+    /// Render the live media playlist for the window's current contents.
+    /// `#EXT-X-ENDLIST` is deliberately omitted: the stream keeps producing
+    /// new segments for as long as the session runs.
+    pub fn to_media_playlist(&self) -> String {
+        let mut m3u8 = String::new();
+        m3u8.push_str("#EXTM3U\n");
+        m3u8.push_str("#EXT-X-VERSION:3\n");
+        m3u8.push_str(&format!("#EXT-X-TARGETDURATION:{}\n", self.target_duration_secs()));
+        m3u8.push_str(&format!("#EXT-X-MEDIA-SEQUENCE:{}\n", self.media_sequence()));
+        for seg in &self.segments {
+            m3u8.push_str(&format!("#EXTINF:{:.3},\n", seg.duration_secs));
+            m3u8.push_str(&format!("seg{}.ts\n", seg.index));
+        }
+        m3u8
+    }
+}
+
+/// One channel's live tune-and-segment session: a background thread runs
+/// [`remux::remux_to_hls`] against the tuned PIDs, cutting `seg<N>.ts` files
+/// into `segment_dir` and updating `window` as each one closes, while HTTP
+/// requests read `window`/`segment_dir` without blocking on the capture.
+pub struct LiveSession {
+    segment_dir: PathBuf,
+    window: Arc<Mutex<SegmentWindow>>,
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+    // Kept alive so the frontend stays tuned for as long as the session
+    // runs; never read directly once tuning has been performed.
+    _frontend: tuner::Tuner,
+}
+
+impl LiveSession {
+    /// Tune `frontend` to `channel` must already have been done by the
+    /// caller; this just takes ownership of it so the tuned frontend fd
+    /// outlives the capture thread, and starts cutting segments into
+    /// `segment_dir`.
+    pub fn start(
+        adapter: u32,
+        frontend: tuner::Tuner,
+        channel: Channel,
+        segment_dir: PathBuf,
+    ) -> Result<LiveSession, String> {
+        std::fs::create_dir_all(&segment_dir)
+            .map_err(|e| format!("Failed to create {}: {e}", segment_dir.display()))?;
+
+        let window = Arc::new(Mutex::new(SegmentWindow::new(WINDOW_SIZE)));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let thread_window = Arc::clone(&window);
+        let thread_stop = Arc::clone(&stop);
+        let thread_dir = segment_dir.clone();
+        let handle = std::thread::spawn(move || {
+            let result = remux::remux_to_hls(
+                adapter,
+                &channel,
+                &thread_dir,
+                MIN_SEGMENT_SECS,
+                MAX_SEGMENT_SECS,
+                &thread_stop,
+                |index, duration_secs| {
+                    let dropped = thread_window.lock().unwrap().push(Segment { index, duration_secs });
+                    if let Some(old_index) = dropped {
+                        let _ = std::fs::remove_file(thread_dir.join(format!("seg{old_index}.ts")));
+                    }
+                },
+            );
+            if let Err(e) = result {
+                error!("live capture for {}: {e}", channel.name);
+            }
+        });
+
+        Ok(LiveSession {
+            segment_dir,
+            window,
+            stop,
+            handle: Some(handle),
+            _frontend: frontend,
+        })
+    }
+
+    /// Render the current live media playlist for this session.
+    pub fn playlist(&self) -> String {
+        self.window.lock().unwrap().to_media_playlist()
+    }
+
+    /// Path a segment's bytes should be read from to serve `GET
+    /// /live/<sid>/seg<index>.ts`.
+    pub fn segment_path(&self, index: u64) -> PathBuf {
+        self.segment_dir.join(format!("seg{index}.ts"))
+    }
+}
+
+impl Drop for LiveSession {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+        let _ = std::fs::remove_dir_all(&self.segment_dir);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_segment_window_empty_playlist_has_no_extinf() {
+        let window = SegmentWindow::new(6);
+        let m3u8 = window.to_media_playlist();
+        assert!(m3u8.starts_with("#EXTM3U\n"));
+        assert!(m3u8.contains("#EXT-X-MEDIA-SEQUENCE:0"));
+        assert!(!m3u8.contains("#EXTINF"));
+        assert!(!m3u8.contains("#EXT-X-ENDLIST"));
+    }
+
+    #[test]
+    fn test_segment_window_push_below_capacity_keeps_all() {
+        let mut window = SegmentWindow::new(6);
+        for i in 0..3 {
+            assert_eq!(
+                window.push(Segment {
+                    index: i,
+                    duration_secs: 2.0
+                }),
+                None
+            );
+        }
+        assert_eq!(window.media_sequence(), 0);
+        assert_eq!(window.segments.len(), 3);
+    }
+
+    #[test]
+    fn test_segment_window_push_past_capacity_drops_oldest() {
+        let mut window = SegmentWindow::new(2);
+        assert_eq!(
+            window.push(Segment {
+                index: 0,
+                duration_secs: 2.0
+            }),
+            None
+        );
+        assert_eq!(
+            window.push(Segment {
+                index: 1,
+                duration_secs: 2.0
+            }),
+            None
+        );
+        assert_eq!(
+            window.push(Segment {
+                index: 2,
+                duration_secs: 2.0
+            }),
+            Some(0)
+        );
+        assert_eq!(window.media_sequence(), 1);
+        assert_eq!(window.segments.len(), 2);
+    }
+
+    #[test]
+    fn test_target_duration_rounds_up_to_longest_segment() {
+        let mut window = SegmentWindow::new(6);
+        window.push(Segment {
+            index: 0,
+            duration_secs: 2.1,
+        });
+        window.push(Segment {
+            index: 1,
+            duration_secs: 3.9,
+        });
+        assert_eq!(window.target_duration_secs(), 4);
+    }
+
+    #[test]
+    fn test_to_media_playlist_lists_segments_in_order() {
+        let mut window = SegmentWindow::new(6);
+        window.push(Segment {
+            index: 5,
+            duration_secs: 2.5,
+        });
+        window.push(Segment {
+            index: 6,
+            duration_secs: 3.25,
+        });
+        let m3u8 = window.to_media_playlist();
+        assert!(m3u8.contains("#EXT-X-MEDIA-SEQUENCE:5\n"));
+        let seg5 = m3u8.find("#EXTINF:2.500,\nseg5.ts\n").unwrap();
+        let seg6 = m3u8.find("#EXTINF:3.250,\nseg6.ts\n").unwrap();
+        assert!(seg5 < seg6);
+    }
+}
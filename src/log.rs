@@ -0,0 +1,218 @@
+// --- Structured, leveled logging ---
+//
+// Every command used to scatter `println!`/`eprintln!` directly, with no way
+// to quiet progress noise or raise diagnostic verbosity. This module gives
+// all of them a single choke point: a global level (set once at startup from
+// `-v`/`-q` flags or the `EPGRAB_LOG` env var) and a handful of macros that
+// route through it. The level names follow libav's callback convention
+// (fatal->error, warning->warn, info->notice, verbose->info, debug->debug),
+// since anyone who has used `-loglevel` on ffmpeg will already know what to
+// expect from `-v`/`-vv` here.
+//
+// `error!`/`warn!` always go to stderr; `notice!`/`info!`/`debug!` go to
+// stdout and are only emitted when the configured level is at least as
+// verbose as the message. A message containing embedded newlines is split
+// so each physical line becomes its own prefixed record.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error = 0,
+    Warn = 1,
+    Notice = 2,
+    Info = 3,
+    Debug = 4,
+}
+
+impl Level {
+    fn prefix(self) -> &'static str {
+        match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Notice => "NOTICE",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Level> {
+        match name.to_ascii_lowercase().as_str() {
+            "error" => Some(Level::Error),
+            "warn" | "warning" => Some(Level::Warn),
+            "notice" => Some(Level::Notice),
+            "info" | "verbose" => Some(Level::Info),
+            "debug" => Some(Level::Debug),
+            _ => None,
+        }
+    }
+}
+
+const DEFAULT_LEVEL: Level = Level::Notice;
+
+static LEVEL: AtomicU8 = AtomicU8::new(DEFAULT_LEVEL as u8);
+
+fn set_level(level: Level) {
+    LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+pub fn current_level() -> Level {
+    match LEVEL.load(Ordering::Relaxed) {
+        0 => Level::Error,
+        1 => Level::Warn,
+        2 => Level::Notice,
+        3 => Level::Info,
+        _ => Level::Debug,
+    }
+}
+
+/// Set the global log level from `EPGRAB_LOG` (if set), then apply one
+/// `-v`/`-q` step per occurrence found in `args`, removing them so the
+/// caller's own flag parsing never sees them. Call once at startup, before
+/// any subcommand dispatch.
+pub fn init(args: &[String]) -> Vec<String> {
+    let base = std::env::var("EPGRAB_LOG")
+        .ok()
+        .and_then(|v| Level::from_name(&v))
+        .unwrap_or(DEFAULT_LEVEL);
+
+    let (level, rest) = apply_verbosity_flags(base, args);
+    set_level(level);
+    rest
+}
+
+/// Pure core of [`init`]: fold `-v`/`-vv`/`-q` occurrences in `args` onto
+/// `base`, returning the resulting level and `args` with those flags
+/// stripped out. Split out so tests can exercise the flag arithmetic
+/// without touching the global level (which every test in this binary
+/// shares).
+fn apply_verbosity_flags(base: Level, args: &[String]) -> (Level, Vec<String>) {
+    let mut level = base;
+    let mut rest = Vec::with_capacity(args.len());
+    for arg in args {
+        match arg.as_str() {
+            "-v" | "--verbose" => level = step(level, 1),
+            "-vv" => level = step(step(level, 1), 1),
+            "-q" | "--quiet" => level = step(level, -1),
+            _ => rest.push(arg.clone()),
+        }
+    }
+    (level, rest)
+}
+
+fn step(level: Level, delta: i8) -> Level {
+    let next = level as i8 + delta;
+    match next.clamp(Level::Error as i8, Level::Debug as i8) {
+        0 => Level::Error,
+        1 => Level::Warn,
+        2 => Level::Notice,
+        3 => Level::Info,
+        _ => Level::Debug,
+    }
+}
+
+/// Emit one record per physical line of `message`, gated on `level` against
+/// the global threshold (error/warn always pass). Not meant to be called
+/// directly — use the `error!`/`warn!`/`notice!`/`info!`/`debug!` macros.
+pub fn log(level: Level, message: std::fmt::Arguments) {
+    if level > Level::Warn && level > current_level() {
+        return;
+    }
+
+    let rendered = message.to_string();
+    for line in rendered.lines() {
+        let record = format!("[{}] {line}", level.prefix());
+        if level <= Level::Warn {
+            eprintln!("{record}");
+        } else {
+            println!("{record}");
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! error {
+    ($($arg:tt)*) => { $crate::log::log($crate::log::Level::Error, format_args!($($arg)*)) };
+}
+
+#[macro_export]
+macro_rules! warn {
+    ($($arg:tt)*) => { $crate::log::log($crate::log::Level::Warn, format_args!($($arg)*)) };
+}
+
+#[macro_export]
+macro_rules! notice {
+    ($($arg:tt)*) => { $crate::log::log($crate::log::Level::Notice, format_args!($($arg)*)) };
+}
+
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)*) => { $crate::log::log($crate::log::Level::Info, format_args!($($arg)*)) };
+}
+
+#[macro_export]
+macro_rules! debug {
+    ($($arg:tt)*) => { $crate::log::log($crate::log::Level::Debug, format_args!($($arg)*)) };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_from_name() {
+        assert_eq!(Level::from_name("error"), Some(Level::Error));
+        assert_eq!(Level::from_name("WARNING"), Some(Level::Warn));
+        assert_eq!(Level::from_name("Info"), Some(Level::Info));
+        assert_eq!(Level::from_name("verbose"), Some(Level::Info));
+        assert_eq!(Level::from_name("debug"), Some(Level::Debug));
+        assert_eq!(Level::from_name("bogus"), None);
+    }
+
+    #[test]
+    fn test_level_ordering() {
+        assert!(Level::Error < Level::Warn);
+        assert!(Level::Warn < Level::Notice);
+        assert!(Level::Notice < Level::Info);
+        assert!(Level::Info < Level::Debug);
+    }
+
+    #[test]
+    fn test_step_clamps_at_bounds() {
+        assert_eq!(step(Level::Error, -1), Level::Error);
+        assert_eq!(step(Level::Debug, 1), Level::Debug);
+    }
+
+    #[test]
+    fn test_step_moves_one_level() {
+        assert_eq!(step(Level::Notice, 1), Level::Info);
+        assert_eq!(step(Level::Notice, -1), Level::Warn);
+    }
+
+    #[test]
+    fn test_apply_verbosity_flags_verbose_raises_level_and_is_consumed() {
+        let args: Vec<String> = vec!["-v".to_string(), "save-xmltv".to_string()];
+        let (level, rest) = apply_verbosity_flags(Level::Notice, &args);
+        assert_eq!(level, Level::Info);
+        assert_eq!(rest, vec!["save-xmltv".to_string()]);
+    }
+
+    #[test]
+    fn test_apply_verbosity_flags_double_v_reaches_debug() {
+        let (level, _) = apply_verbosity_flags(Level::Notice, &["-vv".to_string()]);
+        assert_eq!(level, Level::Debug);
+    }
+
+    #[test]
+    fn test_apply_verbosity_flags_quiet_lowers_level() {
+        let (level, _) = apply_verbosity_flags(Level::Notice, &["-q".to_string()]);
+        assert_eq!(level, Level::Warn);
+    }
+
+    #[test]
+    fn test_apply_verbosity_flags_quiet_then_verbose_cancel_out() {
+        let args: Vec<String> = vec!["-q".to_string(), "-v".to_string()];
+        let (level, _) = apply_verbosity_flags(Level::Notice, &args);
+        assert_eq!(level, Level::Notice);
+    }
+}
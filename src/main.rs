@@ -1,55 +1,122 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 use std::io::{BufRead, BufReader, Read as _, Write};
 use std::net::TcpListener;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::time::Duration;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use std::path::Path;
 use std::process;
 
-use epgrab::channel::Channel;
+use epgrab::cache::EpgCache;
+use epgrab::channel::{Channel, Tuning};
+use epgrab::dmx;
 use epgrab::dvb_device;
 use epgrab::eit;
 use epgrab::channel;
+use epgrab::hls;
+use epgrab::log;
+use epgrab::{debug, error, info, notice, warn};
+use epgrab::mp4;
+use epgrab::pes;
+use epgrab::remux;
 use epgrab::scan;
 use epgrab::tuner;
+use epgrab::xmltv;
+use epgrab::xmltv::TimeOffset;
+
+/// Where the persistent, deduplicated EPG cache (see [`epgrab::cache`]) is
+/// stored between runs, so `run` and `save-xmltv` both grow the same guide
+/// instead of each one starting from scratch.
+const EPG_CACHE_PATH: &str = "epg/epg.dat";
+
+fn unix_now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
 
 fn main() {
-    let args: Vec<String> = std::env::args().collect();
+    let raw_args: Vec<String> = std::env::args().collect();
+    // `-v`/`-vv`/`-q` and `EPGRAB_LOG` are handled globally, ahead of (and
+    // stripped out of) each subcommand's own flag parsing.
+    let args = log::init(&raw_args[1..]);
 
-    match args.get(1).map(|s| s.as_str()) {
+    match args.first().map(|s| s.as_str()) {
         Some("run") => cmd_run(),
-        Some("scan-channels") => cmd_scan_channels(&args[2..]),
+        Some("scan-channels") => cmd_scan_channels(&args[1..]),
+        Some("record") => cmd_record(&args[1..]),
         Some("doctor") => cmd_doctor(),
-        Some("save-xmltv") => cmd_save_xmltv(),
-        Some("serve") => cmd_serve(&args[2..]),
+        Some("save-xmltv") => cmd_save_xmltv(&args[1..]),
+        Some("merge-xmltv") => cmd_merge_xmltv(&args[1..]),
+        Some("save-playlist") => cmd_save_playlist(&args[1..]),
+        Some("serve") => cmd_serve(&args[1..]),
         _ => print_usage(),
     }
 }
 
 fn print_usage() {
-    eprintln!("Usage: epgrab <command> [options]");
+    eprintln!("Usage: epgrab [-v|-vv|-q] <command> [options]");
+    eprintln!();
+    eprintln!("Global options:");
+    eprintln!("  -v, --verbose    Raise the log level by one step (notice -> info); -vv for debug");
+    eprintln!("  -q, --quiet      Lower the log level by one step (notice -> warn)");
+    eprintln!("  EPGRAB_LOG       Set the base log level: error, warn, notice, info, or debug");
     eprintln!();
     eprintln!("Commands:");
     eprintln!("  run              Grab EPG data from DVB-T tuner device");
-    eprintln!("  save-xmltv       Save EPG data as XMLTV files");
-    eprintln!("  serve            Serve XMLTV files over HTTP");
+    eprintln!("  save-xmltv       Save EPG data as a combined XMLTV guide");
+    eprintln!("  merge-xmltv      Import an external XMLTV guide into the EPG cache");
+    eprintln!("  save-playlist    Save an extended M3U playlist linking channels to the guide");
+    eprintln!("  serve            Serve XMLTV files, the playlist, and live HLS streams over HTTP");
     eprintln!("  scan-channels    Scan for available channels");
+    eprintln!("  record           Record a channel's video/audio PIDs to an MP4 or .ts file");
     eprintln!("  doctor           Check system readiness");
     eprintln!();
     eprintln!("Examples:");
     eprintln!("  epgrab run");
-    eprintln!("  epgrab save-xmltv");
+    eprintln!("  epgrab save-xmltv --tz auto");
+    eprintln!("  epgrab save-xmltv --split-files");
+    eprintln!("  epgrab merge-xmltv other-box.xml --tz +0200");
+    eprintln!("  epgrab save-playlist --host 192.168.1.10:8080");
     eprintln!("  epgrab serve -b 0.0.0.0 -p 8080 --public");
     eprintln!("  epgrab scan-channels -C /usr/share/dvb/dvb-t/tw-All");
+    eprintln!("  epgrab record -c \"Channel Name\" -o capture.mp4 -d 30");
     eprintln!("  epgrab doctor");
     process::exit(1);
 }
 
+/// Parse a trailing `--tz <auto|none|+HHMM>` option shared by `save-xmltv`
+/// and `merge-xmltv`, defaulting to [`TimeOffset::Auto`] (the historical
+/// machine-local behavior) when absent.
+fn parse_tz_arg(args: &[String]) -> (TimeOffset, Vec<String>) {
+    let mut offset = TimeOffset::Auto;
+    let mut rest = Vec::new();
+    let mut i = 0;
+    while i < args.len() {
+        if args[i] == "--tz" {
+            let value = args.get(i + 1).cloned().unwrap_or_else(|| {
+                error!("missing value for --tz");
+                process::exit(1);
+            });
+            offset = TimeOffset::parse(&value).unwrap_or_else(|e| {
+                error!("{e}");
+                process::exit(1);
+            });
+            i += 2;
+        } else {
+            rest.push(args[i].clone());
+            i += 1;
+        }
+    }
+    (offset, rest)
+}
+
 fn cmd_run() {
     let devices = dvb_device::detect_devices();
 
     if devices.is_empty() {
-        eprintln!("No DVB-T devices found.");
+        error!("no DVB-T devices found.");
         process::exit(1);
     }
 
@@ -64,22 +131,20 @@ fn cmd_run() {
     for dev in &devices {
         let vendor_display = dev.vendor_name.as_deref().unwrap_or("Unknown vendor");
         let product_display = dev.product_name.as_deref().unwrap_or("Unknown device");
-        println!(
+        notice!(
             "{}: {} - {} (vendor={}, device={})",
             dev.adapter_name, vendor_display, product_display, dev.vendor_id, dev.device_id
         );
     }
 
-    println!();
-
     let conf_path = Path::new("etc/channels.conf");
-    let channels = match channel::parse_channels_conf(conf_path) {
+    let channels = match channel::parse_channel_list(conf_path) {
         Ok(channels) => {
-            println!("Loaded {} channels.", channels.len());
+            notice!("Loaded {} channels.", channels.len());
             channels
         }
         Err(e) => {
-            eprintln!("Error parsing channels.conf: {e}");
+            error!("parsing channels.conf: {e}");
             process::exit(1);
         }
     };
@@ -94,14 +159,23 @@ fn cmd_run() {
     let tuner = match tuner::Tuner::open(adapter) {
         Ok(t) => t,
         Err(e) => {
-            eprintln!("Failed to open tuner: {e}");
+            error!("failed to open tuner: {e}");
+            process::exit(1);
+        }
+    };
+
+    let cache_path = Path::new(EPG_CACHE_PATH);
+    let mut cache = match EpgCache::load(cache_path) {
+        Ok(c) => c,
+        Err(e) => {
+            error!("failed to load EPG cache: {e}");
             process::exit(1);
         }
     };
 
     let num_freqs = freq_groups.len();
     for (i, (freq, group)) in freq_groups.iter().enumerate() {
-        println!(
+        notice!(
             "[{}/{}] Tuning to {} MHz ({} channels)...",
             i + 1,
             num_freqs,
@@ -110,9 +184,8 @@ fn cmd_run() {
         );
 
         // Tune using the first channel in the group (same tuning params for all)
-        if let Err(e) = tuner.tune(group[0]) {
-            eprintln!("  Skipped: {e}");
-            println!();
+        if let Err(e) = tuner.tune(group[0], &tuner::TuneConfig::default()) {
+            warn!("skipped: {e}");
             continue;
         }
 
@@ -120,91 +193,95 @@ fn cmd_run() {
         let mut eit_reader = match eit::EitReader::open(adapter) {
             Ok(r) => r,
             Err(e) => {
-                eprintln!("  Failed to open demux: {e}");
-                println!();
+                error!("failed to open demux: {e}");
                 continue;
             }
         };
 
         if !tuner.has_lock() {
-            eprintln!("  Warning: frontend lost lock before EIT reading");
+            warn!("frontend lost lock before EIT reading");
         }
 
-        // Read EIT data (30-second timeout)
-        println!("  Reading EIT data...");
-        match eit_reader.read_events(30) {
-            Ok(events) => {
-                if events.is_empty() {
-                    println!("  No EIT events received.");
-                } else {
-                    // Group events by service_id and map to channel name
-                    for ch in group {
-                        let ch_events: Vec<_> = events
-                            .iter()
-                            .filter(|e| e.service_id == ch.service_id)
-                            .collect();
-
-                        if ch_events.is_empty() {
-                            continue;
-                        }
-
-                        println!("  {} (SID={}):", ch.name, ch.service_id);
-                        for event in &ch_events {
-                            let start = format_unix_timestamp(event.start_time);
-                            let dur_h = event.duration / 3600;
-                            let dur_m = (event.duration % 3600) / 60;
-                            println!(
-                                "    [{}] {} ({}h{}m) - {} [{}]",
-                                event.event_id,
-                                event.event_name,
-                                dur_h,
-                                dur_m,
-                                start,
-                                event.language,
-                            );
-                            if !event.description.is_empty() {
-                                println!("      {}", event.description);
-                            }
-                        }
+        // Read EIT data (30-second timeout), merging it into the persistent
+        // cache instead of throwing it away at the end of this run.
+        notice!("Reading EIT data...");
+        match eit_reader.read_into_cache(30, &mut cache) {
+            Ok(merged) => {
+                notice!("Merged {merged} new/updated events into the cache.");
+
+                for ch in group {
+                    let ch_events = cache.events_for_service(ch.service_id);
+                    if ch_events.is_empty() {
+                        continue;
                     }
 
-                    // Show events for services not in channels.conf
-                    let known_sids: Vec<u16> = group.iter().map(|ch| ch.service_id).collect();
-                    let unknown: Vec<_> = events
-                        .iter()
-                        .filter(|e| !known_sids.contains(&e.service_id))
-                        .collect();
-                    if !unknown.is_empty() {
-                        println!("  Unknown services:");
-                        for event in &unknown {
-                            let start = format_unix_timestamp(event.start_time);
-                            let dur_h = event.duration / 3600;
-                            let dur_m = (event.duration % 3600) / 60;
-                            println!(
-                                "    SID={}: [{}] {} ({}h{}m) - {} [{}]",
-                                event.service_id,
-                                event.event_id,
-                                event.event_name,
-                                dur_h,
-                                dur_m,
-                                start,
-                                event.language,
-                            );
+                    notice!("{} (SID={}):", ch.name, ch.service_id);
+                    for event in &ch_events {
+                        let start = format_unix_timestamp(event.start_time);
+                        let dur_h = event.duration / 3600;
+                        let dur_m = (event.duration % 3600) / 60;
+                        info!(
+                            "  [{}] {} ({}h{}m) - {} [{}]",
+                            event.event_id,
+                            event.event_name,
+                            dur_h,
+                            dur_m,
+                            start,
+                            event.language,
+                        );
+                        if !event.description.is_empty() {
+                            info!("    {}", event.description);
                         }
                     }
                 }
             }
-            Err(e) => eprintln!("  Failed to read EIT: {e}"),
+            Err(e) => error!("failed to read EIT: {e}"),
         }
-        println!();
+    }
+
+    // Show cached events for services not in channels.conf, across every
+    // frequency tuned above.
+    let known_sids: Vec<u16> = channels.iter().map(|ch| ch.service_id).collect();
+    for sid in cache.service_ids() {
+        if known_sids.contains(&sid) {
+            continue;
+        }
+        notice!("Unknown service SID={sid}:");
+        for event in cache.events_for_service(sid) {
+            let start = format_unix_timestamp(event.start_time);
+            let dur_h = event.duration / 3600;
+            let dur_m = (event.duration % 3600) / 60;
+            info!(
+                "  [{}] {} ({}h{}m) - {} [{}]",
+                event.event_id, event.event_name, dur_h, dur_m, start, event.language,
+            );
+        }
+    }
+
+    cache.evict_expired(unix_now());
+    if let Err(e) = cache.save(cache_path) {
+        error!("failed to save EPG cache: {e}");
     }
 }
 
-fn cmd_save_xmltv() {
+fn cmd_save_xmltv(args: &[String]) {
+    let (offset, rest) = parse_tz_arg(args);
+    let mut split_files = false;
+    for arg in &rest {
+        match arg.as_str() {
+            "--split-files" => split_files = true,
+            _ => {
+                error!("unknown option: {arg}");
+                eprintln!("Usage: epgrab save-xmltv [--tz <auto|none|+HHMM>] [--split-files]");
+                process::exit(1);
+            }
+        }
+    }
+
     let devices = dvb_device::detect_devices();
 
     if devices.is_empty() {
-        eprintln!("No DVB-T devices found.");
+        error!("no DVB-T devices found.");
         process::exit(1);
     }
 
@@ -216,13 +293,13 @@ fn cmd_save_xmltv() {
         .unwrap_or(0);
 
     let conf_path = Path::new("etc/channels.conf");
-    let channels = match channel::parse_channels_conf(conf_path) {
+    let channels = match channel::parse_channel_list(conf_path) {
         Ok(channels) => {
-            println!("Loaded {} channels.", channels.len());
+            notice!("Loaded {} channels.", channels.len());
             channels
         }
         Err(e) => {
-            eprintln!("Error parsing channels.conf: {e}");
+            error!("parsing channels.conf: {e}");
             process::exit(1);
         }
     };
@@ -237,26 +314,29 @@ fn cmd_save_xmltv() {
     let tuner = match tuner::Tuner::open(adapter) {
         Ok(t) => t,
         Err(e) => {
-            eprintln!("Failed to open tuner: {e}");
+            error!("failed to open tuner: {e}");
             process::exit(1);
         }
     };
 
     // Create output directory
     if let Err(e) = std::fs::create_dir_all("epg") {
-        eprintln!("Failed to create epg/ directory: {e}");
+        error!("failed to create epg/ directory: {e}");
         process::exit(1);
     }
 
-    // Collect all events keyed by channel name
-    let mut channel_events: BTreeMap<String, (u16, Vec<eit::EitEvent>)> = BTreeMap::new();
-    for ch in &channels {
-        channel_events.insert(ch.name.clone(), (ch.service_id, Vec::new()));
-    }
+    let cache_path = Path::new(EPG_CACHE_PATH);
+    let mut cache = match EpgCache::load(cache_path) {
+        Ok(c) => c,
+        Err(e) => {
+            error!("failed to load EPG cache: {e}");
+            process::exit(1);
+        }
+    };
 
     let num_freqs = freq_groups.len();
     for (i, (freq, group)) in freq_groups.iter().enumerate() {
-        println!(
+        notice!(
             "[{}/{}] Tuning to {} MHz ({} channels)...",
             i + 1,
             num_freqs,
@@ -264,94 +344,250 @@ fn cmd_save_xmltv() {
             group.len(),
         );
 
-        if let Err(e) = tuner.tune(group[0]) {
-            eprintln!("  Skipped: {e}");
-            println!();
+        if let Err(e) = tuner.tune(group[0], &tuner::TuneConfig::default()) {
+            warn!("skipped: {e}");
             continue;
         }
 
         let mut eit_reader = match eit::EitReader::open(adapter) {
             Ok(r) => r,
             Err(e) => {
-                eprintln!("  Failed to open demux: {e}");
-                println!();
+                error!("failed to open demux: {e}");
                 continue;
             }
         };
 
         if !tuner.has_lock() {
-            eprintln!("  Warning: frontend lost lock before EIT reading");
-        }
-
-        println!("  Reading EIT data...");
-        match eit_reader.read_events(30) {
-            Ok(events) => {
-                let event_count = events.len();
-                for event in events {
-                    for ch in group {
-                        if event.service_id == ch.service_id {
-                            if let Some((_, evts)) = channel_events.get_mut(&ch.name) {
-                                evts.push(event);
-                                break;
-                            }
-                        }
-                    }
-                }
-                println!("  Received {event_count} events.");
-            }
-            Err(e) => eprintln!("  Failed to read EIT: {e}"),
+            warn!("frontend lost lock before EIT reading");
+        }
+
+        notice!("Reading EIT data...");
+        match eit_reader.read_into_cache(30, &mut cache) {
+            Ok(merged) => notice!("Merged {merged} new/updated events into the cache."),
+            Err(e) => error!("failed to read EIT: {e}"),
         }
-        println!();
+    }
+
+    cache.evict_expired(unix_now());
+    if let Err(e) = cache.save(cache_path) {
+        error!("failed to save EPG cache: {e}");
     }
 
     // Check if XSLT stylesheet exists
     let use_xslt = Path::new("epg/epg.xsl").exists();
     if use_xslt {
-        println!("Found epg/epg.xsl, linking stylesheet in XML files.");
+        notice!("Found epg/epg.xsl, linking stylesheet in XML files.");
     }
 
-    // Write XMLTV files
-    let mut files_written = 0;
-    for (name, (_sid, events)) in &channel_events {
-        if events.is_empty() {
-            continue;
+    // Gather every channel's events from the accumulated cache, sorted by
+    // channel name (events within a channel are already start-time sorted
+    // by `events_for_service`), so a guide built up over many runs is
+    // emitted in full rather than just this run's events.
+    let mut sorted_channels: Vec<&Channel> = channels.iter().collect();
+    sorted_channels.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut combined: Vec<(&str, Vec<eit::EitEvent>)> = Vec::new();
+    for ch in &sorted_channels {
+        let events = cache.events_for_service(ch.service_id);
+        if !events.is_empty() {
+            combined.push((ch.name.as_str(), events));
         }
+    }
 
-        let safe_name = sanitize_filename(name);
-        let filename = format!("epg/{}.eit.xml", safe_name);
-        let xml = generate_xmltv(name, events, use_xslt);
+    // The combined guide is the primary artifact: it's what `url-tvg` in
+    // the playlist generated by `save-playlist`/`serve` points at.
+    let guide_xml = generate_combined_xmltv(&combined, use_xslt, &offset);
+    match std::fs::write("epg/guide.xml", &guide_xml) {
+        Ok(()) => notice!("Wrote epg/guide.xml (combined guide, {} channels)", combined.len()),
+        Err(e) => error!("failed to write epg/guide.xml: {e}"),
+    }
 
-        match std::fs::write(&filename, &xml) {
-            Ok(()) => {
-                println!("Wrote {} ({} events)", filename, events.len());
-                files_written += 1;
+    // `--split-files` additionally writes the old one-file-per-channel
+    // layout, for consumers that can't handle a combined document.
+    if split_files {
+        let mut files_written = 0;
+        for (name, events) in &combined {
+            let safe_name = sanitize_filename(name);
+            let filename = format!("epg/{}.eit.xml", safe_name);
+            let xml = generate_xmltv(name, events, use_xslt, &offset);
+
+            match std::fs::write(&filename, &xml) {
+                Ok(()) => {
+                    notice!("Wrote {} ({} events)", filename, events.len());
+                    files_written += 1;
+                }
+                Err(e) => error!("failed to write {filename}: {e}"),
             }
-            Err(e) => eprintln!("Failed to write {filename}: {e}"),
         }
+        notice!("Saved {files_written} per-channel XMLTV files to epg/");
     }
+}
+
+fn cmd_save_playlist(args: &[String]) {
+    let mut host = "127.0.0.1:3000".to_string();
+    let mut url_template = DEFAULT_URL_TEMPLATE.to_string();
 
-    println!("\nSaved {files_written} XMLTV files to epg/");
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--host" => {
+                host = args.get(i + 1).cloned().unwrap_or_else(|| {
+                    error!("missing value for --host");
+                    process::exit(1);
+                });
+                i += 2;
+            }
+            "--url-template" => {
+                url_template = args.get(i + 1).cloned().unwrap_or_else(|| {
+                    error!("missing value for --url-template");
+                    process::exit(1);
+                });
+                i += 2;
+            }
+            _ => {
+                error!("unknown option: {}", args[i]);
+                eprintln!("Usage: epgrab save-playlist [--host <host:port>] [--url-template <template>]");
+                process::exit(1);
+            }
+        }
+    }
+
+    let conf_path = Path::new("etc/channels.conf");
+    let channels = match channel::parse_channel_list(conf_path) {
+        Ok(channels) => channels,
+        Err(e) => {
+            error!("parsing channels.conf: {e}");
+            process::exit(1);
+        }
+    };
+
+    if let Err(e) = std::fs::create_dir_all("epg") {
+        error!("failed to create epg/ directory: {e}");
+        process::exit(1);
+    }
+
+    let guide_url = format!("http://{host}/guide.xml");
+    let m3u = generate_playlist(&channels, Some(&guide_url), &url_template);
+
+    match std::fs::write("epg/playlist.m3u", &m3u) {
+        Ok(()) => notice!("Wrote epg/playlist.m3u ({} channels)", channels.len()),
+        Err(e) => {
+            error!("failed to write epg/playlist.m3u: {e}");
+            process::exit(1);
+        }
+    }
 }
 
-fn generate_xmltv(channel_name: &str, events: &[eit::EitEvent], use_xslt: bool) -> String {
-    let mut xml = String::new();
-    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
-    if use_xslt {
-        xml.push_str("<?xml-stylesheet type=\"text/xsl\" href=\"epg.xsl\"?>\n");
+fn cmd_merge_xmltv(args: &[String]) {
+    let (offset, rest) = parse_tz_arg(args);
+    let file_path = match rest.first() {
+        Some(path) => path.clone(),
+        None => {
+            error!("path to an XMLTV file is required");
+            eprintln!("Usage: epgrab merge-xmltv <file.xml> [--tz <auto|none|+HHMM>]");
+            process::exit(1);
+        }
+    };
+
+    let conf_path = Path::new("etc/channels.conf");
+    let channels = match channel::parse_channel_list(conf_path) {
+        Ok(channels) => channels,
+        Err(e) => {
+            error!("parsing channels.conf: {e}");
+            process::exit(1);
+        }
+    };
+
+    let content = match std::fs::read_to_string(&file_path) {
+        Ok(c) => c,
+        Err(e) => {
+            error!("failed to read {file_path}: {e}");
+            process::exit(1);
+        }
+    };
+
+    let programmes = match xmltv::parse_xmltv(&content, &offset) {
+        Ok(p) => p,
+        Err(e) => {
+            error!("parsing {file_path}: {e}");
+            process::exit(1);
+        }
+    };
+
+    let cache_path = Path::new(EPG_CACHE_PATH);
+    let mut cache = match EpgCache::load(cache_path) {
+        Ok(c) => c,
+        Err(e) => {
+            error!("failed to load EPG cache: {e}");
+            process::exit(1);
+        }
+    };
+
+    let mut merged = 0;
+    let mut unmatched: std::collections::BTreeSet<String> = std::collections::BTreeSet::new();
+    for p in &programmes {
+        let Some(ch) = channels.iter().find(|ch| ch.name == p.channel) else {
+            unmatched.insert(p.channel.clone());
+            continue;
+        };
+
+        let event = eit::EitEvent {
+            service_id: ch.service_id,
+            event_id: synthesize_event_id(ch.service_id, p.start),
+            start_time: p.start,
+            duration: (p.stop - p.start).max(0) as u32,
+            running_status: 0,
+            event_name: p.title.clone(),
+            description: p.description.clone(),
+            language: p.language.clone(),
+        };
+
+        if cache.ingest_external(&event) {
+            merged += 1;
+        }
+    }
+
+    for name in &unmatched {
+        warn!("no channel named \"{name}\" in {}", conf_path.display());
+    }
+
+    cache.evict_expired(unix_now());
+    if let Err(e) = cache.save(cache_path) {
+        error!("failed to save EPG cache: {e}");
+        process::exit(1);
     }
-    xml.push_str("<!DOCTYPE tv SYSTEM \"xmltv.dtd\">\n");
-    xml.push_str("<tv generator-info-name=\"epgrab\">\n");
 
-    // Channel element
+    notice!("Merged {merged} events from {file_path} into the EPG cache.");
+    notice!("Run 'epgrab save-xmltv' to write updated XMLTV files.");
+}
+
+/// Derive a stable `event_id` for an imported programme that has no EIT
+/// event_id of its own, since [`eit::EitEvent`] keys on `(service_id,
+/// event_id)` in the cache. Hashing `(service_id, start_time)` keeps the id
+/// stable across repeated merges of the same guide, so re-importing an
+/// unchanged XMLTV file doesn't keep minting "new" events.
+fn synthesize_event_id(service_id: u16, start_time: i64) -> u16 {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    std::hash::Hash::hash(&(service_id, start_time), &mut hasher);
+    (std::hash::Hasher::finish(&hasher) & 0xFFFF) as u16
+}
+
+/// Render one channel's `<channel>` element followed by its `<programme>`
+/// elements. Shared by [`generate_xmltv`] (one channel per file) and
+/// [`generate_combined_xmltv`] (every channel in a single `<tv>` document),
+/// so the two stay byte-for-byte identical where they overlap and
+/// `tvg-id`/`channel` attributes written by [`generate_playlist`] always
+/// match the `channel id` this emits.
+fn channel_and_programme_xml(channel_name: &str, events: &[eit::EitEvent], offset: &TimeOffset) -> String {
+    let mut xml = String::new();
     let channel_id = xml_escape(channel_name);
     xml.push_str(&format!(
         "  <channel id=\"{channel_id}\">\n    <display-name>{channel_id}</display-name>\n  </channel>\n"
     ));
 
-    // Programme elements
     for event in events {
-        let start = format_xmltv_time(event.start_time);
-        let stop = format_xmltv_time(event.start_time + event.duration as i64);
+        let start = xmltv::format_xmltv_time(event.start_time, offset);
+        let stop = xmltv::format_xmltv_time(event.start_time + event.duration as i64, offset);
         let title = xml_escape(&event.event_name);
         let lang = if event.language.is_empty() {
             String::new()
@@ -372,32 +608,69 @@ fn generate_xmltv(channel_name: &str, events: &[eit::EitEvent], use_xslt: bool)
         xml.push_str("  </programme>\n");
     }
 
+    xml
+}
+
+fn xmltv_header(use_xslt: bool) -> String {
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    if use_xslt {
+        xml.push_str("<?xml-stylesheet type=\"text/xsl\" href=\"epg.xsl\"?>\n");
+    }
+    xml.push_str("<!DOCTYPE tv SYSTEM \"xmltv.dtd\">\n");
+    xml.push_str("<tv generator-info-name=\"epgrab\">\n");
+    xml
+}
+
+fn generate_xmltv(channel_name: &str, events: &[eit::EitEvent], use_xslt: bool, offset: &TimeOffset) -> String {
+    let mut xml = xmltv_header(use_xslt);
+    xml.push_str(&channel_and_programme_xml(channel_name, events, offset));
     xml.push_str("</tv>\n");
     xml
 }
 
-fn format_xmltv_time(ts: i64) -> String {
-    let time_t = ts as libc::time_t;
-    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
-    unsafe { libc::localtime_r(&time_t, &mut tm) };
+/// Emit a single combined XMLTV document covering every channel, for
+/// `url-tvg` in [`generate_playlist`] to point at.
+fn generate_combined_xmltv(channels: &[(&str, Vec<eit::EitEvent>)], use_xslt: bool, offset: &TimeOffset) -> String {
+    let mut xml = xmltv_header(use_xslt);
+    for (name, events) in channels {
+        xml.push_str(&channel_and_programme_xml(name, events, offset));
+    }
+    xml.push_str("</tv>\n");
+    xml
+}
 
-    let offset_secs = tm.tm_gmtoff;
-    let offset_h = offset_secs.abs() / 3600;
-    let offset_m = (offset_secs.abs() % 3600) / 60;
-    let sign = if offset_secs >= 0 { '+' } else { '-' };
+/// `dvb://{freq}/{sid}` by default; `{freq}` and `{sid}` are substituted
+/// with the channel's frequency (Hz) and service id.
+const DEFAULT_URL_TEMPLATE: &str = "dvb://{freq}/{sid}";
+
+/// Emit an extended M3U playlist whose `tvg-id` for each channel matches the
+/// `channel id` [`generate_xmltv`]/[`generate_combined_xmltv`] write for
+/// that same channel, so IPTV front-ends can bind programmes to channels.
+/// `guide_url` is omitted from the `#EXTM3U` header (rather than pointing at
+/// a non-existent guide) when no XMLTV guide has been generated yet, e.g.
+/// right after a scan.
+fn generate_playlist(channels: &[Channel], guide_url: Option<&str>, url_template: &str) -> String {
+    let mut m3u = String::new();
+    match guide_url {
+        Some(url) => m3u.push_str(&format!("#EXTM3U url-tvg=\"{url}\"\n")),
+        None => m3u.push_str("#EXTM3U\n"),
+    }
+
+    for ch in channels {
+        let tvg_id = xml_escape(&ch.name);
+        let group = format!("{} MHz", ch.frequency / 1_000_000);
+        let url = url_template
+            .replace("{freq}", &ch.frequency.to_string())
+            .replace("{sid}", &ch.service_id.to_string());
+
+        m3u.push_str(&format!(
+            "#EXTINF:-1 tvg-id=\"{tvg_id}\" tvg-name=\"{tvg_id}\" group-title=\"{group}\",{tvg_id}\n"
+        ));
+        m3u.push_str(&format!("{url}\n"));
+    }
 
-    format!(
-        "{:04}{:02}{:02}{:02}{:02}{:02} {}{:02}{:02}",
-        tm.tm_year + 1900,
-        tm.tm_mon + 1,
-        tm.tm_mday,
-        tm.tm_hour,
-        tm.tm_min,
-        tm.tm_sec,
-        sign,
-        offset_h,
-        offset_m,
-    )
+    m3u
 }
 
 fn sanitize_filename(s: &str) -> String {
@@ -428,22 +701,22 @@ fn cmd_serve(args: &[String]) {
         match args[i].as_str() {
             "-b" | "--bind" => {
                 bind = args.get(i + 1).cloned().unwrap_or_else(|| {
-                    eprintln!("Error: missing value for {}", args[i]);
+                    error!("missing value for {}", args[i]);
                     process::exit(1);
                 });
                 i += 2;
             }
             "-p" | "--port" => {
                 let port_str = args.get(i + 1).cloned().unwrap_or_else(|| {
-                    eprintln!("Error: missing value for {}", args[i]);
+                    error!("missing value for {}", args[i]);
                     process::exit(1);
                 });
                 port = port_str.parse::<u16>().unwrap_or_else(|_| {
-                    eprintln!("Error: invalid port number '{port_str}' (must be 1-65535)");
+                    error!("invalid port number '{port_str}' (must be 1-65535)");
                     process::exit(1);
                 });
                 if port == 0 {
-                    eprintln!("Error: invalid port number '0' (must be 1-65535)");
+                    error!("invalid port number '0' (must be 1-65535)");
                     process::exit(1);
                 }
                 i += 2;
@@ -453,7 +726,7 @@ fn cmd_serve(args: &[String]) {
                 i += 1;
             }
             _ => {
-                eprintln!("Unknown option: {}", args[i]);
+                error!("unknown option: {}", args[i]);
                 eprintln!("Usage: epgrab serve [-b <bind>] [-p <port>] [--public]");
                 process::exit(1);
             }
@@ -463,24 +736,26 @@ fn cmd_serve(args: &[String]) {
     // Require --public for non-loopback bind addresses
     let is_loopback = bind == "127.0.0.1" || bind == "::1" || bind == "localhost";
     if !is_loopback && !public {
-        eprintln!(
-            "Error: binding to '{bind}' exposes the server to the network."
-        );
-        eprintln!("If this is intentional, add the --public flag.");
+        error!("binding to '{bind}' exposes the server to the network.\nIf this is intentional, add the --public flag.");
         process::exit(1);
     }
 
     let epg_dir = Path::new("epg");
     if !epg_dir.is_dir() {
-        eprintln!("epg/ directory not found. Run 'epgrab save-xmltv' first.");
+        error!("epg/ directory not found. Run 'epgrab save-xmltv' first.");
         process::exit(1);
     }
 
+    // Loaded once up front so `/playlist.m3u` doesn't need to re-parse
+    // channels.conf on every request; a missing/invalid file just means the
+    // playlist endpoint has nothing to serve.
+    let channels = channel::parse_channel_list(Path::new("etc/channels.conf")).unwrap_or_default();
+
     let addr = format!("{}:{}", bind, port);
     let listener = match TcpListener::bind(&addr) {
         Ok(l) => l,
         Err(e) => {
-            eprintln!("Failed to bind to {addr}: {e}");
+            error!("failed to bind to {addr}: {e}");
             process::exit(1);
         }
     };
@@ -494,7 +769,7 @@ fn cmd_serve(args: &[String]) {
         .set_nonblocking(true)
         .expect("Failed to set non-blocking mode");
 
-    eprintln!("Serving epg/ at http://{addr}/");
+    notice!("Serving epg/ at http://{addr}/");
 
     while !SERVE_SHUTDOWN.load(Ordering::Relaxed) {
         let mut stream = match listener.accept() {
@@ -504,7 +779,7 @@ fn cmd_serve(args: &[String]) {
                 continue;
             }
             Err(e) => {
-                eprintln!("Connection error: {e}");
+                warn!("connection error: {e}");
                 continue;
             }
         };
@@ -513,19 +788,40 @@ fn cmd_serve(args: &[String]) {
         let _ = stream.set_read_timeout(timeout);
         let _ = stream.set_write_timeout(timeout);
 
-        // Limit request line to 8 KiB to prevent memory exhaustion
-        const MAX_REQUEST_LINE: u64 = 8192;
-        let mut limited = BufReader::new((&stream).take(MAX_REQUEST_LINE));
+        // Limit the request line plus headers to 16 KiB to prevent memory
+        // exhaustion
+        const MAX_REQUEST_HEADERS: u64 = 16384;
+        let mut limited = BufReader::new((&stream).take(MAX_REQUEST_HEADERS));
         let mut request_line = String::new();
         match limited.read_line(&mut request_line) {
             Ok(0) | Err(_) => continue,
             Ok(_) => {}
         }
 
-        handle_request(&mut stream, request_line.trim_end(), epg_dir);
+        let request_line = request_line.trim_end().to_string();
+
+        // Read the header block (up to the blank line that ends it) off the
+        // same reader so nothing buffered ahead of the request line is lost.
+        let mut header_block = String::new();
+        loop {
+            let mut line = String::new();
+            match limited.read_line(&mut line) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {
+                    let is_blank = line.trim().is_empty();
+                    header_block.push_str(&line);
+                    if is_blank {
+                        break;
+                    }
+                }
+            }
+        }
+
+        let status = handle_request(&mut stream, &request_line, &header_block, epg_dir, &channels, &addr);
+        debug!("{request_line:?} -> {status}");
     }
 
-    eprintln!("\nShutting down.");
+    notice!("Shutting down.");
 }
 
 static SERVE_SHUTDOWN: AtomicBool = AtomicBool::new(false);
@@ -534,11 +830,94 @@ extern "C" fn serve_signal_handler(_sig: libc::c_int) {
     SERVE_SHUTDOWN.store(true, Ordering::Relaxed);
 }
 
-fn handle_request(stream: &mut impl Write, request_line: &str, epg_dir: &Path) {
+/// Parse the header block following the request line (`Name: value` lines
+/// up to the blank line that ends them) into a lowercase-keyed map, so
+/// [`handle_request`] can look up conditional-GET headers case-insensitively.
+fn parse_request_headers(raw: &str) -> HashMap<String, String> {
+    let mut headers = HashMap::new();
+    for line in raw.lines() {
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+    headers
+}
+
+/// Format a Unix timestamp as an RFC 7231 HTTP-date, e.g.
+/// `Sun, 06 Nov 1994 08:49:37 GMT` — used for the `Last-Modified` header and
+/// parsed back by [`parse_http_date`] for `If-Modified-Since`.
+fn format_http_date(ts: i64) -> String {
+    const WEEKDAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+
+    let time_t = ts as libc::time_t;
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    unsafe { libc::gmtime_r(&time_t, &mut tm) };
+
+    format!(
+        "{}, {:02} {} {:04} {:02}:{:02}:{:02} GMT",
+        WEEKDAYS[tm.tm_wday as usize % 7],
+        tm.tm_mday,
+        MONTHS[tm.tm_mon as usize % 12],
+        tm.tm_year + 1900,
+        tm.tm_hour,
+        tm.tm_min,
+        tm.tm_sec,
+    )
+}
+
+/// Parse an RFC 7231 HTTP-date (as sent in `If-Modified-Since`) into a Unix
+/// timestamp. This is the inverse of [`format_http_date`].
+fn parse_http_date(s: &str) -> Option<i64> {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+
+    // "Sun, 06 Nov 1994 08:49:37 GMT" -> "06 Nov 1994 08:49:37 GMT"
+    let rest = s.trim().split_once(", ").map(|(_, r)| r).unwrap_or(s.trim());
+    let fields: Vec<&str> = rest.split_whitespace().collect();
+    if fields.len() != 5 {
+        return None;
+    }
+    let mday: i32 = fields[0].parse().ok()?;
+    let mon = MONTHS.iter().position(|m| *m == fields[1])? as i32;
+    let year: i32 = fields[2].parse().ok()?;
+    let mut time_fields = fields[3].splitn(3, ':');
+    let hour: i32 = time_fields.next()?.parse().ok()?;
+    let min: i32 = time_fields.next()?.parse().ok()?;
+    let sec: i32 = time_fields.next()?.parse().ok()?;
+
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    tm.tm_year = year - 1900;
+    tm.tm_mon = mon;
+    tm.tm_mday = mday;
+    tm.tm_hour = hour;
+    tm.tm_min = min;
+    tm.tm_sec = sec;
+    Some(unsafe { libc::timegm(&mut tm) } as i64)
+}
+
+/// Serve one HTTP request, returning its response status code so the caller
+/// can log it (see the `debug!` call after [`handle_request`] in
+/// [`cmd_serve`]). `headers` is the raw header block following the request
+/// line, used to honor conditional-GET caching on served files.
+fn handle_request(
+    stream: &mut impl Write,
+    request_line: &str,
+    headers: &str,
+    epg_dir: &Path,
+    channels: &[Channel],
+    host: &str,
+) -> u16 {
     let parts: Vec<&str> = request_line.split_whitespace().collect();
     if parts.len() < 2 || parts[0] != "GET" {
         let _ = stream.write_all(b"HTTP/1.1 400 Bad Request\r\nConnection: close\r\n\r\n");
-        return;
+        return 400;
     }
 
     let raw_path = parts[1];
@@ -554,7 +933,30 @@ fn handle_request(stream: &mut impl Write, request_line: &str, epg_dir: &Path) {
         let _ = stream.write_all(
             b"HTTP/1.1 400 Bad Request\r\nConnection: close\r\n\r\nInvalid path\n",
         );
-        return;
+        return 400;
+    }
+
+    if path == "/playlist.m3u" {
+        if channels.is_empty() {
+            let _ = stream.write_all(
+                b"HTTP/1.1 404 Not Found\r\nConnection: close\r\n\r\nNo channels.conf loaded\n",
+            );
+            return 404;
+        }
+
+        let guide_url = format!("http://{host}/guide.xml");
+        let m3u = generate_playlist(channels, Some(&guide_url), DEFAULT_URL_TEMPLATE);
+        let header = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: audio/x-mpegurl\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            m3u.len()
+        );
+        let _ = stream.write_all(header.as_bytes());
+        let _ = stream.write_all(m3u.as_bytes());
+        return 200;
+    }
+
+    if let Some(rest) = path.strip_prefix("/live/") {
+        return handle_live_request(rest, channels, stream);
     }
 
     if path == "/" {
@@ -585,6 +987,7 @@ fn handle_request(stream: &mut impl Write, request_line: &str, epg_dir: &Path) {
         );
         let _ = stream.write_all(header.as_bytes());
         let _ = stream.write_all(body.as_bytes());
+        200
     } else {
         // Serve a file from epg/
         let filename = &path[1..]; // strip leading '/'
@@ -594,7 +997,7 @@ fn handle_request(stream: &mut impl Write, request_line: &str, epg_dir: &Path) {
             let _ = stream.write_all(
                 b"HTTP/1.1 404 Not Found\r\nConnection: close\r\n\r\nNot found\n",
             );
-            return;
+            return 404;
         }
 
         let file_path = epg_dir.join(filename);
@@ -606,11 +1009,49 @@ fn handle_request(stream: &mut impl Write, request_line: &str, epg_dir: &Path) {
                     let _ = stream.write_all(
                         b"HTTP/1.1 403 Forbidden\r\nConnection: close\r\n\r\nForbidden\n",
                     );
-                    return;
+                    return 403;
                 }
             }
         }
 
+        let metadata = match std::fs::metadata(&file_path) {
+            Ok(m) => m,
+            Err(_) => {
+                let _ = stream.write_all(
+                    b"HTTP/1.1 404 Not Found\r\nConnection: close\r\n\r\nNot found\n",
+                );
+                return 404;
+            }
+        };
+
+        let mtime = metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        // Weak validator: good enough to detect "file changed" without
+        // hashing the body, which is the point of serving a 304 at all.
+        let etag = format!("\"{:x}-{:x}\"", metadata.len(), mtime);
+        let last_modified = format_http_date(mtime);
+
+        let request_headers = parse_request_headers(headers);
+        let not_modified = match request_headers.get("if-none-match") {
+            Some(given) => given == &etag,
+            None => request_headers
+                .get("if-modified-since")
+                .and_then(|v| parse_http_date(v))
+                .is_some_and(|since| mtime <= since),
+        };
+
+        if not_modified {
+            let header = format!(
+                "HTTP/1.1 304 Not Modified\r\nETag: {etag}\r\nLast-Modified: {last_modified}\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n"
+            );
+            let _ = stream.write_all(header.as_bytes());
+            return 304;
+        }
+
         match std::fs::read(&file_path) {
             Ok(contents) => {
                 let content_type = if filename.ends_with(".xml") || filename.ends_with(".xsl")
@@ -621,19 +1062,136 @@ fn handle_request(stream: &mut impl Write, request_line: &str, epg_dir: &Path) {
                 };
 
                 let header = format!(
-                    "HTTP/1.1 200 OK\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
-                    contents.len()
+                    "HTTP/1.1 200 OK\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nLast-Modified: {last_modified}\r\nETag: {etag}\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n",
+                    contents.len()
+                );
+                let _ = stream.write_all(header.as_bytes());
+                let _ = stream.write_all(&contents);
+                200
+            }
+            Err(_) => {
+                let _ = stream.write_all(
+                    b"HTTP/1.1 404 Not Found\r\nConnection: close\r\n\r\nNot found\n",
+                );
+                404
+            }
+        }
+    }
+}
+
+/// Live HLS sessions keyed by service id, one per channel currently being
+/// watched; created lazily on the first `/live/` request for that channel
+/// and kept around (still tuning and cutting segments) for as long as the
+/// server runs.
+static LIVE_SESSIONS: std::sync::OnceLock<Mutex<HashMap<u16, Arc<hls::LiveSession>>>> =
+    std::sync::OnceLock::new();
+
+fn live_registry() -> &'static Mutex<HashMap<u16, Arc<hls::LiveSession>>> {
+    LIVE_SESSIONS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Parse `GET /live/<rest>` for a media playlist path (`<sid>.m3u8`).
+fn parse_live_playlist_path(rest: &str) -> Option<u16> {
+    rest.strip_suffix(".m3u8")?.parse().ok()
+}
+
+/// Parse `GET /live/<rest>` for a segment path (`<sid>/seg<index>.ts`).
+fn parse_live_segment_path(rest: &str) -> Option<(u16, u64)> {
+    let (sid_str, seg_name) = rest.split_once('/')?;
+    let sid = sid_str.parse().ok()?;
+    let index = seg_name.strip_prefix("seg")?.strip_suffix(".ts")?.parse().ok()?;
+    Some((sid, index))
+}
+
+/// Look up the running live session for `channel`, tuning and starting one
+/// on the first request for that service id. Sessions are never torn down
+/// by an idle timeout; they live for the lifetime of the server.
+fn live_session_for(channel: &Channel) -> Result<Arc<hls::LiveSession>, String> {
+    let mut sessions = live_registry().lock().unwrap();
+    if let Some(session) = sessions.get(&channel.service_id) {
+        return Ok(Arc::clone(session));
+    }
+
+    let devices = dvb_device::detect_devices();
+    let device = devices.first().ok_or_else(|| "no DVB devices found".to_string())?;
+    let adapter: u32 = device
+        .adapter_name
+        .strip_prefix("dvb")
+        .and_then(|s| s.split('.').next())
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0);
+
+    let frontend = tuner::Tuner::open(adapter)?;
+    frontend.tune(channel, &tuner::TuneConfig::default())?;
+
+    let segment_dir = std::env::temp_dir().join(format!("epgrab-live-{}", channel.service_id));
+    let session = Arc::new(hls::LiveSession::start(adapter, frontend, channel.clone(), segment_dir)?);
+    sessions.insert(channel.service_id, Arc::clone(&session));
+    Ok(session)
+}
+
+/// Serve the `/live/` routes: `<sid>.m3u8` is the live media playlist,
+/// `<sid>/seg<index>.ts` one of its segments.
+fn handle_live_request(rest: &str, channels: &[Channel], stream: &mut impl Write) -> u16 {
+    if let Some(sid) = parse_live_playlist_path(rest) {
+        let Some(channel) = channels.iter().find(|ch| ch.service_id == sid) else {
+            let _ = stream.write_all(
+                b"HTTP/1.1 404 Not Found\r\nConnection: close\r\n\r\nUnknown service id\n",
+            );
+            return 404;
+        };
+
+        let session = match live_session_for(channel) {
+            Ok(session) => session,
+            Err(e) => {
+                error!("failed to start live session for {}: {e}", channel.name);
+                let _ = stream.write_all(
+                    b"HTTP/1.1 503 Service Unavailable\r\nConnection: close\r\n\r\nTuner unavailable\n",
+                );
+                return 503;
+            }
+        };
+
+        let m3u8 = session.playlist();
+        let header = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/vnd.apple.mpegurl\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            m3u8.len()
+        );
+        let _ = stream.write_all(header.as_bytes());
+        let _ = stream.write_all(m3u8.as_bytes());
+        return 200;
+    }
+
+    if let Some((sid, index)) = parse_live_segment_path(rest) {
+        let session = live_registry().lock().unwrap().get(&sid).cloned();
+        let Some(session) = session else {
+            let _ = stream.write_all(
+                b"HTTP/1.1 404 Not Found\r\nConnection: close\r\n\r\nNo live session for this service\n",
+            );
+            return 404;
+        };
+
+        return match std::fs::read(session.segment_path(index)) {
+            Ok(bytes) => {
+                let header = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: video/mp2t\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    bytes.len()
                 );
                 let _ = stream.write_all(header.as_bytes());
-                let _ = stream.write_all(&contents);
+                let _ = stream.write_all(&bytes);
+                200
             }
             Err(_) => {
                 let _ = stream.write_all(
-                    b"HTTP/1.1 404 Not Found\r\nConnection: close\r\n\r\nNot found\n",
+                    b"HTTP/1.1 404 Not Found\r\nConnection: close\r\n\r\nSegment not found\n",
                 );
+                404
             }
-        }
+        };
     }
+
+    let _ = stream.write_all(b"HTTP/1.1 400 Bad Request\r\nConnection: close\r\n\r\nInvalid live path\n");
+    400
 }
 
 fn percent_decode(s: &str) -> String {
@@ -675,7 +1233,14 @@ fn cmd_doctor() {
         let dev = &devices[0];
         let vendor = dev.vendor_name.as_deref().unwrap_or("Unknown vendor");
         let product = dev.product_name.as_deref().unwrap_or("Unknown device");
-        println!("{GREEN}OK{RESET} ({}: {} - {})", dev.adapter_name, vendor, product);
+        let tuner_type = dev
+            .tuner_type
+            .map(|t| t.to_string())
+            .unwrap_or_else(|| "unknown type".to_string());
+        println!(
+            "{GREEN}OK{RESET} ({}: {} - {}, {})",
+            dev.adapter_name, vendor, product, tuner_type
+        );
     }
 
     // 2. Check etc/channels.conf
@@ -686,7 +1251,7 @@ fn cmd_doctor() {
         println!("  Run 'epgrab scan-channels -C <scan-file>' to create it.");
         ok = false;
     } else {
-        match channel::parse_channels_conf(conf_path) {
+        match channel::parse_channel_list(conf_path) {
             Ok(channels) if channels.is_empty() => {
                 println!("{RED}{BOLD}EMPTY{RESET} (no channels)");
                 ok = false;
@@ -716,13 +1281,13 @@ fn cmd_scan_channels(args: &[String]) {
         Some(i) => match args.get(i + 1) {
             Some(path) => path.clone(),
             None => {
-                eprintln!("Error: missing value for {}", args[i]);
+                error!("missing value for {}", args[i]);
                 eprintln!("Usage: epgrab scan-channels -C <file> | --config <file>");
                 process::exit(1);
             }
         },
         None => {
-            eprintln!("Error: -C or --config is required");
+            error!("-C or --config is required");
             eprintln!("Usage: epgrab scan-channels -C <file> | --config <file>");
             process::exit(1);
         }
@@ -731,7 +1296,7 @@ fn cmd_scan_channels(args: &[String]) {
     // Detect DVB device
     let devices = dvb_device::detect_devices();
     if devices.is_empty() {
-        eprintln!("No DVB-T devices found.");
+        error!("no DVB-T devices found.");
         process::exit(1);
     }
 
@@ -745,7 +1310,7 @@ fn cmd_scan_channels(args: &[String]) {
     let dev = &devices[0];
     let vendor_display = dev.vendor_name.as_deref().unwrap_or("Unknown vendor");
     let product_display = dev.product_name.as_deref().unwrap_or("Unknown device");
-    println!(
+    notice!(
         "Using {}: {} - {}",
         dev.adapter_name, vendor_display, product_display
     );
@@ -754,13 +1319,13 @@ fn cmd_scan_channels(args: &[String]) {
     let entries = match scan::parse_scan_file(&config_path) {
         Ok(e) => e,
         Err(e) => {
-            eprintln!("Error: {e}");
+            error!("{e}");
             process::exit(1);
         }
     };
 
-    println!(
-        "Scanning {} frequencies from {config_path}\n",
+    notice!(
+        "Scanning {} seed frequencies from {config_path} (auto-discovering more via NIT)",
         entries.len()
     );
 
@@ -768,49 +1333,61 @@ fn cmd_scan_channels(args: &[String]) {
     let tuner = match tuner::Tuner::open(adapter) {
         Ok(t) => t,
         Err(e) => {
-            eprintln!("Failed to open tuner: {e}");
+            error!("failed to open tuner: {e}");
             process::exit(1);
         }
     };
 
     let mut all_channels: Vec<Channel> = Vec::new();
+    let mut visited: std::collections::HashSet<u64> = std::collections::HashSet::new();
+    let mut queue: std::collections::VecDeque<scan::ScanEntry> = entries.into_iter().collect();
+    let mut scanned = 0usize;
 
-    for (i, entry) in entries.iter().enumerate() {
-        println!(
-            "[{}/{}] Tuning to {} MHz ({})...",
-            i + 1,
-            entries.len(),
+    while let Some(entry) = queue.pop_front() {
+        if !visited.insert(entry.frequency) {
+            continue;
+        }
+        scanned += 1;
+
+        notice!(
+            "[{scanned}] Tuning to {} MHz ({})...",
             entry.frequency / 1_000_000,
             entry.modulation,
         );
 
         let tune_channel = entry.to_channel();
-        if let Err(e) = tuner.tune(&tune_channel) {
-            eprintln!("  Skipped: {e}");
-            println!();
+        if let Err(e) = tuner.tune(&tune_channel, &tuner::TuneConfig::default()) {
+            warn!("skipped: {e}");
             continue;
         }
 
-        match scan::scan_frequency(adapter, entry) {
+        match scan::scan_frequency(adapter, &entry) {
             Ok(channels) => {
-                println!("  Found {} services:", channels.len());
+                notice!("Found {} services:", channels.len());
                 for ch in &channels {
-                    println!(
-                        "    {} (SID={}, video={}, audio={})",
+                    notice!(
+                        "  {} (SID={}, video={}, audio={})",
                         ch.name, ch.service_id, ch.video_pid, ch.audio_pid
                     );
                 }
                 all_channels.extend(channels);
             }
             Err(e) => {
-                eprintln!("  Scan error: {e}");
+                warn!("scan error: {e}");
+            }
+        }
+
+        // Pull other transponders off this one's NIT so a full network scan
+        // only needs a single seed frequency.
+        for discovered in scan::discover_transponders(adapter) {
+            if !visited.contains(&discovered.frequency) {
+                queue.push_back(discovered);
             }
         }
-        println!();
     }
 
     if all_channels.is_empty() {
-        println!("No channels found.");
+        notice!("No channels found.");
         return;
     }
 
@@ -823,7 +1400,7 @@ fn cmd_scan_channels(args: &[String]) {
     }
 
     if let Err(e) = std::fs::create_dir_all("etc") {
-        eprintln!("Failed to create etc/ directory: {e}");
+        error!("failed to create etc/ directory: {e}");
         process::exit(1);
     }
 
@@ -831,43 +1408,260 @@ fn cmd_scan_channels(args: &[String]) {
     if Path::new(output_path).exists() {
         let backup_path = format!("{output_path}.old");
         if let Err(e) = std::fs::rename(output_path, &backup_path) {
-            eprintln!("Failed to back up {output_path}: {e}");
+            error!("failed to back up {output_path}: {e}");
             process::exit(1);
         }
-        println!("Backed up existing {output_path} to {backup_path}");
+        notice!("Backed up existing {output_path} to {backup_path}");
     }
 
     match std::fs::write(output_path, &content) {
         Ok(()) => {
-            println!(
+            notice!(
                 "Wrote {} channels to {output_path}",
                 all_channels.len()
             );
         }
         Err(e) => {
-            eprintln!("Failed to write {output_path}: {e}");
+            error!("failed to write {output_path}: {e}");
             process::exit(1);
         }
     }
+
+    // Write etc/channels.m3u alongside channels.conf: no XMLTV guide exists
+    // yet at scan time, so there's no `url-tvg` to point at, but the
+    // `tvg-id`s already match what `save-xmltv` will later write for these
+    // same channels.
+    let m3u_path = "etc/channels.m3u";
+    let m3u = generate_playlist(&all_channels, None, DEFAULT_URL_TEMPLATE);
+    match std::fs::write(m3u_path, &m3u) {
+        Ok(()) => notice!("Wrote {} channels to {m3u_path}", all_channels.len()),
+        Err(e) => error!("failed to write {m3u_path}: {e}"),
+    }
 }
 
+/// Format `ch` as a zap-style colon-separated channel line, in whichever of
+/// the four layouts [`channel::parse_zap_line`] matches its delivery
+/// system (DVB-T: 13 fields, DVB-C: 9, DVB-S: 8, ATSC: 6).
 fn channel_to_zap_line(ch: &Channel) -> String {
-    format!(
-        "{}:{}:{}:{}:{}:{}:{}:{}:{}:{}:{}:{}:{}",
-        ch.name,
-        ch.frequency,
-        ch.inversion,
-        ch.bandwidth,
-        ch.fec_hp,
-        ch.fec_lp,
-        ch.modulation,
-        ch.transmission_mode,
-        ch.guard_interval,
-        ch.hierarchy,
-        ch.video_pid,
-        ch.audio_pid,
-        ch.service_id,
-    )
+    match &ch.tuning {
+        Tuning::DvbT {
+            inversion,
+            bandwidth,
+            fec_hp,
+            fec_lp,
+            modulation,
+            transmission_mode,
+            guard_interval,
+            hierarchy,
+        } => format!(
+            "{}:{}:{}:{}:{}:{}:{}:{}:{}:{}:{}:{}:{}",
+            ch.name,
+            ch.frequency,
+            inversion,
+            bandwidth,
+            fec_hp,
+            fec_lp,
+            modulation,
+            transmission_mode,
+            guard_interval,
+            hierarchy,
+            ch.video_pid,
+            ch.audio_pid,
+            ch.service_id,
+        ),
+        Tuning::DvbC {
+            inversion,
+            symbol_rate,
+            fec,
+            modulation,
+        } => format!(
+            "{}:{}:{}:{}:{}:{}:{}:{}:{}",
+            ch.name, ch.frequency, inversion, symbol_rate, fec, modulation, ch.video_pid, ch.audio_pid, ch.service_id,
+        ),
+        Tuning::DvbS {
+            polarization,
+            symbol_rate,
+            fec,
+            ..
+        } => format!(
+            "{}:{}:{}:{}:{}:{}:{}:{}",
+            ch.name, ch.frequency, polarization, symbol_rate, fec, ch.video_pid, ch.audio_pid, ch.service_id,
+        ),
+        Tuning::Atsc { modulation } => format!(
+            "{}:{}:{}:{}:{}:{}",
+            ch.name, ch.frequency, modulation, ch.video_pid, ch.audio_pid, ch.service_id,
+        ),
+    }
+}
+
+fn cmd_record(args: &[String]) {
+    let channel_name = match args.iter().position(|a| a == "-c" || a == "--channel") {
+        Some(i) => match args.get(i + 1) {
+            Some(name) => name.clone(),
+            None => {
+                error!("missing value for {}", args[i]);
+                print_record_usage();
+            }
+        },
+        None => {
+            error!("-c or --channel is required");
+            print_record_usage();
+        }
+    };
+
+    let output_path = match args.iter().position(|a| a == "-o" || a == "--output") {
+        Some(i) => match args.get(i + 1) {
+            Some(path) => path.clone(),
+            None => {
+                error!("missing value for {}", args[i]);
+                print_record_usage();
+            }
+        },
+        None => "capture.mp4".to_string(),
+    };
+
+    let duration_secs: u64 = match args.iter().position(|a| a == "-d" || a == "--duration") {
+        Some(i) => match args.get(i + 1).and_then(|v| v.parse().ok()) {
+            Some(secs) => secs,
+            None => {
+                error!("missing or invalid value for {}", args[i]);
+                print_record_usage();
+            }
+        },
+        None => 30,
+    };
+
+    let conf_path = Path::new("etc/channels.conf");
+    let channels = match channel::parse_channel_list(conf_path) {
+        Ok(channels) => channels,
+        Err(e) => {
+            error!("parsing channels.conf: {e}");
+            process::exit(1);
+        }
+    };
+
+    let target = match channels.iter().find(|ch| ch.name == channel_name) {
+        Some(ch) => ch,
+        None => {
+            error!("channel \"{channel_name}\" not found in {}", conf_path.display());
+            process::exit(1);
+        }
+    };
+
+    let devices = dvb_device::detect_devices();
+    if devices.is_empty() {
+        error!("no DVB-T devices found.");
+        process::exit(1);
+    }
+
+    let adapter: u32 = devices[0]
+        .adapter_name
+        .strip_prefix("dvb")
+        .and_then(|s| s.split('.').next())
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0);
+
+    let tuner = match tuner::Tuner::open(adapter) {
+        Ok(t) => t,
+        Err(e) => {
+            error!("failed to open tuner: {e}");
+            process::exit(1);
+        }
+    };
+
+    notice!("Tuning to {} ({} MHz)...", target.name, target.frequency / 1_000_000);
+    if let Err(e) = tuner.tune(target, &tuner::TuneConfig::default()) {
+        error!("failed to tune: {e}");
+        process::exit(1);
+    }
+
+    if !tuner.has_lock() {
+        warn!("frontend lost lock before recording");
+    }
+
+    // A ".ts" output asks for a clean single-program transport stream (raw
+    // PIDs plus a freshly synthesized PAT/PMT) instead of the MP4 remux path
+    // below, so the recording can be re-muxed or played back independent of
+    // the rest of the original multiplex.
+    if output_path.ends_with(".ts") {
+        notice!("Recording {duration_secs}s to {output_path}...");
+        match remux::remux_to_ts(adapter, target, duration_secs, Path::new(&output_path)) {
+            Ok(()) => notice!("Wrote {output_path}"),
+            Err(e) => {
+                error!("failed to write {output_path}: {e}");
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let mut video_demux = match dmx::open_demux_pes(adapter, target.video_pid, dmx::DMX_PES_VIDEO) {
+        Ok(f) => f,
+        Err(e) => {
+            error!("failed to open video demux: {e}");
+            process::exit(1);
+        }
+    };
+
+    let mut audio_demux = match dmx::open_demux_pes(adapter, target.audio_pid, dmx::DMX_PES_AUDIO) {
+        Ok(f) => f,
+        Err(e) => {
+            error!("failed to open audio demux: {e}");
+            process::exit(1);
+        }
+    };
+
+    notice!("Recording {duration_secs}s to {output_path}...");
+    let video_samples = read_pes_samples(&mut video_demux, duration_secs);
+    let audio_samples = read_pes_samples(&mut audio_demux, duration_secs);
+
+    let tracks = vec![
+        mp4::Track {
+            handler_type: b"vide",
+            samples: video_samples,
+            sample_duration: 3000, // 30 fps at the 90 kHz timescale
+        },
+        mp4::Track {
+            handler_type: b"soun",
+            samples: audio_samples,
+            sample_duration: 1920, // 1024 samples at 48 kHz, expressed in 90 kHz ticks
+        },
+    ];
+
+    match mp4::write_mp4(Path::new(&output_path), &tracks) {
+        Ok(()) => notice!("Wrote {output_path}"),
+        Err(e) => {
+            error!("failed to write {output_path}: {e}");
+            process::exit(1);
+        }
+    }
+}
+
+/// Read PES packets from an already-filtered demux device for `duration_secs`,
+/// stripping each packet's header down to its elementary stream payload.
+fn read_pes_samples(demux_file: &mut std::fs::File, duration_secs: u64) -> Vec<Vec<u8>> {
+    let mut samples = Vec::new();
+    let mut buf = [0u8; 64 * 1024];
+    let deadline = Instant::now() + Duration::from_secs(duration_secs);
+
+    while Instant::now() < deadline {
+        match demux_file.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => {
+                if let Some(payload) = pes::strip_pes_header(&buf[..n]) {
+                    samples.push(payload.to_vec());
+                }
+            }
+            Err(_) => break,
+        }
+    }
+
+    samples
+}
+
+fn print_record_usage() -> ! {
+    eprintln!("Usage: epgrab record -c <channel> [-o <file.mp4>|<file.ts>] [-d <seconds>]");
+    process::exit(1);
 }
 
 #[cfg(test)]
@@ -947,25 +1741,6 @@ mod tests {
         assert_eq!(xml_escape(""), "");
     }
 
-    // --- format_xmltv_time ---
-
-    #[test]
-    fn test_format_xmltv_time_format() {
-        // Just verify it produces a properly formatted string
-        let result = format_xmltv_time(0);
-        // Should match: YYYYMMDDHHmmSS +HHMM or -HHMM
-        assert_eq!(result.len(), 20); // "19700101HHMMSS +HHMM"
-        assert!(result.contains(' ')); // space between datetime and timezone
-    }
-
-    #[test]
-    fn test_format_xmltv_time_known_timestamp() {
-        // 946684800 = 2000-01-01 00:00:00 UTC
-        let result = format_xmltv_time(946684800);
-        // The output depends on local timezone, but should start with 2000
-        assert!(result.starts_with("2000"));
-    }
-
     // --- channel_to_zap_line ---
 
     #[test]
@@ -973,17 +1748,20 @@ mod tests {
         let ch = Channel {
             name: "公視".to_string(),
             frequency: 557000000,
-            inversion: "INVERSION_AUTO".to_string(),
-            bandwidth: "BANDWIDTH_6_MHZ".to_string(),
-            fec_hp: "FEC_AUTO".to_string(),
-            fec_lp: "FEC_AUTO".to_string(),
-            modulation: "QAM_64".to_string(),
-            transmission_mode: "TRANSMISSION_MODE_8K".to_string(),
-            guard_interval: "GUARD_INTERVAL_1_8".to_string(),
-            hierarchy: "HIERARCHY_NONE".to_string(),
             video_pid: 4097,
             audio_pid: 4098,
             service_id: 1,
+            tuning: Tuning::DvbT {
+                inversion: channel::Inversion::Auto,
+                bandwidth: channel::Bandwidth::Mhz6,
+                fec_hp: channel::Fec::Auto,
+                fec_lp: channel::Fec::Auto,
+                modulation: channel::Modulation::Qam64,
+                transmission_mode: channel::TransmissionMode::K8,
+                guard_interval: channel::GuardInterval::Eighth,
+                hierarchy: channel::Hierarchy::None,
+            },
+            elementary_streams: Vec::new(),
         };
         let line = channel_to_zap_line(&ch);
         assert_eq!(
@@ -998,17 +1776,20 @@ mod tests {
         let ch = Channel {
             name: "TestCH".to_string(),
             frequency: 563000000,
-            inversion: "INVERSION_AUTO".to_string(),
-            bandwidth: "BANDWIDTH_6_MHZ".to_string(),
-            fec_hp: "FEC_2_3".to_string(),
-            fec_lp: "FEC_AUTO".to_string(),
-            modulation: "QAM_64".to_string(),
-            transmission_mode: "TRANSMISSION_MODE_8K".to_string(),
-            guard_interval: "GUARD_INTERVAL_1_8".to_string(),
-            hierarchy: "HIERARCHY_NONE".to_string(),
             video_pid: 100,
             audio_pid: 101,
             service_id: 42,
+            tuning: Tuning::DvbT {
+                inversion: channel::Inversion::Auto,
+                bandwidth: channel::Bandwidth::Mhz6,
+                fec_hp: channel::Fec::TwoThirds,
+                fec_lp: channel::Fec::Auto,
+                modulation: channel::Modulation::Qam64,
+                transmission_mode: channel::TransmissionMode::K8,
+                guard_interval: channel::GuardInterval::Eighth,
+                hierarchy: channel::Hierarchy::None,
+            },
+            elementary_streams: Vec::new(),
         };
         let line = channel_to_zap_line(&ch);
         let fields: Vec<&str> = line.split(':').collect();
@@ -1033,7 +1814,7 @@ mod tests {
             language: "eng".to_string(),
         }];
 
-        let xml = generate_xmltv("TestChannel", &events, false);
+        let xml = generate_xmltv("TestChannel", &events, false, &TimeOffset::Auto);
         assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>"));
         assert!(xml.contains("<tv generator-info-name=\"epgrab\">"));
         assert!(xml.contains("<channel id=\"TestChannel\">"));
@@ -1057,7 +1838,7 @@ mod tests {
             language: "eng".to_string(),
         }];
 
-        let xml = generate_xmltv("CH1", &events, true);
+        let xml = generate_xmltv("CH1", &events, true, &TimeOffset::Auto);
         assert!(xml.contains("<?xml-stylesheet type=\"text/xsl\" href=\"epg.xsl\"?>"));
     }
 
@@ -1074,7 +1855,7 @@ mod tests {
             language: String::new(),
         }];
 
-        let xml = generate_xmltv("CH&1", &events, false);
+        let xml = generate_xmltv("CH&1", &events, false, &TimeOffset::Auto);
         assert!(xml.contains("CH&amp;1"));
         assert!(xml.contains("A &amp; B &lt;Show&gt;"));
     }
@@ -1092,7 +1873,7 @@ mod tests {
             language: "eng".to_string(),
         }];
 
-        let xml = generate_xmltv("CH1", &events, false);
+        let xml = generate_xmltv("CH1", &events, false, &TimeOffset::Auto);
         assert!(!xml.contains("<desc"));
     }
 
@@ -1109,17 +1890,108 @@ mod tests {
             language: String::new(),
         }];
 
-        let xml = generate_xmltv("CH1", &events, false);
+        let xml = generate_xmltv("CH1", &events, false, &TimeOffset::Auto);
         assert!(xml.contains("<title>Show</title>")); // no lang attr
     }
 
     #[test]
     fn test_generate_xmltv_empty_events() {
-        let xml = generate_xmltv("CH1", &[], false);
+        let xml = generate_xmltv("CH1", &[], false, &TimeOffset::Auto);
         assert!(xml.contains("<channel id=\"CH1\">"));
         assert!(!xml.contains("<programme"));
     }
 
+    // --- generate_combined_xmltv ---
+
+    #[test]
+    fn test_generate_combined_xmltv_multiple_channels() {
+        let ev1 = vec![eit::EitEvent {
+            service_id: 1,
+            event_id: 1,
+            start_time: 946684800,
+            duration: 1800,
+            running_status: 0,
+            event_name: "Show A".to_string(),
+            description: String::new(),
+            language: "eng".to_string(),
+        }];
+        let ev2 = vec![eit::EitEvent {
+            service_id: 2,
+            event_id: 1,
+            start_time: 946684800,
+            duration: 1800,
+            running_status: 0,
+            event_name: "Show B".to_string(),
+            description: String::new(),
+            language: "eng".to_string(),
+        }];
+
+        let channels = vec![("CH1", ev1), ("CH2", ev2)];
+        let xml = generate_combined_xmltv(&channels, false, &TimeOffset::Auto);
+        assert_eq!(xml.matches("<tv ").count(), 1);
+        assert!(xml.contains("<channel id=\"CH1\">"));
+        assert!(xml.contains("<channel id=\"CH2\">"));
+        assert!(xml.contains("Show A"));
+        assert!(xml.contains("Show B"));
+    }
+
+    // --- generate_playlist ---
+
+    fn test_channel(name: &str, frequency: u64, service_id: u16) -> Channel {
+        Channel {
+            name: name.to_string(),
+            frequency,
+            video_pid: 100,
+            audio_pid: 101,
+            service_id,
+            tuning: Tuning::DvbT {
+                inversion: channel::Inversion::Auto,
+                bandwidth: channel::Bandwidth::Mhz6,
+                fec_hp: channel::Fec::Auto,
+                fec_lp: channel::Fec::Auto,
+                modulation: channel::Modulation::Qam64,
+                transmission_mode: channel::TransmissionMode::K8,
+                guard_interval: channel::GuardInterval::Eighth,
+                hierarchy: channel::Hierarchy::None,
+            },
+            elementary_streams: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_generate_playlist_basic() {
+        let channels = vec![test_channel("公視", 557_000_000, 1)];
+        let m3u = generate_playlist(&channels, Some("http://host/guide.xml"), DEFAULT_URL_TEMPLATE);
+        assert!(m3u.starts_with("#EXTM3U url-tvg=\"http://host/guide.xml\"\n"));
+        assert!(m3u.contains("tvg-id=\"公視\""));
+        assert!(m3u.contains("group-title=\"557 MHz\""));
+        assert!(m3u.contains("dvb://557000000/1"));
+    }
+
+    #[test]
+    fn test_generate_playlist_tvg_id_matches_channel_id() {
+        let channels = vec![test_channel("A & B", 600_000_000, 5)];
+        let m3u = generate_playlist(&channels, Some("http://host/guide.xml"), DEFAULT_URL_TEMPLATE);
+        let xml = generate_xmltv("A & B", &[], false, &TimeOffset::Auto);
+        assert!(m3u.contains("tvg-id=\"A &amp; B\""));
+        assert!(xml.contains("<channel id=\"A &amp; B\">"));
+    }
+
+    #[test]
+    fn test_generate_playlist_custom_url_template() {
+        let channels = vec![test_channel("CH1", 500_000_000, 9)];
+        let m3u = generate_playlist(&channels, Some("http://host/guide.xml"), "rtp://239.0.0.{sid}:1234");
+        assert!(m3u.contains("rtp://239.0.0.9:1234"));
+    }
+
+    #[test]
+    fn test_generate_playlist_no_guide_url_omits_attribute() {
+        let channels = vec![test_channel("CH1", 500_000_000, 9)];
+        let m3u = generate_playlist(&channels, None, DEFAULT_URL_TEMPLATE);
+        assert!(m3u.starts_with("#EXTM3U\n"));
+        assert!(!m3u.contains("url-tvg"));
+    }
+
     // --- percent_decode ---
 
     #[test]
@@ -1156,7 +2028,7 @@ mod tests {
 
     fn response_str(request_line: &str, epg_dir: &Path) -> String {
         let mut buf: Vec<u8> = Vec::new();
-        handle_request(&mut buf, request_line, epg_dir);
+        handle_request(&mut buf, request_line, "", epg_dir, &[], "127.0.0.1:3000");
         String::from_utf8(buf).unwrap()
     }
 
@@ -1184,6 +2056,105 @@ mod tests {
         assert!(resp.starts_with("HTTP/1.1 200 OK"));
         assert!(resp.contains("Content-Type: application/xml"));
         assert!(resp.contains("<tv>data</tv>"));
+        assert!(resp.contains("Last-Modified: "));
+        assert!(resp.contains("ETag: "));
+    }
+
+    fn served_etag(dir: &Path, request_line: &str) -> String {
+        let mut buf: Vec<u8> = Vec::new();
+        handle_request(&mut buf, request_line, "", dir, &[], "127.0.0.1:3000");
+        let resp = String::from_utf8(buf).unwrap();
+        resp.lines()
+            .find_map(|l| l.strip_prefix("ETag: "))
+            .expect("response should carry an ETag")
+            .trim_end_matches('\r')
+            .to_string()
+    }
+
+    #[test]
+    fn test_serve_if_none_match_returns_304() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("ch1.eit.xml"), "<tv>data</tv>").unwrap();
+
+        let etag = served_etag(dir.path(), "GET /ch1.eit.xml HTTP/1.1");
+
+        let mut buf: Vec<u8> = Vec::new();
+        let headers = format!("If-None-Match: {etag}\r\n\r\n");
+        let status = handle_request(
+            &mut buf,
+            "GET /ch1.eit.xml HTTP/1.1",
+            &headers,
+            dir.path(),
+            &[],
+            "127.0.0.1:3000",
+        );
+        let resp = String::from_utf8(buf).unwrap();
+        assert_eq!(status, 304);
+        assert!(resp.starts_with("HTTP/1.1 304 Not Modified"));
+        assert!(!resp.contains("<tv>data</tv>"));
+    }
+
+    #[test]
+    fn test_serve_if_none_match_stale_returns_200() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("ch1.eit.xml"), "<tv>data</tv>").unwrap();
+
+        let mut buf: Vec<u8> = Vec::new();
+        let headers = "If-None-Match: \"stale\"\r\n\r\n";
+        let status = handle_request(
+            &mut buf,
+            "GET /ch1.eit.xml HTTP/1.1",
+            headers,
+            dir.path(),
+            &[],
+            "127.0.0.1:3000",
+        );
+        assert_eq!(status, 200);
+        assert!(String::from_utf8(buf).unwrap().contains("<tv>data</tv>"));
+    }
+
+    #[test]
+    fn test_serve_if_modified_since_future_returns_304() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("ch1.eit.xml"), "<tv>data</tv>").unwrap();
+
+        let mut buf: Vec<u8> = Vec::new();
+        let headers = format!("If-Modified-Since: {}\r\n\r\n", format_http_date(unix_now() + 3600));
+        let status = handle_request(
+            &mut buf,
+            "GET /ch1.eit.xml HTTP/1.1",
+            &headers,
+            dir.path(),
+            &[],
+            "127.0.0.1:3000",
+        );
+        assert_eq!(status, 304);
+    }
+
+    #[test]
+    fn test_serve_if_modified_since_past_returns_200() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("ch1.eit.xml"), "<tv>data</tv>").unwrap();
+
+        let mut buf: Vec<u8> = Vec::new();
+        let headers = format!("If-Modified-Since: {}\r\n\r\n", format_http_date(unix_now() - 3600));
+        let status = handle_request(
+            &mut buf,
+            "GET /ch1.eit.xml HTTP/1.1",
+            &headers,
+            dir.path(),
+            &[],
+            "127.0.0.1:3000",
+        );
+        assert_eq!(status, 200);
+    }
+
+    #[test]
+    fn test_format_http_date_roundtrip() {
+        let ts = 784111777; // 1994-11-06 08:49:37 UTC
+        let formatted = format_http_date(ts);
+        assert_eq!(formatted, "Sun, 06 Nov 1994 08:49:37 GMT");
+        assert_eq!(parse_http_date(&formatted), Some(ts));
     }
 
     #[test]
@@ -1232,6 +2203,28 @@ mod tests {
         assert!(resp.starts_with("HTTP/1.1 400 Bad Request"));
     }
 
+    #[test]
+    fn test_serve_playlist_with_channels() {
+        let dir = tempfile::tempdir().unwrap();
+        let channels = vec![test_channel("CH1", 557_000_000, 1)];
+        let mut buf: Vec<u8> = Vec::new();
+        handle_request(&mut buf, "GET /playlist.m3u HTTP/1.1", "", dir.path(), &channels, "example.com:8080");
+        let resp = String::from_utf8(buf).unwrap();
+
+        assert!(resp.starts_with("HTTP/1.1 200 OK"));
+        assert!(resp.contains("Content-Type: audio/x-mpegurl"));
+        assert!(resp.contains("url-tvg=\"http://example.com:8080/guide.xml\""));
+        assert!(resp.contains("tvg-id=\"CH1\""));
+    }
+
+    #[test]
+    fn test_serve_playlist_no_channels() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let resp = response_str("GET /playlist.m3u HTTP/1.1", dir.path());
+        assert!(resp.starts_with("HTTP/1.1 404 Not Found"));
+    }
+
     #[test]
     fn test_serve_percent_encoded_filename() {
         let dir = tempfile::tempdir().unwrap();
@@ -1268,6 +2261,61 @@ mod tests {
         );
         assert!(!resp.contains("sensitive data"));
     }
+
+    // --- /live/ routing ---
+
+    #[test]
+    fn test_parse_live_playlist_path_accepts_service_id() {
+        assert_eq!(parse_live_playlist_path("42.m3u8"), Some(42));
+    }
+
+    #[test]
+    fn test_parse_live_playlist_path_rejects_non_numeric() {
+        assert_eq!(parse_live_playlist_path("abc.m3u8"), None);
+    }
+
+    #[test]
+    fn test_parse_live_playlist_path_rejects_wrong_extension() {
+        assert_eq!(parse_live_playlist_path("42.ts"), None);
+    }
+
+    #[test]
+    fn test_parse_live_segment_path_accepts_sid_and_index() {
+        assert_eq!(parse_live_segment_path("42/seg7.ts"), Some((42, 7)));
+    }
+
+    #[test]
+    fn test_parse_live_segment_path_rejects_malformed_segment_name() {
+        assert_eq!(parse_live_segment_path("42/segment7.ts"), None);
+        assert_eq!(parse_live_segment_path("42/seg7.mp4"), None);
+        assert_eq!(parse_live_segment_path("42seg7.ts"), None);
+    }
+
+    #[test]
+    fn test_serve_live_playlist_unknown_service_id() {
+        let dir = tempfile::tempdir().unwrap();
+        let channels = vec![test_channel("CH1", 500_000_000, 9)];
+        let mut buf: Vec<u8> = Vec::new();
+        let status = handle_request(&mut buf, "GET /live/999.m3u8 HTTP/1.1", "", dir.path(), &channels, "127.0.0.1:3000");
+        assert_eq!(status, 404);
+        assert!(String::from_utf8(buf).unwrap().starts_with("HTTP/1.1 404 Not Found"));
+    }
+
+    #[test]
+    fn test_serve_live_segment_without_session_is_404() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut buf: Vec<u8> = Vec::new();
+        let status = handle_request(&mut buf, "GET /live/31337/seg0.ts HTTP/1.1", "", dir.path(), &[], "127.0.0.1:3000");
+        assert_eq!(status, 404);
+    }
+
+    #[test]
+    fn test_serve_live_malformed_path_is_400() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut buf: Vec<u8> = Vec::new();
+        let status = handle_request(&mut buf, "GET /live/not-a-path HTTP/1.1", "", dir.path(), &[], "127.0.0.1:3000");
+        assert_eq!(status, 400);
+    }
 }
 
 fn format_unix_timestamp(ts: i64) -> String {
@@ -0,0 +1,374 @@
+// --- ISO-BMFF (MP4) container writer ---
+//
+// Every box in the format is `size(4) + fourcc(4) + body`, where `size`
+// covers the whole box including its own 8-byte header. Since the body is
+// built incrementally, `write_box` reserves a zero placeholder, runs the
+// closure that appends the body, then backpatches the real length. A "full
+// box" is the same thing with a version byte and 24-bit flags field at the
+// front of the body, used by most `moov` descendants.
+
+const TIMESCALE: u32 = 90_000; // 90 kHz, matches the PES PTS clock
+
+/// Write a box: a 4-byte big-endian size, a 4-byte FourCC, then whatever
+/// `body` appends to `buf`. The size is backpatched once `body` returns.
+pub fn write_box(buf: &mut Vec<u8>, fourcc: &[u8; 4], body: impl FnOnce(&mut Vec<u8>)) {
+    let size_pos = buf.len();
+    buf.extend_from_slice(&[0u8; 4]); // placeholder
+    buf.extend_from_slice(fourcc);
+    body(buf);
+    let size = (buf.len() - size_pos) as u32;
+    buf[size_pos..size_pos + 4].copy_from_slice(&size.to_be_bytes());
+}
+
+/// Write a "full box": a [`write_box`] whose body starts with a version
+/// byte and a 24-bit flags field.
+pub fn write_full_box(
+    buf: &mut Vec<u8>,
+    fourcc: &[u8; 4],
+    version: u8,
+    flags: u32,
+    body: impl FnOnce(&mut Vec<u8>),
+) {
+    write_box(buf, fourcc, |buf| {
+        buf.push(version);
+        buf.extend_from_slice(&flags.to_be_bytes()[1..4]);
+        body(buf);
+    });
+}
+
+/// One elementary-stream track's samples, ready to mux.
+pub struct Track {
+    pub handler_type: &'static [u8; 4], // "vide" or "soun"
+    pub samples: Vec<Vec<u8>>,
+    /// Duration of one sample, in `TIMESCALE` ticks (constant frame/packet
+    /// rate is assumed; no per-sample CTS offsets are recorded).
+    pub sample_duration: u32,
+}
+
+fn write_ftyp(buf: &mut Vec<u8>) {
+    write_box(buf, b"ftyp", |buf| {
+        buf.extend_from_slice(b"isom"); // major_brand
+        buf.extend_from_slice(&[0, 0, 0, 1]); // minor_version
+        buf.extend_from_slice(b"isom");
+        buf.extend_from_slice(b"mp41");
+    });
+}
+
+fn write_mvhd(buf: &mut Vec<u8>, duration: u32) {
+    write_full_box(buf, b"mvhd", 0, 0, |buf| {
+        buf.extend_from_slice(&[0u8; 4]); // creation_time
+        buf.extend_from_slice(&[0u8; 4]); // modification_time
+        buf.extend_from_slice(&TIMESCALE.to_be_bytes());
+        buf.extend_from_slice(&duration.to_be_bytes());
+        buf.extend_from_slice(&0x0001_0000u32.to_be_bytes()); // rate 1.0
+        buf.extend_from_slice(&0x0100u16.to_be_bytes()); // volume 1.0
+        buf.extend_from_slice(&[0u8; 2]); // reserved
+        buf.extend_from_slice(&[0u8; 8]); // reserved
+        // unity matrix
+        for v in [0x0001_0000u32, 0, 0, 0, 0x0001_0000, 0, 0, 0, 0x4000_0000] {
+            buf.extend_from_slice(&v.to_be_bytes());
+        }
+        buf.extend_from_slice(&[0u8; 24]); // pre_defined
+        buf.extend_from_slice(&2u32.to_be_bytes()); // next_track_ID
+    });
+}
+
+fn write_tkhd(buf: &mut Vec<u8>, track_id: u32, duration: u32) {
+    write_full_box(buf, b"tkhd", 0, 0x000007, |buf| {
+        buf.extend_from_slice(&[0u8; 4]); // creation_time
+        buf.extend_from_slice(&[0u8; 4]); // modification_time
+        buf.extend_from_slice(&track_id.to_be_bytes());
+        buf.extend_from_slice(&[0u8; 4]); // reserved
+        buf.extend_from_slice(&duration.to_be_bytes());
+        buf.extend_from_slice(&[0u8; 8]); // reserved
+        buf.extend_from_slice(&[0u8; 2]); // layer
+        buf.extend_from_slice(&[0u8; 2]); // alternate_group
+        buf.extend_from_slice(&[0u8; 2]); // volume
+        buf.extend_from_slice(&[0u8; 2]); // reserved
+        for v in [0x0001_0000u32, 0, 0, 0, 0x0001_0000, 0, 0, 0, 0x4000_0000] {
+            buf.extend_from_slice(&v.to_be_bytes());
+        }
+        buf.extend_from_slice(&[0u8; 4]); // width (fixed-point, unused for audio)
+        buf.extend_from_slice(&[0u8; 4]); // height
+    });
+}
+
+fn write_mdhd(buf: &mut Vec<u8>, duration: u32) {
+    write_full_box(buf, b"mdhd", 0, 0, |buf| {
+        buf.extend_from_slice(&[0u8; 4]); // creation_time
+        buf.extend_from_slice(&[0u8; 4]); // modification_time
+        buf.extend_from_slice(&TIMESCALE.to_be_bytes());
+        buf.extend_from_slice(&duration.to_be_bytes());
+        buf.extend_from_slice(&0x55C4u16.to_be_bytes()); // language "und"
+        buf.extend_from_slice(&[0u8; 2]); // pre_defined
+    });
+}
+
+fn write_hdlr(buf: &mut Vec<u8>, handler_type: &[u8; 4]) {
+    write_full_box(buf, b"hdlr", 0, 0, |buf| {
+        buf.extend_from_slice(&[0u8; 4]); // pre_defined
+        buf.extend_from_slice(handler_type);
+        buf.extend_from_slice(&[0u8; 12]); // reserved
+        buf.extend_from_slice(b"epgrab\0"); // name
+    });
+}
+
+fn write_stsd_video(buf: &mut Vec<u8>) {
+    write_full_box(buf, b"stsd", 0, 0, |buf| {
+        buf.extend_from_slice(&1u32.to_be_bytes()); // entry_count
+        write_box(buf, b"avc1", |buf| {
+            buf.extend_from_slice(&[0u8; 6]); // reserved
+            buf.extend_from_slice(&1u16.to_be_bytes()); // data_reference_index
+            buf.extend_from_slice(&[0u8; 16]); // pre_defined/reserved
+            buf.extend_from_slice(&1920u16.to_be_bytes()); // width (best-effort default)
+            buf.extend_from_slice(&1080u16.to_be_bytes()); // height
+            buf.extend_from_slice(&0x0048_0000u32.to_be_bytes()); // horizresolution 72dpi
+            buf.extend_from_slice(&0x0048_0000u32.to_be_bytes()); // vertresolution
+            buf.extend_from_slice(&[0u8; 4]); // reserved
+            buf.extend_from_slice(&1u16.to_be_bytes()); // frame_count
+            buf.extend_from_slice(&[0u8; 32]); // compressorname
+            buf.extend_from_slice(&0x0018u16.to_be_bytes()); // depth 24
+            buf.extend_from_slice(&0xFFFFu16.to_be_bytes()); // pre_defined
+            write_box(buf, b"avcC", |buf| {
+                // AVCDecoderConfigurationRecord with no SPS/PPS captured yet;
+                // a real stream's parameter sets would be inserted here.
+                buf.push(1); // configurationVersion
+                buf.push(0x4D); // AVCProfileIndication (Main)
+                buf.push(0x40); // profile_compatibility
+                buf.push(0x1F); // AVCLevelIndication (3.1)
+                buf.push(0xFF); // reserved(6) + lengthSizeMinusOne=3 (4-byte NAL lengths)
+                buf.push(0xE0); // reserved(3) + numOfSequenceParameterSets=0
+                buf.push(0); // numOfPictureParameterSets=0
+            });
+        });
+    });
+}
+
+fn write_stsd_audio(buf: &mut Vec<u8>, sample_rate: u32, channels: u16) {
+    write_full_box(buf, b"stsd", 0, 0, |buf| {
+        buf.extend_from_slice(&1u32.to_be_bytes()); // entry_count
+        write_box(buf, b"mp4a", |buf| {
+            buf.extend_from_slice(&[0u8; 6]); // reserved
+            buf.extend_from_slice(&1u16.to_be_bytes()); // data_reference_index
+            buf.extend_from_slice(&[0u8; 8]); // reserved
+            buf.extend_from_slice(&channels.to_be_bytes());
+            buf.extend_from_slice(&16u16.to_be_bytes()); // samplesize
+            buf.extend_from_slice(&[0u8; 4]); // pre_defined/reserved
+            buf.extend_from_slice(&((sample_rate as u32) << 16).to_be_bytes());
+            write_box(buf, b"esds", |_buf| {
+                // An ES_Descriptor with AudioSpecificConfig would go here;
+                // left empty until ADTS header parsing feeds it one.
+            });
+        });
+    });
+}
+
+fn write_stbl(buf: &mut Vec<u8>, track: &Track, mdat_offset: u32) {
+    write_box(buf, b"stbl", |buf| {
+        if track.handler_type == b"vide" {
+            write_stsd_video(buf);
+        } else {
+            write_stsd_audio(buf, TIMESCALE, 2);
+        }
+
+        write_full_box(buf, b"stts", 0, 0, |buf| {
+            buf.extend_from_slice(&1u32.to_be_bytes()); // entry_count
+            buf.extend_from_slice(&(track.samples.len() as u32).to_be_bytes());
+            buf.extend_from_slice(&track.sample_duration.to_be_bytes());
+        });
+
+        write_full_box(buf, b"stsc", 0, 0, |buf| {
+            buf.extend_from_slice(&1u32.to_be_bytes()); // entry_count
+            buf.extend_from_slice(&1u32.to_be_bytes()); // first_chunk
+            buf.extend_from_slice(&(track.samples.len() as u32).to_be_bytes()); // samples_per_chunk
+            buf.extend_from_slice(&1u32.to_be_bytes()); // sample_description_index
+        });
+
+        write_full_box(buf, b"stsz", 0, 0, |buf| {
+            buf.extend_from_slice(&0u32.to_be_bytes()); // sample_size (0 = use table)
+            buf.extend_from_slice(&(track.samples.len() as u32).to_be_bytes());
+            for sample in &track.samples {
+                buf.extend_from_slice(&(sample.len() as u32).to_be_bytes());
+            }
+        });
+
+        write_full_box(buf, b"stco", 0, 0, |buf| {
+            buf.extend_from_slice(&1u32.to_be_bytes()); // entry_count
+            buf.extend_from_slice(&mdat_offset.to_be_bytes());
+        });
+    });
+}
+
+fn write_trak(buf: &mut Vec<u8>, track_id: u32, track: &Track, mdat_offset: u32) {
+    let duration = track.samples.len() as u32 * track.sample_duration;
+
+    write_box(buf, b"trak", |buf| {
+        write_tkhd(buf, track_id, duration);
+        write_box(buf, b"mdia", |buf| {
+            write_mdhd(buf, duration);
+            write_hdlr(buf, track.handler_type);
+            write_box(buf, b"minf", |buf| {
+                if track.handler_type == b"vide" {
+                    write_full_box(buf, b"vmhd", 0, 1, |buf| buf.extend_from_slice(&[0u8; 8]));
+                } else {
+                    write_full_box(buf, b"smhd", 0, 0, |buf| buf.extend_from_slice(&[0u8; 4]));
+                }
+                write_box(buf, b"dinf", |buf| {
+                    write_full_box(buf, b"dref", 0, 0, |buf| {
+                        buf.extend_from_slice(&1u32.to_be_bytes());
+                        write_full_box(buf, b"url ", 0, 1, |_| {});
+                    });
+                });
+                write_stbl(buf, track, mdat_offset);
+            });
+        });
+    });
+}
+
+fn write_moov(buf: &mut Vec<u8>, tracks: &[(u32, &Track, u32)]) {
+    let duration = tracks
+        .iter()
+        .map(|(_, t, _)| t.samples.len() as u32 * t.sample_duration)
+        .max()
+        .unwrap_or(0);
+
+    write_box(buf, b"moov", |buf| {
+        write_mvhd(buf, duration);
+        for (track_id, track, mdat_offset) in tracks {
+            write_trak(buf, *track_id, track, *mdat_offset);
+        }
+    });
+}
+
+/// Mux the given tracks' samples into an MP4 file at `path`: `ftyp`, then
+/// `moov` (whose `stco` chunk offsets point into the single `mdat` that
+/// follows), then `mdat` itself.
+pub fn write_mp4(path: &std::path::Path, tracks: &[Track]) -> Result<(), String> {
+    let mut buf = Vec::new();
+    write_ftyp(&mut buf);
+
+    // moov comes before mdat, so its size (and therefore mdat's offset)
+    // must be known before mdat's bytes are laid out. Render moov against
+    // a placeholder offset first to get its size, then render it again
+    // with the real offset now that it's known.
+    let placeholder: Vec<(u32, &Track, u32)> = tracks
+        .iter()
+        .enumerate()
+        .map(|(i, t)| (i as u32 + 1, t, 0))
+        .collect();
+    let mut probe = Vec::new();
+    write_moov(&mut probe, &placeholder);
+
+    let mdat_offset = (buf.len() + probe.len() + 8) as u32; // +8 for mdat's own header
+    let tracks_with_offset: Vec<(u32, &Track, u32)> = tracks
+        .iter()
+        .enumerate()
+        .map(|(i, t)| (i as u32 + 1, t, mdat_offset))
+        .collect();
+    write_moov(&mut buf, &tracks_with_offset);
+
+    write_box(&mut buf, b"mdat", |buf| {
+        for track in tracks {
+            for sample in &track.samples {
+                buf.extend_from_slice(sample);
+            }
+        }
+    });
+
+    std::fs::write(path, &buf).map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // --- write_box / write_full_box ---
+
+    #[test]
+    fn test_write_box_size_and_fourcc() {
+        let mut buf = Vec::new();
+        write_box(&mut buf, b"test", |buf| buf.extend_from_slice(&[1, 2, 3]));
+        assert_eq!(buf.len(), 11); // 4 size + 4 fourcc + 3 body
+        assert_eq!(&buf[0..4], &11u32.to_be_bytes());
+        assert_eq!(&buf[4..8], b"test");
+        assert_eq!(&buf[8..11], &[1, 2, 3]);
+    }
+
+    #[test]
+    fn test_write_box_empty_body() {
+        let mut buf = Vec::new();
+        write_box(&mut buf, b"free", |_| {});
+        assert_eq!(buf.len(), 8);
+        assert_eq!(&buf[0..4], &8u32.to_be_bytes());
+    }
+
+    #[test]
+    fn test_write_box_nested() {
+        let mut buf = Vec::new();
+        write_box(&mut buf, b"outr", |buf| {
+            write_box(buf, b"innr", |buf| buf.extend_from_slice(&[9]));
+        });
+        assert_eq!(&buf[0..4], &17u32.to_be_bytes()); // 8 (outer hdr) + 9 (inner box)
+        assert_eq!(&buf[8..12], &9u32.to_be_bytes());
+        assert_eq!(&buf[12..16], b"innr");
+        assert_eq!(buf[16], 9);
+    }
+
+    #[test]
+    fn test_write_full_box_version_and_flags() {
+        let mut buf = Vec::new();
+        write_full_box(&mut buf, b"mvhd", 1, 0x00ABCD, |buf| buf.push(0xFF));
+        assert_eq!(&buf[4..8], b"mvhd");
+        assert_eq!(buf[8], 1); // version
+        assert_eq!(&buf[9..12], &[0x00, 0xAB, 0xCD]); // 24-bit flags
+        assert_eq!(buf[12], 0xFF);
+        assert_eq!(&buf[0..4], &13u32.to_be_bytes());
+    }
+
+    // --- write_mp4 ---
+
+    fn sample_track(handler_type: &'static [u8; 4]) -> Track {
+        Track {
+            handler_type,
+            samples: vec![vec![0xAA; 10], vec![0xBB; 20]],
+            sample_duration: 3000,
+        }
+    }
+
+    #[test]
+    fn test_write_mp4_roundtrip_structure() {
+        let dir = std::env::temp_dir();
+        let path = dir.join(format!("epgrab-mp4-test-{:p}.mp4", &dir));
+        let tracks = vec![sample_track(b"vide"), sample_track(b"soun")];
+        write_mp4(&path, &tracks).unwrap();
+
+        let data = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(&data[4..8], b"ftyp");
+
+        // moov immediately follows ftyp; find it by its own box size.
+        let ftyp_len = u32::from_be_bytes(data[0..4].try_into().unwrap()) as usize;
+        assert_eq!(&data[ftyp_len + 4..ftyp_len + 8], b"moov");
+
+        let moov_len = u32::from_be_bytes(
+            data[ftyp_len..ftyp_len + 4].try_into().unwrap(),
+        ) as usize;
+        let mdat_pos = ftyp_len + moov_len;
+        assert_eq!(&data[mdat_pos + 4..mdat_pos + 8], b"mdat");
+
+        // mdat's payload is exactly the concatenated sample bytes.
+        let mdat_payload = &data[mdat_pos + 8..];
+        assert_eq!(mdat_payload.len(), 10 + 20 + 10 + 20);
+    }
+
+    #[test]
+    fn test_write_mp4_empty_tracks() {
+        let dir = std::env::temp_dir();
+        let path = dir.join(format!("epgrab-mp4-empty-{:p}.mp4", &dir));
+        write_mp4(&path, &[]).unwrap();
+        let data = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(&data[4..8], b"ftyp");
+    }
+}
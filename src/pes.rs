@@ -0,0 +1,116 @@
+// --- PES depacketization ---
+//
+// Elementary streams carried in a DVB transport stream are wrapped in PES
+// (Packetized Elementary Stream) packets: a start code, stream id, packet
+// length, then a set of optional fields (PTS/DTS among them) whose total
+// size is given by `PES_header_data_length`. Recording mode strips that
+// header off each packet so the raw access units can be muxed into MP4.
+
+/// Strip the PES header from a single PES packet, returning the elementary
+/// stream payload that follows it. Returns `None` if `data` doesn't start
+/// with a PES start code or is too short to contain a full header.
+pub fn strip_pes_header(data: &[u8]) -> Option<&[u8]> {
+    if data.len() < 9 || data[0] != 0x00 || data[1] != 0x00 || data[2] != 0x01 {
+        return None;
+    }
+
+    let pes_header_data_length = data[8] as usize;
+    let payload_start = 9 + pes_header_data_length;
+    if data.len() < payload_start {
+        return None;
+    }
+
+    Some(&data[payload_start..])
+}
+
+/// Extract the presentation timestamp (in 90 kHz ticks) from a PES packet's
+/// optional header, if present (`PTS_DTS_flags` bit set in byte 7).
+pub fn extract_pts(data: &[u8]) -> Option<u64> {
+    if data.len() < 14 || data[0] != 0x00 || data[1] != 0x00 || data[2] != 0x01 {
+        return None;
+    }
+
+    let pts_dts_flags = (data[7] >> 6) & 0x03;
+    if pts_dts_flags == 0 {
+        return None;
+    }
+
+    let pts_bytes = &data[9..14];
+    let pts = (((pts_bytes[0] >> 1) & 0x07) as u64) << 30
+        | (pts_bytes[1] as u64) << 22
+        | ((pts_bytes[2] >> 1) as u64) << 15
+        | (pts_bytes[3] as u64) << 7
+        | ((pts_bytes[4] >> 1) as u64);
+
+    Some(pts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // --- strip_pes_header ---
+
+    #[test]
+    fn test_strip_pes_header_no_optional_fields() {
+        let data = [0x00, 0x00, 0x01, 0xE0, 0x00, 0x00, 0x80, 0x00, 0x00, 0xAA, 0xBB];
+        assert_eq!(strip_pes_header(&data), Some(&[0xAA, 0xBB][..]));
+    }
+
+    #[test]
+    fn test_strip_pes_header_with_pts() {
+        // PES_header_data_length = 5 (one PTS field)
+        let mut data = vec![0x00, 0x00, 0x01, 0xE0, 0x00, 0x00, 0x80, 0x80, 0x05];
+        data.extend_from_slice(&[0x21, 0x00, 0x01, 0x00, 0x01]); // PTS field
+        data.extend_from_slice(&[0xCC, 0xDD]);
+        assert_eq!(strip_pes_header(&data), Some(&[0xCC, 0xDD][..]));
+    }
+
+    #[test]
+    fn test_strip_pes_header_bad_start_code() {
+        let data = [0x00, 0x00, 0x02, 0xE0, 0x00, 0x00, 0x80, 0x00, 0x00];
+        assert_eq!(strip_pes_header(&data), None);
+    }
+
+    #[test]
+    fn test_strip_pes_header_too_short() {
+        let data = [0x00, 0x00, 0x01, 0xE0];
+        assert_eq!(strip_pes_header(&data), None);
+    }
+
+    #[test]
+    fn test_strip_pes_header_truncated_optional_fields() {
+        // Claims 10 bytes of optional fields but only 2 are present
+        let data = [0x00, 0x00, 0x01, 0xE0, 0x00, 0x00, 0x80, 0x00, 0x0A, 0x01, 0x02];
+        assert_eq!(strip_pes_header(&data), None);
+    }
+
+    // --- extract_pts ---
+
+    #[test]
+    fn test_extract_pts_absent() {
+        let data = [0x00, 0x00, 0x01, 0xE0, 0x00, 0x00, 0x80, 0x00, 0x00, 0xAA, 0xBB];
+        assert_eq!(extract_pts(&data), None);
+    }
+
+    #[test]
+    fn test_extract_pts_zero() {
+        let mut data = vec![0x00, 0x00, 0x01, 0xE0, 0x00, 0x00, 0x80, 0x80, 0x05];
+        data.extend_from_slice(&[0x21, 0x00, 0x00, 0x00, 0x01]);
+        assert_eq!(extract_pts(&data), Some(0));
+    }
+
+    #[test]
+    fn test_extract_pts_nonzero() {
+        // PTS = 90000 (1 second at 90 kHz)
+        let pts: u64 = 90000;
+        let b0 = 0x21 | (((pts >> 30) & 0x07) as u8) << 1 | 0x01;
+        let b1 = ((pts >> 22) & 0xFF) as u8;
+        let b2 = ((((pts >> 15) & 0x7F) as u8) << 1) | 0x01;
+        let b3 = ((pts >> 7) & 0xFF) as u8;
+        let b4 = (((pts & 0x7F) as u8) << 1) | 0x01;
+        let mut data = vec![0x00, 0x00, 0x01, 0xE0, 0x00, 0x00, 0x80, 0x80, 0x05];
+        data.extend_from_slice(&[b0, b1, b2, b3, b4]);
+        assert_eq!(extract_pts(&data), Some(pts));
+    }
+}
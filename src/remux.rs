@@ -0,0 +1,397 @@
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::channel::Channel;
+use crate::dmx;
+use crate::scan::crc32_mpeg2;
+
+const TS_PACKET_SIZE: usize = 188;
+const TS_SYNC_BYTE: u8 = 0x47;
+
+/// PID the synthesized PMT is carried on. Arbitrary but fixed, since a
+/// standalone remux doesn't need to match the broadcaster's original PMT PID.
+const REMUX_PMT_PID: u16 = 0x1FFC;
+
+/// How often to re-emit the PAT/PMT pair while capturing, so a player that
+/// joins mid-stream (or a demuxer scanning forward) still finds them.
+const PAT_PMT_REPEAT_INTERVAL: Duration = Duration::from_secs(2);
+
+fn ts_header(pid: u16, payload_start: bool) -> [u8; 4] {
+    [
+        TS_SYNC_BYTE,
+        (if payload_start { 0x40 } else { 0x00 }) | ((pid >> 8) as u8 & 0x1F),
+        (pid & 0xFF) as u8,
+        0x10, // adaptation_field_control = payload only, continuity_counter = 0
+    ]
+}
+
+/// Wrap a single PSI section (no pointer-field continuation, always fits in
+/// one packet here since PAT/PMT for one program are tiny) in a TS packet,
+/// padding the remainder with stuffing bytes (`0xFF`).
+fn wrap_section_in_ts_packet(pid: u16, section: &[u8]) -> [u8; TS_PACKET_SIZE] {
+    let mut packet = [0xFFu8; TS_PACKET_SIZE];
+    packet[0..4].copy_from_slice(&ts_header(pid, true));
+    packet[4] = 0x00; // pointer_field: section starts immediately after it
+    let copy_len = section.len().min(TS_PACKET_SIZE - 5);
+    packet[5..5 + copy_len].copy_from_slice(&section[..copy_len]);
+    packet
+}
+
+fn build_pat_section(service_id: u16, pmt_pid: u16) -> Vec<u8> {
+    let section_length: u16 = 5 + 4 + 4; // 5 remaining header + 1 program entry + CRC
+    let mut data = vec![0u8; 3 + section_length as usize];
+    data[0] = 0x00; // table_id = PAT
+    data[1] = 0xB0 | ((section_length >> 8) as u8 & 0x0F);
+    data[2] = section_length as u8;
+    data[3] = 0x00;
+    data[4] = 0x01; // transport_stream_id
+    data[5] = 0xC1; // version_number = 0, current_next_indicator = 1
+    data[6] = 0x00; // section_number
+    data[7] = 0x00; // last_section_number
+    data[8] = (service_id >> 8) as u8;
+    data[9] = service_id as u8;
+    data[10] = 0xE0 | ((pmt_pid >> 8) as u8 & 0x1F);
+    data[11] = pmt_pid as u8;
+
+    let crc = crc32_mpeg2(&data[..12]);
+    data[12..16].copy_from_slice(&crc.to_be_bytes());
+    data
+}
+
+/// Look up the PMT `stream_type` the original broadcast announced for `pid`,
+/// falling back to 0x00 (reserved) if the channel wasn't discovered via PMT
+/// scanning and carries no elementary stream info.
+fn stream_type_for_pid(channel: &Channel, pid: u16) -> u8 {
+    channel
+        .elementary_streams
+        .iter()
+        .find(|s| s.pid == pid)
+        .map(|s| s.stream_type)
+        .unwrap_or(0x00)
+}
+
+fn build_pmt_section(service_id: u16, channel: &Channel) -> Vec<u8> {
+    let video_pid = channel.video_pid;
+    let audio_pid = channel.audio_pid;
+
+    // 9 remaining header bytes (up to and including program_info_length) +
+    // two 5-byte ES entries (no ES info) + 4-byte CRC.
+    let section_length: u16 = 9 + 5 + 5 + 4;
+    let mut data = vec![0u8; 3 + section_length as usize];
+    data[0] = 0x02; // table_id = PMT
+    data[1] = 0xB0 | ((section_length >> 8) as u8 & 0x0F);
+    data[2] = section_length as u8;
+    data[3] = (service_id >> 8) as u8;
+    data[4] = service_id as u8;
+    data[5] = 0xC1; // version_number = 0, current_next_indicator = 1
+    data[6] = 0x00; // section_number
+    data[7] = 0x00; // last_section_number
+    data[8] = 0xE0 | ((video_pid >> 8) as u8 & 0x1F);
+    data[9] = video_pid as u8; // PCR_PID: carried on the video stream
+    data[10] = 0xF0; // program_info_length = 0
+    data[11] = 0x00;
+
+    let mut pos = 12;
+    for pid in [video_pid, audio_pid] {
+        data[pos] = stream_type_for_pid(channel, pid);
+        data[pos + 1] = 0xE0 | ((pid >> 8) as u8 & 0x1F);
+        data[pos + 2] = pid as u8;
+        data[pos + 3] = 0xF0; // ES_info_length = 0
+        data[pos + 4] = 0x00;
+        pos += 5;
+    }
+
+    let crc = crc32_mpeg2(&data[..pos]);
+    data[pos..pos + 4].copy_from_slice(&crc.to_be_bytes());
+    data
+}
+
+fn write_pat_and_pmt(out: &mut File, channel: &Channel) -> Result<(), String> {
+    let pat = wrap_section_in_ts_packet(0x0000, &build_pat_section(channel.service_id, REMUX_PMT_PID));
+    let pmt = wrap_section_in_ts_packet(
+        REMUX_PMT_PID,
+        &build_pmt_section(channel.service_id, channel),
+    );
+    out.write_all(&pat).map_err(|e| format!("Failed to write PAT: {e}"))?;
+    out.write_all(&pmt).map_err(|e| format!("Failed to write PMT: {e}"))?;
+    Ok(())
+}
+
+/// Copy every sync-aligned 188-byte TS packet out of `buf` (a raw read from a
+/// `DMX_OUT_TS_TAP` demux, which may contain several back-to-back packets) to
+/// `out`. Packets not starting with the sync byte are dropped rather than
+/// resynchronized, since `read_all_sections`'s CRC checks are the layer that
+/// deals with noisy reception; here we're just forwarding what the kernel
+/// already delivered as whole packets.
+fn copy_ts_packets(buf: &[u8], out: &mut File) -> Result<(), String> {
+    let mut pos = 0;
+    while pos + TS_PACKET_SIZE <= buf.len() {
+        if buf[pos] == TS_SYNC_BYTE {
+            out.write_all(&buf[pos..pos + TS_PACKET_SIZE])
+                .map_err(|e| format!("Failed to write TS packet: {e}"))?;
+        }
+        pos += TS_PACKET_SIZE;
+    }
+    Ok(())
+}
+
+/// Capture `channel`'s video and audio PIDs for `duration_secs` and write
+/// them out as a standalone single-program transport stream: a freshly
+/// synthesized PAT/PMT (re-emitted periodically) followed by the PIDs'
+/// untouched TS packets, so the result plays back without needing the rest
+/// of the original multiplex.
+pub fn remux_to_ts(
+    adapter: u32,
+    channel: &Channel,
+    duration_secs: u64,
+    output_path: &Path,
+) -> Result<(), String> {
+    let mut out = File::create(output_path)
+        .map_err(|e| format!("Failed to create {}: {e}", output_path.display()))?;
+
+    write_pat_and_pmt(&mut out, channel)?;
+
+    let mut video_demux = dmx::open_demux_ts(adapter, channel.video_pid)?;
+    let mut audio_demux = dmx::open_demux_ts(adapter, channel.audio_pid)?;
+
+    let start = Instant::now();
+    let timeout = Duration::from_secs(duration_secs);
+    let mut last_pat_pmt = Instant::now();
+    let mut buf = [0u8; 4096];
+
+    while start.elapsed() < timeout {
+        if last_pat_pmt.elapsed() >= PAT_PMT_REPEAT_INTERVAL {
+            write_pat_and_pmt(&mut out, channel)?;
+            last_pat_pmt = Instant::now();
+        }
+
+        for demux in [&mut video_demux, &mut audio_demux] {
+            if let Ok(n) = demux.read(&mut buf) {
+                if n > 0 {
+                    copy_ts_packets(&buf[..n], &mut out)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// True if a raw TS packet (already confirmed sync-aligned) carries the
+/// `payload_unit_start_indicator` bit, meaning it begins a new PES packet —
+/// the only point a segment boundary can land without splitting a frame
+/// across two files.
+fn packet_starts_pes(packet: &[u8]) -> bool {
+    packet[1] & 0x40 != 0
+}
+
+/// Capture `channel`'s video/audio PIDs like [`remux_to_ts`], but instead of
+/// one continuous file, cut a fresh `seg<N>.ts` under `segment_dir` roughly
+/// every `min_segment_secs`, aligning each cut to the next video PES boundary
+/// so a segment never starts mid-frame (falling back to a hard cut at
+/// `max_segment_secs` if reception stalls and no boundary arrives in time).
+/// `on_segment` is called with each closed segment's index and wall-clock
+/// duration, so the caller can maintain a live playlist window; runs until
+/// `stop` is set.
+pub fn remux_to_hls(
+    adapter: u32,
+    channel: &Channel,
+    segment_dir: &Path,
+    min_segment_secs: f64,
+    max_segment_secs: f64,
+    stop: &std::sync::atomic::AtomicBool,
+    mut on_segment: impl FnMut(u64, f64),
+) -> Result<(), String> {
+    use std::sync::atomic::Ordering;
+
+    std::fs::create_dir_all(segment_dir)
+        .map_err(|e| format!("Failed to create {}: {e}", segment_dir.display()))?;
+
+    let mut video_demux = dmx::open_demux_ts(adapter, channel.video_pid)?;
+    let mut audio_demux = dmx::open_demux_ts(adapter, channel.audio_pid)?;
+
+    let mut index = 0u64;
+    let mut audio_buf = [0u8; 4096];
+    let mut video_buf = [0u8; 4096];
+    // Video packets read past a segment's cut point, carried over so the PES
+    // packet they start isn't split across two segment files.
+    let mut carry: Vec<u8> = Vec::new();
+
+    while !stop.load(Ordering::Relaxed) {
+        let seg_path = segment_dir.join(format!("seg{index}.ts"));
+        let mut out = File::create(&seg_path)
+            .map_err(|e| format!("Failed to create {}: {e}", seg_path.display()))?;
+        write_pat_and_pmt(&mut out, channel)?;
+        if !carry.is_empty() {
+            copy_ts_packets(&carry, &mut out)?;
+            carry.clear();
+        }
+
+        let seg_start = Instant::now();
+        loop {
+            if stop.load(Ordering::Relaxed) {
+                break;
+            }
+
+            if let Ok(n) = audio_demux.read(&mut audio_buf) {
+                if n > 0 {
+                    copy_ts_packets(&audio_buf[..n], &mut out)?;
+                }
+            }
+
+            let elapsed = seg_start.elapsed().as_secs_f64();
+            let n = video_demux.read(&mut video_buf).unwrap_or(0);
+            if n > 0 {
+                let mut pos = 0;
+                let mut split = None;
+                while pos + TS_PACKET_SIZE <= n {
+                    let packet = &video_buf[pos..pos + TS_PACKET_SIZE];
+                    let at_boundary = pos > 0
+                        && packet[0] == TS_SYNC_BYTE
+                        && packet_starts_pes(packet)
+                        && elapsed >= min_segment_secs;
+                    if at_boundary {
+                        split = Some(pos);
+                        break;
+                    }
+                    pos += TS_PACKET_SIZE;
+                }
+
+                match split {
+                    Some(at) => {
+                        copy_ts_packets(&video_buf[..at], &mut out)?;
+                        carry = video_buf[at..n].to_vec();
+                        break;
+                    }
+                    None => copy_ts_packets(&video_buf[..n], &mut out)?,
+                }
+            }
+
+            if elapsed >= max_segment_secs && carry.is_empty() {
+                // No clean PES boundary showed up in time; cut anyway so a
+                // stalled video PID can't wedge the segmenter open forever.
+                break;
+            }
+        }
+
+        on_segment(index, seg_start.elapsed().as_secs_f64());
+        index += 1;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::channel::{
+        Bandwidth, ElementaryStream, Fec, GuardInterval, Hierarchy, Inversion, Modulation,
+        StreamKind, TransmissionMode, Tuning,
+    };
+
+    fn sample_channel() -> Channel {
+        Channel {
+            name: "Test".to_string(),
+            frequency: 557000000,
+            video_pid: 4097,
+            audio_pid: 4098,
+            service_id: 1,
+            tuning: Tuning::DvbT {
+                inversion: Inversion::Auto,
+                bandwidth: Bandwidth::Mhz6,
+                fec_hp: Fec::Auto,
+                fec_lp: Fec::Auto,
+                modulation: Modulation::Qam64,
+                transmission_mode: TransmissionMode::K8,
+                guard_interval: GuardInterval::Auto,
+                hierarchy: Hierarchy::None,
+            },
+            elementary_streams: vec![
+                ElementaryStream {
+                    pid: 4097,
+                    stream_type: 0x1B,
+                    kind: StreamKind::Video,
+                    language: String::new(),
+                },
+                ElementaryStream {
+                    pid: 4098,
+                    stream_type: 0x0F,
+                    kind: StreamKind::Audio,
+                    language: String::new(),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_build_pat_section_is_crc_valid() {
+        let section = build_pat_section(1, REMUX_PMT_PID);
+        assert_eq!(crc32_mpeg2(&section), 0);
+    }
+
+    #[test]
+    fn test_build_pmt_section_is_crc_valid() {
+        let ch = sample_channel();
+        let section = build_pmt_section(ch.service_id, &ch);
+        assert_eq!(crc32_mpeg2(&section), 0);
+    }
+
+    #[test]
+    fn test_build_pmt_section_carries_real_stream_types() {
+        let ch = sample_channel();
+        let section = build_pmt_section(ch.service_id, &ch);
+        // First ES entry (video): stream_type at offset 12.
+        assert_eq!(section[12], 0x1B);
+        // Second ES entry (audio): stream_type at offset 17.
+        assert_eq!(section[17], 0x0F);
+    }
+
+    #[test]
+    fn test_wrap_section_in_ts_packet_size_and_sync() {
+        let section = build_pat_section(1, REMUX_PMT_PID);
+        let packet = wrap_section_in_ts_packet(0x0000, &section);
+        assert_eq!(packet.len(), TS_PACKET_SIZE);
+        assert_eq!(packet[0], TS_SYNC_BYTE);
+        assert_eq!(packet[4], 0x00); // pointer_field
+        assert_eq!(&packet[5..5 + section.len()], &section[..]);
+    }
+
+    #[test]
+    fn test_stream_type_for_pid_falls_back_when_unknown() {
+        let ch = sample_channel();
+        assert_eq!(stream_type_for_pid(&ch, 9999), 0x00);
+    }
+
+    #[test]
+    fn test_copy_ts_packets_drops_unaligned_trailing_bytes() {
+        let mut out = tempfile::NamedTempFile::new().unwrap().reopen().unwrap();
+        let mut packet = [0u8; TS_PACKET_SIZE];
+        packet[0] = TS_SYNC_BYTE;
+        let mut buf = packet.to_vec();
+        buf.extend_from_slice(&[0u8; 10]); // trailing partial packet
+        copy_ts_packets(&buf, &mut out).unwrap();
+        use std::io::{Seek, SeekFrom};
+        out.seek(SeekFrom::Start(0)).unwrap();
+        let mut written = Vec::new();
+        out.read_to_end(&mut written).unwrap();
+        assert_eq!(written.len(), TS_PACKET_SIZE);
+    }
+
+    #[test]
+    fn test_packet_starts_pes_detects_pusi_bit() {
+        let mut packet = [0u8; TS_PACKET_SIZE];
+        packet[0] = TS_SYNC_BYTE;
+        packet[1] = 0x40;
+        assert!(packet_starts_pes(&packet));
+    }
+
+    #[test]
+    fn test_packet_starts_pes_false_without_pusi_bit() {
+        let mut packet = [0u8; TS_PACKET_SIZE];
+        packet[0] = TS_SYNC_BYTE;
+        packet[1] = 0x00;
+        assert!(!packet_starts_pes(&packet));
+    }
+}
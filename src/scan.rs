@@ -4,9 +4,10 @@ use std::os::unix::io::AsRawFd;
 
 use nix::poll::{poll, PollFd, PollFlags, PollTimeout};
 
-use crate::channel::Channel;
+use crate::channel::{Channel, ElementaryStream, Modulation, StreamKind, Tuning};
 use crate::dmx;
 use crate::eit::decode_dvb_text;
+use crate::warn;
 
 // --- ScanEntry and dvbv5 file parsing ---
 
@@ -21,6 +22,14 @@ pub struct ScanEntry {
     pub guard_interval: String,
     pub hierarchy: String,
     pub inversion: String,
+    /// Symbol rate in symbols/second (DVB-C/DVB-S); 0 for terrestrial/ATSC.
+    pub symbol_rate: u64,
+    /// Polarization for DVB-S/S2: "H"/"V"; empty for non-satellite.
+    pub polarization: String,
+    /// Orbital position of the satellite, e.g. "19.2E"; empty for non-satellite.
+    pub satellite_position: String,
+    /// DiSEqC 1.0 committed-switch port (0-3) selecting the LNB input.
+    pub diseqc_port: u8,
 }
 
 pub fn parse_scan_file(path: &str) -> Result<Vec<ScanEntry>, String> {
@@ -48,6 +57,10 @@ pub fn parse_scan_file(path: &str) -> Result<Vec<ScanEntry>, String> {
                 guard_interval: String::new(),
                 hierarchy: String::new(),
                 inversion: String::new(),
+                symbol_rate: 0,
+                polarization: String::new(),
+                satellite_position: String::new(),
+                diseqc_port: 0,
             });
             continue;
         }
@@ -86,6 +99,18 @@ pub fn parse_scan_file(path: &str) -> Result<Vec<ScanEntry>, String> {
             "GUARD_INTERVAL" => entry.guard_interval = value.to_string(),
             "HIERARCHY" => entry.hierarchy = value.to_string(),
             "INVERSION" => entry.inversion = value.to_string(),
+            "SYMBOL_RATE" => {
+                entry.symbol_rate = value
+                    .parse()
+                    .map_err(|e| format!("Invalid SYMBOL_RATE '{value}': {e}"))?;
+            }
+            "POLARIZATION" => entry.polarization = dvbv5_to_zap_polarization(value),
+            "SATELLITE" => entry.satellite_position = value.to_string(),
+            "SAT_NUMBER" => {
+                entry.diseqc_port = value
+                    .parse()
+                    .map_err(|e| format!("Invalid SAT_NUMBER '{value}': {e}"))?;
+            }
             _ => {}
         }
     }
@@ -99,7 +124,7 @@ pub fn parse_scan_file(path: &str) -> Result<Vec<ScanEntry>, String> {
 
 // --- dvbv5 → zap format conversions ---
 
-fn dvbv5_to_zap_inversion(s: &str) -> String {
+pub(crate) fn dvbv5_to_zap_inversion(s: &str) -> String {
     match s {
         "AUTO" => "INVERSION_AUTO",
         "ON" => "INVERSION_ON",
@@ -109,7 +134,18 @@ fn dvbv5_to_zap_inversion(s: &str) -> String {
     .to_string()
 }
 
-fn dvbv5_to_zap_bandwidth(hz: u64) -> String {
+pub(crate) fn dvbv5_to_zap_polarization(s: &str) -> String {
+    match s {
+        "HORIZONTAL" | "H" => "H",
+        "VERTICAL" | "V" => "V",
+        "LEFT" | "L" => "L",
+        "RIGHT" | "R" => "R",
+        _ => "",
+    }
+    .to_string()
+}
+
+pub(crate) fn dvbv5_to_zap_bandwidth(hz: u64) -> String {
     match hz {
         5000000 => "BANDWIDTH_5_MHZ",
         6000000 => "BANDWIDTH_6_MHZ",
@@ -122,7 +158,7 @@ fn dvbv5_to_zap_bandwidth(hz: u64) -> String {
     .to_string()
 }
 
-fn dvbv5_to_zap_fec(s: &str) -> String {
+pub(crate) fn dvbv5_to_zap_fec(s: &str) -> String {
     match s {
         "NONE" => "FEC_NONE",
         "1/2" => "FEC_1_2",
@@ -139,7 +175,7 @@ fn dvbv5_to_zap_fec(s: &str) -> String {
     .to_string()
 }
 
-fn dvbv5_to_zap_modulation(s: &str) -> String {
+pub(crate) fn dvbv5_to_zap_modulation(s: &str) -> String {
     match s {
         "QPSK" => "QPSK",
         "QAM/16" => "QAM_16",
@@ -148,12 +184,15 @@ fn dvbv5_to_zap_modulation(s: &str) -> String {
         "QAM/128" => "QAM_128",
         "QAM/256" => "QAM_256",
         "QAM/AUTO" => "QAM_AUTO",
+        "VSB/8" => "8VSB",
+        "VSB/16" => "16VSB",
+        "PSK/8" => "PSK_8",
         _ => "QAM_AUTO",
     }
     .to_string()
 }
 
-fn dvbv5_to_zap_transmission(s: &str) -> String {
+pub(crate) fn dvbv5_to_zap_transmission(s: &str) -> String {
     match s {
         "1K" => "TRANSMISSION_MODE_1K",
         "2K" => "TRANSMISSION_MODE_2K",
@@ -167,7 +206,7 @@ fn dvbv5_to_zap_transmission(s: &str) -> String {
     .to_string()
 }
 
-fn dvbv5_to_zap_guard(s: &str) -> String {
+pub(crate) fn dvbv5_to_zap_guard(s: &str) -> String {
     match s {
         "1/32" => "GUARD_INTERVAL_1_32",
         "1/16" => "GUARD_INTERVAL_1_16",
@@ -179,7 +218,7 @@ fn dvbv5_to_zap_guard(s: &str) -> String {
     .to_string()
 }
 
-fn dvbv5_to_zap_hierarchy(s: &str) -> String {
+pub(crate) fn dvbv5_to_zap_hierarchy(s: &str) -> String {
     match s {
         "NONE" => "HIERARCHY_NONE",
         "1" => "HIERARCHY_1",
@@ -191,24 +230,72 @@ fn dvbv5_to_zap_hierarchy(s: &str) -> String {
     .to_string()
 }
 
+/// Parse a `dvbv5_to_zap_*` helper's output as a validated zap-token enum.
+/// Every helper it is used with always returns one of the canonical tokens
+/// the enum recognizes, so this can never actually fail. (Modulation is
+/// parsed separately, since `dvbv5_to_zap_modulation` also returns the
+/// non-DVB-T tokens QPSK, 8VSB, 16VSB and PSK_8.)
+fn zap_token<T: std::str::FromStr<Err = String>>(token: String) -> T {
+    token
+        .parse()
+        .expect("dvbv5_to_zap_* always returns a canonical zap token")
+}
+
 impl ScanEntry {
     /// Convert scan entry tuning params to a Channel (for use with Tuner::tune).
     /// Name/PIDs/service_id are left empty/zero.
+    ///
+    /// Branches on `delivery_system` to build the matching [`Tuning`] variant:
+    /// ATSC carries only a modulation, DVB-C carries a symbol rate and QAM,
+    /// DVB-S carries polarization/symbol rate/LNB routing, and everything
+    /// else is treated as terrestrial DVB-T.
     pub fn to_channel(&self) -> Channel {
+        let delivery = self.delivery_system.to_ascii_uppercase();
+
+        let tuning = if delivery == "ATSC" {
+            Tuning::Atsc {
+                modulation: dvbv5_to_zap_modulation(&self.modulation),
+            }
+        } else if delivery.starts_with("DVBC") {
+            Tuning::DvbC {
+                inversion: dvbv5_to_zap_inversion(&self.inversion),
+                symbol_rate: self.symbol_rate,
+                fec: dvbv5_to_zap_fec(&self.code_rate_hp),
+                modulation: dvbv5_to_zap_modulation(&self.modulation),
+            }
+        } else if delivery.starts_with("DVBS") {
+            Tuning::DvbS {
+                polarization: self.polarization.clone(),
+                symbol_rate: self.symbol_rate,
+                fec: dvbv5_to_zap_fec(&self.code_rate_hp),
+                modulation: dvbv5_to_zap_modulation(&self.modulation),
+                satellite_position: self.satellite_position.clone(),
+                diseqc_port: self.diseqc_port,
+            }
+        } else {
+            Tuning::DvbT {
+                inversion: zap_token(dvbv5_to_zap_inversion(&self.inversion)),
+                bandwidth: zap_token(dvbv5_to_zap_bandwidth(self.bandwidth_hz)),
+                fec_hp: zap_token(dvbv5_to_zap_fec(&self.code_rate_hp)),
+                fec_lp: zap_token(dvbv5_to_zap_fec(&self.code_rate_lp)),
+                // QPSK/VSB/8PSK have no DVB-T `Modulation` and fall back to QAM_AUTO.
+                modulation: dvbv5_to_zap_modulation(&self.modulation)
+                    .parse()
+                    .unwrap_or(Modulation::QamAuto),
+                transmission_mode: zap_token(dvbv5_to_zap_transmission(&self.transmission_mode)),
+                guard_interval: zap_token(dvbv5_to_zap_guard(&self.guard_interval)),
+                hierarchy: zap_token(dvbv5_to_zap_hierarchy(&self.hierarchy)),
+            }
+        };
+
         Channel {
             name: String::new(),
             frequency: self.frequency,
-            inversion: dvbv5_to_zap_inversion(&self.inversion),
-            bandwidth: dvbv5_to_zap_bandwidth(self.bandwidth_hz),
-            fec_hp: dvbv5_to_zap_fec(&self.code_rate_hp),
-            fec_lp: dvbv5_to_zap_fec(&self.code_rate_lp),
-            modulation: dvbv5_to_zap_modulation(&self.modulation),
-            transmission_mode: dvbv5_to_zap_transmission(&self.transmission_mode),
-            guard_interval: dvbv5_to_zap_guard(&self.guard_interval),
-            hierarchy: dvbv5_to_zap_hierarchy(&self.hierarchy),
             video_pid: 0,
             audio_pid: 0,
             service_id: 0,
+            tuning,
+            elementary_streams: Vec::new(),
         }
     }
 }
@@ -424,22 +511,239 @@ BANDWIDTH_HZ = 6000000
             guard_interval: "1/8".to_string(),
             hierarchy: "NONE".to_string(),
             inversion: "AUTO".to_string(),
+            symbol_rate: 0,
+            polarization: String::new(),
+            satellite_position: String::new(),
+            diseqc_port: 0,
         };
         let ch = entry.to_channel();
         assert_eq!(ch.frequency, 557000000);
-        assert_eq!(ch.bandwidth, "BANDWIDTH_6_MHZ");
-        assert_eq!(ch.fec_hp, "FEC_2_3");
-        assert_eq!(ch.fec_lp, "FEC_AUTO");
-        assert_eq!(ch.modulation, "QAM_64");
-        assert_eq!(ch.transmission_mode, "TRANSMISSION_MODE_8K");
-        assert_eq!(ch.guard_interval, "GUARD_INTERVAL_1_8");
-        assert_eq!(ch.hierarchy, "HIERARCHY_NONE");
-        assert_eq!(ch.inversion, "INVERSION_AUTO");
+        match &ch.tuning {
+            Tuning::DvbT {
+                inversion,
+                bandwidth,
+                fec_hp,
+                fec_lp,
+                modulation,
+                transmission_mode,
+                guard_interval,
+                hierarchy,
+            } => {
+                assert_eq!(bandwidth.to_string(), "BANDWIDTH_6_MHZ");
+                assert_eq!(fec_hp.to_string(), "FEC_2_3");
+                assert_eq!(fec_lp.to_string(), "FEC_AUTO");
+                assert_eq!(modulation.to_string(), "QAM_64");
+                assert_eq!(transmission_mode.to_string(), "TRANSMISSION_MODE_8K");
+                assert_eq!(guard_interval.to_string(), "GUARD_INTERVAL_1_8");
+                assert_eq!(hierarchy.to_string(), "HIERARCHY_NONE");
+                assert_eq!(inversion.to_string(), "INVERSION_AUTO");
+            }
+            other => panic!("expected Tuning::DvbT, got {other:?}"),
+        }
         assert_eq!(ch.video_pid, 0);
         assert_eq!(ch.audio_pid, 0);
         assert_eq!(ch.service_id, 0);
     }
 
+    // --- TS packet framing detection ---
+
+    fn ts_packets(stride: usize, offset: usize, count: usize) -> Vec<u8> {
+        let mut buf = vec![0u8; stride * count];
+        for i in 0..count {
+            buf[i * stride + offset] = TS_SYNC_BYTE;
+        }
+        buf
+    }
+
+    #[test]
+    fn test_detect_ts_packet_format_188() {
+        let buf = ts_packets(188, 0, 4);
+        assert_eq!(detect_ts_packet_format(&buf), Some(TsPacketFormat::Raw188));
+    }
+
+    #[test]
+    fn test_detect_ts_packet_format_192_timestamped() {
+        let buf = ts_packets(192, 4, 4);
+        assert_eq!(
+            detect_ts_packet_format(&buf),
+            Some(TsPacketFormat::Timestamped192)
+        );
+    }
+
+    #[test]
+    fn test_detect_ts_packet_format_204_fec() {
+        let buf = ts_packets(204, 0, 4);
+        assert_eq!(
+            detect_ts_packet_format(&buf),
+            Some(TsPacketFormat::FecProtected204)
+        );
+    }
+
+    #[test]
+    fn test_detect_ts_packet_format_too_short() {
+        assert_eq!(detect_ts_packet_format(&[0x47, 0x00]), None);
+    }
+
+    #[test]
+    fn test_detect_ts_packet_format_no_sync() {
+        let buf = vec![0u8; 188 * 4];
+        assert_eq!(detect_ts_packet_format(&buf), None);
+    }
+
+    #[test]
+    fn test_ts_packet_payload_188() {
+        let mut buf = ts_packets(188, 0, 2);
+        buf[188] = 0x47;
+        buf[189] = 0xAB;
+        let payload = ts_packet_payload(&buf, 1, TsPacketFormat::Raw188).unwrap();
+        assert_eq!(payload.len(), 188);
+        assert_eq!(payload[0], 0x47);
+        assert_eq!(payload[1], 0xAB);
+    }
+
+    #[test]
+    fn test_ts_packet_payload_192_skips_timecode() {
+        let mut buf = ts_packets(192, 4, 1);
+        buf[4] = 0x47;
+        buf[5] = 0xCD;
+        let payload = ts_packet_payload(&buf, 0, TsPacketFormat::Timestamped192).unwrap();
+        assert_eq!(payload[0], 0x47);
+        assert_eq!(payload[1], 0xCD);
+    }
+
+    #[test]
+    fn test_ts_packet_payload_out_of_range() {
+        let buf = ts_packets(188, 0, 2);
+        assert!(ts_packet_payload(&buf, 5, TsPacketFormat::Raw188).is_none());
+    }
+
+    // --- crc32_mpeg2 ---
+
+    #[test]
+    fn test_crc32_mpeg2_known_vector() {
+        // The canonical MPEG-2 CRC check value for the ASCII string "123456789"
+        // is 0x0376E6E7.
+        assert_eq!(crc32_mpeg2(b"123456789"), 0x0376_E6E7);
+    }
+
+    #[test]
+    fn test_crc32_mpeg2_roundtrip_yields_zero() {
+        // Appending the big-endian CRC of a payload makes the CRC over the whole
+        // buffer come out to 0, which is how sections are validated.
+        let payload = [0x00u8, 0xB0, 0x0D, 0x00, 0x01, 0xC1, 0x00, 0x00];
+        let crc = crc32_mpeg2(&payload);
+        let mut section = payload.to_vec();
+        section.extend_from_slice(&crc.to_be_bytes());
+        assert_eq!(crc32_mpeg2(&section), 0);
+    }
+
+    #[test]
+    fn test_crc32_mpeg2_detects_bit_flip() {
+        // A single corrupted byte anywhere in the section must no longer
+        // yield a zero remainder, so read_all_sections rejects it.
+        let payload = [0x00u8, 0xB0, 0x0D, 0x00, 0x01, 0xC1, 0x00, 0x00];
+        let crc = crc32_mpeg2(&payload);
+        let mut section = payload.to_vec();
+        section.extend_from_slice(&crc.to_be_bytes());
+        section[4] ^= 0xFF;
+        assert_ne!(crc32_mpeg2(&section), 0);
+    }
+
+    #[test]
+    fn test_scan_entry_to_channel_atsc() {
+        let entry = ScanEntry {
+            delivery_system: "ATSC".to_string(),
+            frequency: 533000000,
+            bandwidth_hz: 0,
+            code_rate_hp: String::new(),
+            code_rate_lp: String::new(),
+            modulation: "VSB/8".to_string(),
+            transmission_mode: String::new(),
+            guard_interval: String::new(),
+            hierarchy: String::new(),
+            inversion: "AUTO".to_string(),
+            symbol_rate: 0,
+            polarization: String::new(),
+            satellite_position: String::new(),
+            diseqc_port: 0,
+        };
+        let ch = entry.to_channel();
+        match &ch.tuning {
+            Tuning::Atsc { modulation } => assert_eq!(modulation, "8VSB"),
+            other => panic!("expected Tuning::Atsc, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_scan_entry_to_channel_dvbc() {
+        let entry = ScanEntry {
+            delivery_system: "DVBC/ANNEX_A".to_string(),
+            frequency: 346000000,
+            bandwidth_hz: 0,
+            code_rate_hp: "NONE".to_string(),
+            code_rate_lp: "NONE".to_string(),
+            modulation: "QAM/256".to_string(),
+            transmission_mode: String::new(),
+            guard_interval: String::new(),
+            hierarchy: String::new(),
+            inversion: "AUTO".to_string(),
+            symbol_rate: 6900000,
+            polarization: String::new(),
+            satellite_position: String::new(),
+            diseqc_port: 0,
+        };
+        let ch = entry.to_channel();
+        match &ch.tuning {
+            Tuning::DvbC {
+                modulation,
+                symbol_rate,
+                ..
+            } => {
+                assert_eq!(modulation, "QAM_256");
+                assert_eq!(*symbol_rate, 6900000);
+            }
+            other => panic!("expected Tuning::DvbC, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_scan_entry_to_channel_dvbs() {
+        let entry = ScanEntry {
+            delivery_system: "DVBS2".to_string(),
+            frequency: 11494000,
+            bandwidth_hz: 0,
+            code_rate_hp: "2/3".to_string(),
+            code_rate_lp: "NONE".to_string(),
+            modulation: "PSK/8".to_string(),
+            transmission_mode: String::new(),
+            guard_interval: String::new(),
+            hierarchy: String::new(),
+            inversion: "AUTO".to_string(),
+            symbol_rate: 22000000,
+            polarization: "H".to_string(),
+            satellite_position: "19.2E".to_string(),
+            diseqc_port: 1,
+        };
+        let ch = entry.to_channel();
+        match &ch.tuning {
+            Tuning::DvbS {
+                polarization,
+                symbol_rate,
+                modulation,
+                satellite_position,
+                diseqc_port,
+                ..
+            } => {
+                assert_eq!(polarization, "H");
+                assert_eq!(*symbol_rate, 22000000);
+                assert_eq!(modulation, "PSK_8");
+                assert_eq!(satellite_position, "19.2E");
+                assert_eq!(*diseqc_port, 1);
+            }
+            other => panic!("expected Tuning::DvbS, got {other:?}"),
+        }
+    }
+
     // --- parse_pat_sections ---
 
     #[test]
@@ -503,6 +807,137 @@ BANDWIDTH_HZ = 6000000
         assert!(result.is_empty());
     }
 
+    // --- parse_nit_sections ---
+
+    /// Build a minimal NIT section (table_id 0x40) with no network
+    /// descriptors and one transport stream entry carrying `descriptors`.
+    fn build_nit_section(descriptors: &[u8]) -> Vec<u8> {
+        let transport_stream_loop_length = 6 + descriptors.len();
+        let section_length = 5 + 2 + 2 + transport_stream_loop_length + 4;
+        let mut data = vec![0u8; 3 + section_length];
+        data[0] = 0x40; // table_id = NIT (actual network)
+        data[1] = 0xB0 | ((section_length >> 8) as u8 & 0x0F);
+        data[2] = section_length as u8;
+        data[3] = 0x00; data[4] = 0x01; // network_id
+        data[5] = 0xC1; // version
+        data[6] = 0x00; // section_number
+        data[7] = 0x00; // last_section_number
+        data[8] = 0xF0; data[9] = 0x00; // network_descriptors_length = 0
+
+        let mut pos = 10;
+        data[pos] = (0xF0) | ((transport_stream_loop_length >> 8) as u8 & 0x0F);
+        data[pos + 1] = transport_stream_loop_length as u8;
+        pos += 2;
+
+        data[pos] = 0x00; data[pos + 1] = 0x02; // transport_stream_id
+        data[pos + 2] = 0x00; data[pos + 3] = 0x01; // original_network_id
+        data[pos + 4] = 0xF0 | ((descriptors.len() >> 8) as u8 & 0x0F);
+        data[pos + 5] = descriptors.len() as u8;
+        pos += 6;
+        data[pos..pos + descriptors.len()].copy_from_slice(descriptors);
+
+        // CRC at end (not validated)
+        data
+    }
+
+    /// Build a Terrestrial Delivery System Descriptor (tag 0x5A) for
+    /// 578 MHz, 6 MHz bandwidth, 64-QAM, 2/3 HP, 8k, 1/8 guard, non-hierarchical.
+    fn build_terrestrial_delivery_descriptor() -> Vec<u8> {
+        let centre_frequency: u32 = 578_000_000 / 10;
+        let mut desc = vec![0x5A, 11];
+        desc.extend_from_slice(&centre_frequency.to_be_bytes());
+        desc.push(0b010_0_0_0_00); // bandwidth=6MHz(010), priority/slicing/fec/reserved
+        desc.push(0b10_000_001); // constellation=64QAM(10), hierarchy=NONE(000), code_rate_hp=2/3(001)
+        desc.push(0b000_10_01_0); // code_rate_lp=1/2(000), guard=1/8(10), transmission=8k(01)
+        desc.extend_from_slice(&[0xFF, 0xFF, 0xFF, 0xFF]); // reserved_future_use
+        desc
+    }
+
+    #[test]
+    fn test_parse_nit_sections_terrestrial_descriptor() {
+        let desc = build_terrestrial_delivery_descriptor();
+        let section = build_nit_section(&desc);
+        let entries = parse_nit_sections(&[section]);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].delivery_system, "DVBT");
+        assert_eq!(entries[0].frequency, 578_000_000);
+        assert_eq!(entries[0].bandwidth_hz, 6_000_000);
+        assert_eq!(entries[0].modulation, "QAM/64");
+        assert_eq!(entries[0].hierarchy, "NONE");
+        assert_eq!(entries[0].code_rate_hp, "2/3");
+        assert_eq!(entries[0].code_rate_lp, "1/2");
+        assert_eq!(entries[0].guard_interval, "1/8");
+        assert_eq!(entries[0].transmission_mode, "8K");
+    }
+
+    /// Build a Cable Delivery System Descriptor (tag 0x44) for 346 MHz,
+    /// 64-QAM, symbol rate 6.9 Msym/s, FEC inner 3/4.
+    fn build_cable_delivery_descriptor() -> Vec<u8> {
+        let mut desc = vec![0x44, 11];
+        desc.extend_from_slice(&[0x03, 0x46, 0x00, 0x00]); // frequency BCD: 346.00 MHz
+        desc.push(0x00); // reserved
+        desc.push(0x02); // reserved(4) | FEC_outer(4) = RS(204/188)
+        desc.push(3); // modulation = 64-QAM
+        desc.extend_from_slice(&[0x00, 0x69, 0x00]); // symbol_rate BCD high digits
+        desc.push(0x00 | 3); // symbol_rate low digit(4) | FEC_inner(4) = 3/4
+        desc
+    }
+
+    #[test]
+    fn test_parse_nit_sections_cable_descriptor() {
+        let desc = build_cable_delivery_descriptor();
+        let section = build_nit_section(&desc);
+        let entries = parse_nit_sections(&[section]);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].delivery_system, "DVBC");
+        assert_eq!(entries[0].frequency, 346_000_000);
+        assert_eq!(entries[0].modulation, "QAM/64");
+        assert_eq!(entries[0].symbol_rate, 6_900_000);
+        assert_eq!(entries[0].code_rate_hp, "3/4");
+    }
+
+    /// Build a Satellite Delivery System Descriptor (tag 0x43) for
+    /// 12515 MHz, 19.2E, vertical, DVB-S2 8PSK, symbol rate 27.5 Msym/s,
+    /// FEC inner 2/3.
+    fn build_satellite_delivery_descriptor() -> Vec<u8> {
+        let mut desc = vec![0x43, 11];
+        desc.extend_from_slice(&[0x01, 0x25, 0x15, 0x00]); // frequency BCD: 1251500 x10kHz = 12515 MHz
+        desc.extend_from_slice(&[0x01, 0x92]); // orbital_position BCD: 19.2
+        // west_east_flag=1(E), polarization=01(V), roll_off=00, modulation_system=1(S2), modulation_type=10(8PSK)
+        desc.push(0b1_01_00_1_10);
+        desc.extend_from_slice(&[0x02, 0x75, 0x00]); // symbol_rate BCD high digits
+        desc.push(0x00 | 2); // symbol_rate low digit(4) | FEC_inner(4) = 2/3
+        desc
+    }
+
+    #[test]
+    fn test_parse_nit_sections_satellite_descriptor() {
+        let desc = build_satellite_delivery_descriptor();
+        let section = build_nit_section(&desc);
+        let entries = parse_nit_sections(&[section]);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].delivery_system, "DVBS");
+        assert_eq!(entries[0].frequency, 12_515_000_000);
+        assert_eq!(entries[0].satellite_position, "19.2E");
+        assert_eq!(entries[0].polarization, "V");
+        assert_eq!(entries[0].modulation, "PSK/8");
+        assert_eq!(entries[0].symbol_rate, 27_500_000);
+        assert_eq!(entries[0].code_rate_hp, "2/3");
+    }
+
+    #[test]
+    fn test_parse_nit_sections_ignores_unknown_descriptor() {
+        let desc = vec![0x41, 2, 0xAA, 0xBB]; // unrelated descriptor tag (0x41)
+        let section = build_nit_section(&desc);
+        let entries = parse_nit_sections(&[section]);
+        assert!(entries.is_empty());
+    }
+
+    #[test]
+    fn test_parse_nit_sections_empty() {
+        assert!(parse_nit_sections(&[]).is_empty());
+    }
+
     // --- parse_pmt ---
 
     #[test]
@@ -541,8 +976,11 @@ BANDWIDTH_HZ = 6000000
         data[pos + 4] = stream2_es_info_len as u8;
 
         let pmt = parse_pmt(&data).unwrap();
-        assert_eq!(pmt.video_pid, 0x100);
-        assert_eq!(pmt.audio_pid, 0x101);
+        assert_eq!(pmt.streams.len(), 2);
+        assert_eq!(pmt.streams[0].pid, 0x100);
+        assert_eq!(pmt.streams[0].kind, StreamKind::Video);
+        assert_eq!(pmt.streams[1].pid, 0x101);
+        assert_eq!(pmt.streams[1].kind, StreamKind::Audio);
     }
 
     #[test]
@@ -562,17 +1000,218 @@ BANDWIDTH_HZ = 6000000
         data[10] = 0xF0; data[11] = 0x00; // program_info_length = 0
 
         let pmt = parse_pmt(&data).unwrap();
-        assert_eq!(pmt.video_pid, 0);
-        assert_eq!(pmt.audio_pid, 0);
+        assert!(pmt.streams.is_empty());
+    }
+
+    /// Build a one-stream PMT section with the given stream_type/ES info,
+    /// for exercising descriptor parsing in isolation.
+    fn build_pmt_with_stream(stream_type: u8, es_info: &[u8]) -> Vec<u8> {
+        let es_info_len = es_info.len() as u16;
+        let entries_size = 5 + es_info_len;
+        let section_length: u16 = 9 + entries_size + 4;
+
+        let mut data = vec![0u8; 3 + section_length as usize];
+        data[0] = 0x02;
+        data[1] = 0xB0 | ((section_length >> 8) as u8 & 0x0F);
+        data[2] = section_length as u8;
+        data[10] = 0xF0;
+        data[11] = 0x00; // program_info_length = 0
+
+        let pos = 12;
+        data[pos] = stream_type;
+        data[pos + 1] = 0xE0 | 0x01;
+        data[pos + 2] = 0x23; // PID = 0x123
+        data[pos + 3] = 0xF0 | ((es_info_len >> 8) as u8 & 0x0F);
+        data[pos + 4] = es_info_len as u8;
+        data[pos + 5..pos + 5 + es_info.len()].copy_from_slice(es_info);
+
+        data
+    }
+
+    #[test]
+    fn test_parse_pmt_iso_639_language_descriptor() {
+        let mut es_info = vec![ISO_639_LANGUAGE_DESCRIPTOR, 4];
+        es_info.extend_from_slice(b"eng");
+        es_info.push(0); // audio_type
+        let data = build_pmt_with_stream(0x0F, &es_info); // AAC
+        let pmt = parse_pmt(&data).unwrap();
+        assert_eq!(pmt.streams[0].kind, StreamKind::Audio);
+        assert_eq!(pmt.streams[0].language, "eng");
+    }
+
+    #[test]
+    fn test_parse_pmt_subtitling_descriptor() {
+        let mut es_info = vec![SUBTITLING_DESCRIPTOR, 8];
+        es_info.extend_from_slice(b"deu");
+        es_info.extend_from_slice(&[0x10, 0x00, 0x01, 0x00, 0x02]); // type + page ids
+        let data = build_pmt_with_stream(0x06, &es_info); // private data
+        let pmt = parse_pmt(&data).unwrap();
+        assert_eq!(pmt.streams[0].kind, StreamKind::Subtitle);
+        assert_eq!(pmt.streams[0].language, "deu");
+    }
+
+    #[test]
+    fn test_parse_pmt_teletext_descriptor() {
+        let mut es_info = vec![TELETEXT_DESCRIPTOR, 5];
+        es_info.extend_from_slice(b"fra");
+        es_info.extend_from_slice(&[0x02, 0x00]); // type/magazine + page
+        let data = build_pmt_with_stream(0x06, &es_info);
+        let pmt = parse_pmt(&data).unwrap();
+        assert_eq!(pmt.streams[0].kind, StreamKind::Teletext);
+        assert_eq!(pmt.streams[0].language, "fra");
+    }
+
+    #[test]
+    fn test_parse_pmt_ac3_descriptor_marks_audio() {
+        let es_info = vec![AC3_DESCRIPTOR, 1, 0x00];
+        let data = build_pmt_with_stream(0x06, &es_info); // private data, identified by AC-3 descriptor
+        let pmt = parse_pmt(&data).unwrap();
+        assert_eq!(pmt.streams[0].kind, StreamKind::Audio);
+    }
+
+    #[test]
+    fn test_parse_pmt_enhanced_ac3_descriptor_marks_audio() {
+        let es_info = vec![ENHANCED_AC3_DESCRIPTOR, 1, 0x00];
+        let data = build_pmt_with_stream(0x06, &es_info);
+        let pmt = parse_pmt(&data).unwrap();
+        assert_eq!(pmt.streams[0].kind, StreamKind::Audio);
+    }
+
+    #[test]
+    fn test_parse_pmt_stream_type_ac3_is_audio() {
+        let data = build_pmt_with_stream(0x81, &[]); // ATSC AC-3 stream_type
+        let pmt = parse_pmt(&data).unwrap();
+        assert_eq!(pmt.streams[0].kind, StreamKind::Audio);
+    }
+
+    #[test]
+    fn test_parse_pmt_stream_type_eac3_is_audio() {
+        let data = build_pmt_with_stream(0x87, &[]); // Enhanced AC-3 stream_type
+        let pmt = parse_pmt(&data).unwrap();
+        assert_eq!(pmt.streams[0].kind, StreamKind::Audio);
+    }
+
+    #[test]
+    fn test_parse_pmt_av1_registration_descriptor() {
+        let mut es_info = vec![REGISTRATION_DESCRIPTOR, 4];
+        es_info.extend_from_slice(b"AV01");
+        let data = build_pmt_with_stream(0x06, &es_info);
+        let pmt = parse_pmt(&data).unwrap();
+        assert_eq!(pmt.streams[0].kind, StreamKind::Video);
+    }
+
+    #[test]
+    fn test_parse_pmt_unknown_private_stream_is_other() {
+        let data = build_pmt_with_stream(0x06, &[]);
+        let pmt = parse_pmt(&data).unwrap();
+        assert_eq!(pmt.streams[0].kind, StreamKind::Other);
+        assert_eq!(pmt.streams[0].language, "");
+    }
+}
+
+// --- TS packet framing detection ---
+
+const TS_SYNC_BYTE: u8 = 0x47;
+
+/// Transport-stream packet framing, as found in `.ts` dumps and ASI
+/// captures: the classic 188-byte packet, a 192-byte packet prefixed with a
+/// 4-byte timecode, or a 204-byte packet suffixed with 16 bytes of
+/// Reed-Solomon FEC parity. Kernel demux reads already hand back bare
+/// sections and never need this, but a raw capture file does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TsPacketFormat {
+    Raw188,
+    Timestamped192,
+    FecProtected204,
+}
+
+impl TsPacketFormat {
+    /// The stride between the start of one packet and the next.
+    pub fn stride(self) -> usize {
+        match self {
+            TsPacketFormat::Raw188 => 188,
+            TsPacketFormat::Timestamped192 => 192,
+            TsPacketFormat::FecProtected204 => 204,
+        }
+    }
+
+    /// The byte offset of the 188-byte MPEG-TS packet within one stride.
+    pub fn payload_offset(self) -> usize {
+        match self {
+            TsPacketFormat::Timestamped192 => 4,
+            TsPacketFormat::Raw188 | TsPacketFormat::FecProtected204 => 0,
+        }
     }
 }
 
+/// Detect which of the three known TS packet sizes `buf` is framed in, by
+/// checking which stride keeps the 0x47 sync byte aligned over a probe
+/// window of consecutive packets. Returns `None` if no stride produces a
+/// run of aligned sync bytes (e.g. `buf` is too short or isn't a TS at all).
+pub fn detect_ts_packet_format(buf: &[u8]) -> Option<TsPacketFormat> {
+    const PROBE_PACKETS: usize = 32;
+    const CANDIDATES: [TsPacketFormat; 3] = [
+        TsPacketFormat::Raw188,
+        TsPacketFormat::Timestamped192,
+        TsPacketFormat::FecProtected204,
+    ];
+
+    CANDIDATES.into_iter().find(|format| {
+        let stride = format.stride();
+        let offset = format.payload_offset();
+        if buf.len() < stride {
+            return false;
+        }
+
+        let probe_count = (buf.len() / stride).min(PROBE_PACKETS);
+        probe_count >= 2
+            && (0..probe_count).all(|i| {
+                let sync_pos = i * stride + offset;
+                buf.get(sync_pos) == Some(&TS_SYNC_BYTE)
+            })
+    })
+}
+
+/// Return the 188-byte MPEG-TS packet at stride index `i` within `buf`,
+/// stripped of any leading timecode or trailing FEC parity, or `None` if
+/// the full stride isn't present.
+pub fn ts_packet_payload(buf: &[u8], i: usize, format: TsPacketFormat) -> Option<&[u8]> {
+    let stride = format.stride();
+    let start = i * stride + format.payload_offset();
+    let end = start + 188;
+    buf.get(start..end)
+}
+
+// --- MPEG-2/DVB section CRC-32 ---
+
+/// Compute the MPEG-2/DVB CRC-32 over a PSI section.
+///
+/// 32-bit register initialized to `0xFFFF_FFFF`, processed MSB-first over every
+/// byte using polynomial `0x04C1_1DB7`, with no final inversion and no input or
+/// output bit reflection. A valid section (header through the trailing 4-byte
+/// CRC field) yields a remainder of 0.
+pub(crate) fn crc32_mpeg2(data: &[u8]) -> u32 {
+    const POLY: u32 = 0x04C1_1DB7;
+    let mut crc: u32 = 0xFFFF_FFFF;
+    for &byte in data {
+        for bit in (0..8).rev() {
+            let input = (byte >> bit) & 1;
+            if ((crc >> 31) ^ input as u32) & 1 != 0 {
+                crc = (crc << 1) ^ POLY;
+            } else {
+                crc <<= 1;
+            }
+        }
+    }
+    crc
+}
+
 // --- Generic section reader ---
 
 /// Read all sections for a given PID/table_id, collecting until we have
 /// section_number 0 through last_section_number. Returns all raw section buffers.
 fn read_all_sections(adapter: u32, pid: u16, table_id: u8, timeout_secs: u64) -> Result<Vec<Vec<u8>>, String> {
-    let mut demux_file = dmx::open_demux_with_filter(adapter, pid)?;
+    let mut demux_file = dmx::open_demux_with_filter(adapter, pid, Some(table_id), None)?;
     let fd = demux_file.as_raw_fd();
 
     let mut buf = [0u8; 4096];
@@ -580,6 +1219,7 @@ fn read_all_sections(adapter: u32, pid: u16, table_id: u8, timeout_secs: u64) ->
     let timeout = std::time::Duration::from_secs(timeout_secs);
     let mut sections: std::collections::HashMap<u8, Vec<u8>> = std::collections::HashMap::new();
     let mut expected_last: Option<u8> = None;
+    let mut crc_drops: u32 = 0;
 
     while start.elapsed() < timeout {
         let remaining_ms = timeout
@@ -617,6 +1257,18 @@ fn read_all_sections(adapter: u32, pid: u16, table_id: u8, timeout_secs: u64) ->
             continue;
         }
 
+        // Validate the section CRC-32 before trusting its contents. A corrupt
+        // section on noisy reception would otherwise yield garbage IDs/PIDs.
+        let section_length = (((buf[1] & 0x0F) as usize) << 8) | buf[2] as usize;
+        let section_end = 3 + section_length;
+        if section_end > n {
+            continue;
+        }
+        if crc32_mpeg2(&buf[..section_end]) != 0 {
+            crc_drops += 1;
+            continue;
+        }
+
         let section_number = buf[6];
         let last_section_number = buf[7];
 
@@ -630,6 +1282,12 @@ fn read_all_sections(adapter: u32, pid: u16, table_id: u8, timeout_secs: u64) ->
         }
     }
 
+    if crc_drops > 0 {
+        warn!(
+            "dropped {crc_drops} section(s) with bad CRC (PID=0x{pid:04X}, table_id=0x{table_id:02X})"
+        );
+    }
+
     if sections.is_empty() {
         return Err(format!(
             "Timeout reading sections (PID=0x{pid:04X}, table_id=0x{table_id:02X})"
@@ -642,8 +1300,8 @@ fn read_all_sections(adapter: u32, pid: u16, table_id: u8, timeout_secs: u64) ->
 
     if let Some(last) = expected_last {
         if result.len() <= last as usize {
-            eprintln!(
-                "  Warning: only got {}/{} sections for PID=0x{pid:04X}",
+            warn!(
+                "only got {}/{} sections for PID=0x{pid:04X}",
                 result.len(),
                 last + 1
             );
@@ -765,11 +1423,400 @@ fn parse_sdt_sections(sections: &[Vec<u8>]) -> Vec<(u16, String)> {
     services
 }
 
+// --- NIT parsing (PID 0x0010, table_id 0x40/0x41) ---
+
+const TERRESTRIAL_DELIVERY_SYSTEM_DESCRIPTOR: u8 = 0x5A;
+const SATELLITE_DELIVERY_SYSTEM_DESCRIPTOR: u8 = 0x43;
+const CABLE_DELIVERY_SYSTEM_DESCRIPTOR: u8 = 0x44;
+
+/// Decode one 8-bit BCD digit pair (e.g. `0x46` -> `46`).
+fn bcd_byte(byte: u8) -> u32 {
+    ((byte >> 4) * 10 + (byte & 0x0F)) as u32
+}
+
+/// Decode a big-endian run of BCD-digit-pair bytes into its decimal value,
+/// as used by the frequency and symbol_rate fields of the cable/satellite
+/// delivery system descriptors.
+fn bcd_to_u64(bytes: &[u8]) -> u64 {
+    bytes
+        .iter()
+        .fold(0u64, |acc, &b| acc * 100 + bcd_byte(b) as u64)
+}
+
+/// Decode the 28-bit BCD `symbol_rate` field shared by the cable and
+/// satellite delivery descriptors: 6 digits in `desc[7..10]` plus a 7th
+/// digit in the top nibble of `desc[10]`, in units of 100 symbols/second.
+fn symbol_rate_from_bcd(desc: &[u8]) -> u64 {
+    (bcd_to_u64(&desc[7..10]) * 10 + (desc[10] >> 4) as u64) * 100
+}
+
+fn decode_terrestrial_bandwidth(bits: u8) -> u64 {
+    match bits {
+        0 => 8_000_000,
+        1 => 7_000_000,
+        2 => 6_000_000,
+        3 => 5_000_000,
+        _ => 8_000_000,
+    }
+}
+
+fn decode_terrestrial_constellation(bits: u8) -> &'static str {
+    match bits {
+        0 => "QPSK",
+        1 => "QAM/16",
+        2 => "QAM/64",
+        _ => "QAM/AUTO",
+    }
+}
+
+fn decode_terrestrial_hierarchy(bits: u8) -> &'static str {
+    match bits {
+        0 => "NONE",
+        1 => "1",
+        2 => "2",
+        3 => "4",
+        _ => "NONE",
+    }
+}
+
+fn decode_terrestrial_code_rate(bits: u8) -> &'static str {
+    match bits {
+        0 => "1/2",
+        1 => "2/3",
+        2 => "3/4",
+        3 => "5/6",
+        4 => "7/8",
+        _ => "AUTO",
+    }
+}
+
+fn decode_terrestrial_guard_interval(bits: u8) -> &'static str {
+    match bits {
+        0 => "1/32",
+        1 => "1/16",
+        2 => "1/8",
+        3 => "1/4",
+        _ => "1/32",
+    }
+}
+
+fn decode_terrestrial_transmission_mode(bits: u8) -> &'static str {
+    match bits {
+        0 => "2K",
+        1 => "8K",
+        2 => "4K",
+        _ => "AUTO",
+    }
+}
+
+/// Parse a Terrestrial Delivery System Descriptor (tag 0x5A, ETSI EN 300 468
+/// table 84) into a [`ScanEntry`] ready to be tuned. `centre_frequency` is
+/// carried in units of 10 Hz. Returns `None` if the descriptor is shorter
+/// than its fixed 11-byte payload.
+fn parse_terrestrial_delivery_descriptor(desc: &[u8]) -> Option<ScanEntry> {
+    if desc.len() < 11 {
+        return None;
+    }
+
+    let centre_frequency = u32::from_be_bytes([desc[0], desc[1], desc[2], desc[3]]) as u64 * 10;
+
+    Some(ScanEntry {
+        delivery_system: "DVBT".to_string(),
+        frequency: centre_frequency,
+        bandwidth_hz: decode_terrestrial_bandwidth(desc[4] >> 5),
+        code_rate_hp: decode_terrestrial_code_rate(desc[5] & 0x07).to_string(),
+        code_rate_lp: decode_terrestrial_code_rate(desc[6] >> 5).to_string(),
+        modulation: decode_terrestrial_constellation(desc[5] >> 6).to_string(),
+        transmission_mode: decode_terrestrial_transmission_mode((desc[6] >> 1) & 0x03).to_string(),
+        guard_interval: decode_terrestrial_guard_interval((desc[6] >> 3) & 0x03).to_string(),
+        hierarchy: decode_terrestrial_hierarchy((desc[5] >> 3) & 0x07).to_string(),
+        inversion: "AUTO".to_string(),
+        symbol_rate: 0,
+        polarization: String::new(),
+        satellite_position: String::new(),
+        diseqc_port: 0,
+    })
+}
+
+fn decode_cable_modulation(bits: u8) -> &'static str {
+    match bits {
+        1 => "QAM/16",
+        2 => "QAM/32",
+        3 => "QAM/64",
+        4 => "QAM/128",
+        5 => "QAM/256",
+        _ => "QAM/AUTO",
+    }
+}
+
+/// `FEC_inner`, shared by the cable and satellite delivery descriptors
+/// (ETSI EN 300 468 table 53). 9/10 falls back to AUTO, same as an
+/// unrecognized value; 3/5 is returned as "3/5", which
+/// [`crate::channel::Fec`] cannot represent either and `dvbv5_to_zap_fec`
+/// later maps to FEC_AUTO.
+fn decode_fec_inner(bits: u8) -> &'static str {
+    match bits {
+        1 => "1/2",
+        2 => "2/3",
+        3 => "3/4",
+        4 => "5/6",
+        5 => "7/8",
+        6 => "8/9",
+        7 => "3/5",
+        8 => "4/5",
+        0xF => "NONE",
+        _ => "AUTO",
+    }
+}
+
+/// Parse a Cable Delivery System Descriptor (tag 0x44, ETSI EN 300 468 table
+/// 53) into a [`ScanEntry`] ready to be tuned. Returns `None` if the
+/// descriptor is shorter than its fixed 11-byte payload.
+fn parse_cable_delivery_descriptor(desc: &[u8]) -> Option<ScanEntry> {
+    if desc.len() < 11 {
+        return None;
+    }
+
+    // frequency: BCD, units of 100 Hz.
+    let frequency = bcd_to_u64(&desc[0..4]) * 100;
+    // symbol_rate: BCD, high 24 bits in desc[7..10], low nibble in desc[10],
+    // units of 100 symbols/second.
+    let symbol_rate = symbol_rate_from_bcd(desc);
+
+    Some(ScanEntry {
+        delivery_system: "DVBC".to_string(),
+        frequency,
+        bandwidth_hz: 8_000_000,
+        code_rate_hp: decode_fec_inner(desc[10] & 0x0F).to_string(),
+        code_rate_lp: String::new(),
+        modulation: decode_cable_modulation(desc[6]).to_string(),
+        transmission_mode: String::new(),
+        guard_interval: String::new(),
+        hierarchy: String::new(),
+        inversion: "AUTO".to_string(),
+        symbol_rate,
+        polarization: String::new(),
+        satellite_position: String::new(),
+        diseqc_port: 0,
+    })
+}
+
+/// Parse a Satellite Delivery System Descriptor (tag 0x43, ETSI EN 300 468
+/// table 48) into a [`ScanEntry`] ready to be tuned. Returns `None` if the
+/// descriptor is shorter than its fixed 11-byte payload. The LNB's DiSEqC
+/// port isn't broadcast anywhere (it depends on the viewer's own dish/switch
+/// wiring), so it's left at 0 for the caller to override.
+fn parse_satellite_delivery_descriptor(desc: &[u8]) -> Option<ScanEntry> {
+    if desc.len() < 11 {
+        return None;
+    }
+
+    // frequency: BCD, units of 10 kHz.
+    let frequency = bcd_to_u64(&desc[0..4]) * 10_000;
+
+    // orbital_position: BCD, units of 0.1 degree.
+    let orbital_tenths = bcd_to_u64(&desc[4..6]);
+    let west_east = if desc[6] & 0x80 != 0 { "E" } else { "W" };
+    let satellite_position = format!("{}.{}{}", orbital_tenths / 10, orbital_tenths % 10, west_east);
+
+    let polarization = match (desc[6] >> 5) & 0x03 {
+        0 => "H",
+        1 => "V",
+        2 => "L",
+        _ => "R",
+    };
+    let modulation_system = (desc[6] >> 2) & 0x01;
+    let modulation_type = desc[6] & 0x03;
+    let modulation = if modulation_system == 0 {
+        "QPSK"
+    } else {
+        match modulation_type {
+            1 => "QPSK",
+            2 => "PSK/8",
+            3 => "QAM/16",
+            _ => "QAM/AUTO",
+        }
+    };
+
+    let symbol_rate = symbol_rate_from_bcd(desc);
+
+    Some(ScanEntry {
+        delivery_system: "DVBS".to_string(),
+        frequency,
+        bandwidth_hz: 0,
+        code_rate_hp: decode_fec_inner(desc[10] & 0x0F).to_string(),
+        code_rate_lp: String::new(),
+        modulation: modulation.to_string(),
+        transmission_mode: String::new(),
+        guard_interval: String::new(),
+        hierarchy: String::new(),
+        inversion: "AUTO".to_string(),
+        symbol_rate,
+        polarization: polarization.to_string(),
+        satellite_position,
+        diseqc_port: 0,
+    })
+}
+
+/// Walk a NIT's transport_stream loop, pulling whichever delivery system
+/// descriptor (terrestrial 0x5A, cable 0x44, satellite 0x43) each transport
+/// stream's descriptor loop carries to synthesize a [`ScanEntry`] for every
+/// transponder the network announces (including the one already tuned).
+/// Callers de-duplicate by frequency since the same transponder is typically
+/// listed on every transponder's NIT.
+fn parse_nit_sections(sections: &[Vec<u8>]) -> Vec<ScanEntry> {
+    let mut entries = Vec::new();
+
+    for data in sections {
+        if data.len() < 10 {
+            continue;
+        }
+
+        let section_length = (((data[1] & 0x0F) as usize) << 8) | data[2] as usize;
+        let section_end = 3 + section_length;
+        if data.len() < section_end {
+            continue;
+        }
+        let entries_end = section_end - 4; // exclude CRC
+
+        let network_descriptors_length = (((data[8] & 0x0F) as usize) << 8) | data[9] as usize;
+        let mut pos = 10 + network_descriptors_length;
+        if pos + 2 > entries_end {
+            continue;
+        }
+
+        let transport_stream_loop_length =
+            (((data[pos] & 0x0F) as usize) << 8) | data[pos + 1] as usize;
+        pos += 2;
+        let loop_end = (pos + transport_stream_loop_length).min(entries_end);
+
+        while pos + 6 <= loop_end {
+            let transport_descriptors_length =
+                (((data[pos + 4] & 0x0F) as usize) << 8) | data[pos + 5] as usize;
+            let desc_start = pos + 6;
+            let desc_end = (desc_start + transport_descriptors_length).min(loop_end);
+
+            let mut dpos = desc_start;
+            while dpos + 2 <= desc_end {
+                let tag = data[dpos];
+                let len = data[dpos + 1] as usize;
+                if dpos + 2 + len > desc_end {
+                    break;
+                }
+
+                let payload = &data[dpos + 2..dpos + 2 + len];
+                let entry = match tag {
+                    TERRESTRIAL_DELIVERY_SYSTEM_DESCRIPTOR => {
+                        parse_terrestrial_delivery_descriptor(payload)
+                    }
+                    CABLE_DELIVERY_SYSTEM_DESCRIPTOR => parse_cable_delivery_descriptor(payload),
+                    SATELLITE_DELIVERY_SYSTEM_DESCRIPTOR => {
+                        parse_satellite_delivery_descriptor(payload)
+                    }
+                    _ => None,
+                };
+                if let Some(entry) = entry {
+                    entries.push(entry);
+                }
+
+                dpos += 2 + len;
+            }
+
+            pos = desc_end;
+        }
+    }
+
+    entries
+}
+
+/// Read the NIT for the currently tuned transponder (PID 0x0010, table_id
+/// 0x40 for the actual network and 0x41 for other networks the broadcaster
+/// announces) and return a [`ScanEntry`] for every transponder it lists.
+/// Used to auto-discover the rest of a network from a single seed frequency;
+/// missing or unreadable NIT tables simply yield no extra transponders.
+pub fn discover_transponders(adapter: u32) -> Vec<ScanEntry> {
+    let mut entries = Vec::new();
+    for table_id in [0x40, 0x41] {
+        if let Ok(sections) = read_all_sections(adapter, 0x0010, table_id, 5) {
+            entries.extend(parse_nit_sections(&sections));
+        }
+    }
+    entries
+}
+
 // --- PMT parsing (variable PID, table_id 0x02) ---
 
+// Descriptor tags read out of a PMT elementary stream's ES info loop.
+const ISO_639_LANGUAGE_DESCRIPTOR: u8 = 0x0A;
+const TELETEXT_DESCRIPTOR: u8 = 0x56;
+const SUBTITLING_DESCRIPTOR: u8 = 0x59;
+const AC3_DESCRIPTOR: u8 = 0x6A;
+const REGISTRATION_DESCRIPTOR: u8 = 0x05;
+const ENHANCED_AC3_DESCRIPTOR: u8 = 0x7A;
+
+/// Classify a PMT elementary stream from its raw `stream_type`, before any
+/// ES info descriptors are taken into account.
+fn classify_stream_type(stream_type: u8) -> StreamKind {
+    match stream_type {
+        // MPEG-1(0x01), MPEG-2(0x02), MPEG-4(0x10), H.264(0x1B), H.265(0x24)
+        0x01 | 0x02 | 0x10 | 0x1B | 0x24 => StreamKind::Video,
+        // MPEG-1(0x03), MPEG-2(0x04), AAC(0x0F), HE-AAC(0x11), AC-3(0x81), E-AC-3(0x87)
+        0x03 | 0x04 | 0x0F | 0x11 | 0x81 | 0x87 => StreamKind::Audio,
+        // 0x06 is "private data" / unspecified PES and needs a descriptor
+        // (subtitling, teletext, AC-3, or a format registration) to resolve.
+        _ => StreamKind::Other,
+    }
+}
+
+/// Walk a PMT elementary stream's ES info descriptor loop, returning the
+/// language it names (if any) and the `StreamKind` refined by descriptors
+/// that override the bare `stream_type` guess (subtitling, teletext, AC-3/
+/// Enhanced AC-3, and an AV1 format registration).
+fn parse_es_descriptors(stream_type: u8, mut kind: StreamKind, data: &[u8]) -> (StreamKind, String) {
+    let mut language = String::new();
+    let mut pos = 0;
+
+    while pos + 2 <= data.len() {
+        let tag = data[pos];
+        let len = data[pos + 1] as usize;
+        if pos + 2 + len > data.len() {
+            break;
+        }
+        let desc = &data[pos + 2..pos + 2 + len];
+
+        match tag {
+            ISO_639_LANGUAGE_DESCRIPTOR if desc.len() >= 4 => {
+                language = String::from_utf8_lossy(&desc[0..3]).to_string();
+            }
+            SUBTITLING_DESCRIPTOR if desc.len() >= 8 => {
+                language = String::from_utf8_lossy(&desc[0..3]).to_string();
+                kind = StreamKind::Subtitle;
+            }
+            TELETEXT_DESCRIPTOR if desc.len() >= 5 => {
+                language = String::from_utf8_lossy(&desc[0..3]).to_string();
+                kind = StreamKind::Teletext;
+            }
+            AC3_DESCRIPTOR | ENHANCED_AC3_DESCRIPTOR => {
+                if kind == StreamKind::Other {
+                    kind = StreamKind::Audio;
+                }
+            }
+            REGISTRATION_DESCRIPTOR if desc.len() >= 4 => {
+                if stream_type == 0x06 && &desc[0..4] == b"AV01" {
+                    kind = StreamKind::Video;
+                }
+            }
+            _ => {}
+        }
+
+        pos += 2 + len;
+    }
+
+    (kind, language)
+}
+
 struct PmtInfo {
-    video_pid: u16,
-    audio_pid: u16,
+    streams: Vec<ElementaryStream>,
 }
 
 fn parse_pmt(data: &[u8]) -> Result<PmtInfo, String> {
@@ -787,30 +1834,29 @@ fn parse_pmt(data: &[u8]) -> Result<PmtInfo, String> {
     let entries_end = section_end - 4;
     let mut pos = 12 + program_info_length;
 
-    let mut video_pid: u16 = 0;
-    let mut audio_pid: u16 = 0;
+    let mut streams = Vec::new();
 
     while pos + 5 <= entries_end {
         let stream_type = data[pos];
         let elementary_pid = ((data[pos + 1] & 0x1F) as u16) << 8 | data[pos + 2] as u16;
         let es_info_length = (((data[pos + 3] & 0x0F) as usize) << 8) | data[pos + 4] as usize;
+        let es_info_end = (pos + 5 + es_info_length).min(entries_end);
+        let es_info = &data[pos + 5..es_info_end];
 
-        // Video: MPEG-1(0x01), MPEG-2(0x02), MPEG-4(0x10), H.264(0x1B), H.265(0x24)
-        if video_pid == 0 && matches!(stream_type, 0x01 | 0x02 | 0x10 | 0x1B | 0x24) {
-            video_pid = elementary_pid;
-        }
-        // Audio: MPEG-1(0x03), MPEG-2(0x04), AAC(0x0F), HE-AAC(0x11)
-        if audio_pid == 0 && matches!(stream_type, 0x03 | 0x04 | 0x0F | 0x11) {
-            audio_pid = elementary_pid;
-        }
+        let base_kind = classify_stream_type(stream_type);
+        let (kind, language) = parse_es_descriptors(stream_type, base_kind, es_info);
+
+        streams.push(ElementaryStream {
+            pid: elementary_pid,
+            stream_type,
+            kind,
+            language,
+        });
 
         pos += 5 + es_info_length;
     }
 
-    Ok(PmtInfo {
-        video_pid,
-        audio_pid,
-    })
+    Ok(PmtInfo { streams })
 }
 
 // --- Channel scanning orchestrator ---
@@ -843,31 +1889,32 @@ pub fn scan_frequency(adapter: u32, entry: &ScanEntry) -> Result<Vec<Channel>, S
         // Read PMT for this service (single section per program)
         let pmt = match read_all_sections(adapter, pat_entry.pmt_pid, 0x02, 5) {
             Ok(sections) if !sections.is_empty() => {
-                parse_pmt(&sections[0]).unwrap_or(PmtInfo {
-                    video_pid: 0,
-                    audio_pid: 0,
-                })
+                parse_pmt(&sections[0]).unwrap_or(PmtInfo { streams: Vec::new() })
             }
-            _ => PmtInfo {
-                video_pid: 0,
-                audio_pid: 0,
-            },
+            _ => PmtInfo { streams: Vec::new() },
         };
 
+        let video_pid = pmt
+            .streams
+            .iter()
+            .find(|s| s.kind == StreamKind::Video)
+            .map(|s| s.pid)
+            .unwrap_or(0);
+        let audio_pid = pmt
+            .streams
+            .iter()
+            .find(|s| s.kind == StreamKind::Audio)
+            .map(|s| s.pid)
+            .unwrap_or(0);
+
         channels.push(Channel {
             name,
             frequency: base.frequency,
-            inversion: base.inversion.clone(),
-            bandwidth: base.bandwidth.clone(),
-            fec_hp: base.fec_hp.clone(),
-            fec_lp: base.fec_lp.clone(),
-            modulation: base.modulation.clone(),
-            transmission_mode: base.transmission_mode.clone(),
-            guard_interval: base.guard_interval.clone(),
-            hierarchy: base.hierarchy.clone(),
-            video_pid: pmt.video_pid,
-            audio_pid: pmt.audio_pid,
+            video_pid,
+            audio_pid,
             service_id: pat_entry.service_id,
+            tuning: base.tuning.clone(),
+            elementary_streams: pmt.streams,
         });
     }
 
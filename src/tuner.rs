@@ -1,9 +1,13 @@
 use std::fs::OpenOptions;
-use std::os::unix::io::AsRawFd;
+use std::os::unix::io::{AsFd, AsRawFd};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use crate::channel::Channel;
+use nix::poll::{poll, PollFd, PollFlags, PollTimeout};
+
+use crate::channel::{Channel, Fec, GuardInterval, Inversion, Modulation, TransmissionMode, Tuning};
+use crate::debug;
+use crate::info;
 
 // DVB v5 API property command IDs
 const DTV_TUNE: u32 = 1;
@@ -12,19 +16,127 @@ const DTV_FREQUENCY: u32 = 3;
 const DTV_MODULATION: u32 = 4;
 const DTV_BANDWIDTH_HZ: u32 = 5;
 const DTV_INVERSION: u32 = 6;
+const DTV_SYMBOL_RATE: u32 = 8;
+const DTV_INNER_FEC: u32 = 9;
+const DTV_PILOT: u32 = 12;
+const DTV_ROLLOFF: u32 = 13;
 const DTV_DELIVERY_SYSTEM: u32 = 17;
 const DTV_CODE_RATE_HP: u32 = 36;
 const DTV_CODE_RATE_LP: u32 = 37;
 const DTV_GUARD_INTERVAL: u32 = 38;
 const DTV_TRANSMISSION_MODE: u32 = 39;
 const DTV_HIERARCHY: u32 = 40;
+const DTV_STAT_SIGNAL_STRENGTH: u32 = 62;
+const DTV_STAT_CNR: u32 = 63;
+const DTV_STAT_PRE_ERROR_BIT_COUNT: u32 = 64;
+const DTV_STAT_PRE_TOTAL_BIT_COUNT: u32 = 65;
+const DTV_STAT_ERROR_BLOCK_COUNT: u32 = 68;
 
 // Delivery system
 const SYS_DVBT: u32 = 3;
+const SYS_DVBC_ANNEX_A: u32 = 1;
+const SYS_DVBS: u32 = 5;
+const SYS_DVBS2: u32 = 6;
+const SYS_ATSC: u32 = 11;
 
 // Frontend status flags
 const FE_HAS_LOCK: u32 = 0x10;
 
+// dtv_fe_stats scale tags (enum fecap_scale_params); 0 = NOT_AVAILABLE is
+// the default/fallback case and has no named constant below.
+const FE_SCALE_DECIBEL: u8 = 1;
+const FE_SCALE_RELATIVE: u8 = 2;
+const FE_SCALE_COUNTER: u8 = 3;
+
+// LNB/DiSEqC voltage and 22kHz tone state
+const SEC_VOLTAGE_13: i32 = 0; // Vertical/Right
+const SEC_VOLTAGE_18: i32 = 1; // Horizontal/Left
+const SEC_TONE_ON: i32 = 0; // high band
+const SEC_TONE_OFF: i32 = 1; // low band
+
+// Kernel struct: dvb_diseqc_master_cmd
+#[repr(C)]
+struct DiseqcMasterCmd {
+    msg: [u8; 6],
+    msg_len: u8,
+}
+
+// Kernel struct: dvb_frontend_info
+//   name: char[128], type: fe_type_t (u32), frequency_{min,max,stepsize,tolerance}: u32,
+//   symbol_rate_{min,max,tolerance}: u32, notifier_delay: u32, caps: fe_caps_t (u32)
+#[repr(C)]
+struct DvbFrontendInfo {
+    name: [u8; 128],
+    fe_type: u32,
+    frequency_min: u32,
+    frequency_max: u32,
+    frequency_stepsize: u32,
+    frequency_tolerance: u32,
+    symbol_rate_min: u32,
+    symbol_rate_max: u32,
+    symbol_rate_tolerance: u32,
+    notifier_delay: u32,
+    caps: u32,
+}
+
+// Kernel struct: dvb_frontend_event (40 bytes)
+//   status: fe_status_t (u32), parameters: dvb_frontend_parameters (36
+//   bytes; the deprecated legacy per-delivery-system tuning params, which
+//   we never read — only `status` matters for lock detection)
+#[repr(C)]
+struct DvbFrontendEvent {
+    status: u32,
+    _parameters: [u8; 36],
+}
+
+/// The frontend's fundamental delivery-system family, as reported by
+/// `FE_GET_INFO`'s (deprecated but still populated) `type` field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrontendType {
+    Qpsk,
+    Qam,
+    Ofdm,
+    Atsc,
+}
+
+impl FrontendType {
+    fn from_raw(raw: u32) -> Option<Self> {
+        match raw {
+            0 => Some(FrontendType::Qpsk),
+            1 => Some(FrontendType::Qam),
+            2 => Some(FrontendType::Ofdm),
+            3 => Some(FrontendType::Atsc),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for FrontendType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let label = match self {
+            FrontendType::Qpsk => "DVB-S",
+            FrontendType::Qam => "DVB-C",
+            FrontendType::Ofdm => "DVB-T",
+            FrontendType::Atsc => "ATSC",
+        };
+        f.write_str(label)
+    }
+}
+
+/// Frontend capabilities reported by `FE_GET_INFO`, enough to know what a
+/// tuner can do before we commit to tuning it (mirrors how mpv/v4l-utils
+/// probe `fe_info.type` and the frequency/symbol-rate ranges).
+pub struct FrontendInfo {
+    pub name: String,
+    pub fe_type: FrontendType,
+    pub frequency_min: u32,
+    pub frequency_max: u32,
+    pub frequency_stepsize: u32,
+    pub symbol_rate_min: u32,
+    pub symbol_rate_max: u32,
+    pub caps: u32,
+}
+
 // Kernel struct: dtv_property (76 bytes, packed)
 //   cmd: u32, reserved: [u32; 3], u: union(56 bytes), result: i32
 #[repr(C, packed)]
@@ -44,9 +156,41 @@ struct DtvProperties {
     props: *mut DtvProperty,
 }
 
+// Same layout as DtvProperty, but reading the union back as a
+// `dtv_fe_stats` (`{ u8 len; struct dtv_stats { u8 scale; union { u64; i64;
+// } value; } stat[4]; }`) instead of writing a plain u32. We only ever care
+// about the first reported stat entry, so the remaining three `stat[]`
+// slots are folded into `_padding` along with the rest of the union.
+#[repr(C, packed)]
+struct DtvStatProperty {
+    cmd: u32,
+    reserved: [u32; 3],
+    stat_len: u8,
+    scale0: u8,
+    value0: i64,
+    _padding: [u8; 46], // remaining union space (56 - 1 - 1 - 8)
+    result: i32,
+}
+
+#[repr(C)]
+struct DtvStatProperties {
+    num: u32,
+    props: *mut DtvStatProperty,
+}
+
 // ioctl declarations
 nix::ioctl_write_ptr!(fe_set_property, b'o', 82, DtvProperties);
+nix::ioctl_readwrite!(fe_get_property, b'o', 89, DtvStatProperties);
 nix::ioctl_read!(fe_read_status, b'o', 69, u32);
+nix::ioctl_read!(fe_read_ber, b'o', 70, u32);
+nix::ioctl_read!(fe_read_signal_strength, b'o', 71, u16);
+nix::ioctl_read!(fe_read_snr, b'o', 72, u16);
+nix::ioctl_read!(fe_read_uncorrected_blocks, b'o', 73, u32);
+nix::ioctl_read!(fe_get_info, b'o', 61, DvbFrontendInfo);
+nix::ioctl_read!(fe_get_event, b'o', 78, DvbFrontendEvent);
+nix::ioctl_write_ptr!(fe_diseqc_send_master_cmd, b'o', 63, DiseqcMasterCmd);
+nix::ioctl_write_int!(fe_set_tone, b'o', 66);
+nix::ioctl_write_int!(fe_set_voltage, b'o', 67);
 
 impl DtvProperty {
     fn new(cmd: u32, data: u32) -> Self {
@@ -60,6 +204,73 @@ impl DtvProperty {
     }
 }
 
+impl DtvStatProperty {
+    fn new(cmd: u32) -> Self {
+        DtvStatProperty {
+            cmd,
+            reserved: [0; 3],
+            stat_len: 0,
+            scale0: 0,
+            value0: 0,
+            _padding: [0; 46],
+            result: 0,
+        }
+    }
+}
+
+/// A single DVB v5 statistic, decoded from its `dtv_fe_stats` scale tag so
+/// callers never have to interpret the raw scale/value encoding themselves.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Stat {
+    /// Value in dB (kernel reports `FE_SCALE_DECIBEL` in 0.001 dB units).
+    Decibel(f64),
+    /// A driver-relative 0..65535 reading, for `FE_SCALE_RELATIVE`.
+    Relative(u16),
+    /// A raw counter, for `FE_SCALE_COUNTER` (used by the bit/block-error
+    /// stats).
+    Counter(u64),
+    /// `FE_SCALE_NOT_AVAILABLE`, or a scale we don't recognize.
+    Unavailable,
+}
+
+impl Stat {
+    fn from_raw(scale: u8, value: u64) -> Stat {
+        match scale {
+            FE_SCALE_DECIBEL => Stat::Decibel(value as i64 as f64 / 1000.0),
+            FE_SCALE_RELATIVE => Stat::Relative(value as u16),
+            FE_SCALE_COUNTER => Stat::Counter(value),
+            _ => Stat::Unavailable,
+        }
+    }
+
+    fn is_available(self) -> bool {
+        !matches!(self, Stat::Unavailable)
+    }
+}
+
+/// Combine the DVB v5 pre-BCH/pre-Viterbi error and total bit counters into
+/// a bit error rate, if both were reported as counters.
+fn ber_ratio(error_bits: Stat, total_bits: Stat) -> Option<f64> {
+    match (error_bits, total_bits) {
+        (Stat::Counter(errors), Stat::Counter(total)) if total > 0 => {
+            Some(errors as f64 / total as f64)
+        }
+        _ => None,
+    }
+}
+
+/// Signal-quality stats returned by [`Tuner::stats`]: enough to judge
+/// reception quality and log it while grabbing EPG.
+pub struct SignalStats {
+    pub signal_strength: Stat,
+    pub cnr: Stat,
+    /// Pre-BCH/pre-Viterbi bit error rate as a 0.0..1.0 ratio, or the
+    /// legacy driver-reported value (unitless, driver-specific) when the
+    /// v5 counters aren't available.
+    pub ber: Option<f64>,
+    pub uncorrected_blocks: Stat,
+}
+
 fn parse_bandwidth(s: &str) -> Result<u32, String> {
     match s {
         "BANDWIDTH_6_MHZ" => Ok(6_000_000),
@@ -82,10 +293,33 @@ fn parse_modulation(s: &str) -> Result<u32, String> {
         "QAM_128" => Ok(4),
         "QAM_256" => Ok(5),
         "QAM_AUTO" => Ok(6),
+        "8VSB" => Ok(7),
+        "16VSB" => Ok(8),
+        "PSK_8" => Ok(9),
+        "APSK_16" => Ok(10),
         _ => Err(format!("Unknown modulation: {s}")),
     }
 }
 
+fn parse_pilot(s: &str) -> Result<u32, String> {
+    match s {
+        "PILOT_ON" => Ok(0),
+        "PILOT_OFF" => Ok(1),
+        "PILOT_AUTO" => Ok(2),
+        _ => Err(format!("Unknown pilot: {s}")),
+    }
+}
+
+fn parse_rolloff(s: &str) -> Result<u32, String> {
+    match s {
+        "ROLLOFF_35" => Ok(0),
+        "ROLLOFF_20" => Ok(1),
+        "ROLLOFF_25" => Ok(2),
+        "ROLLOFF_AUTO" => Ok(3),
+        _ => Err(format!("Unknown rolloff: {s}")),
+    }
+}
+
 fn parse_fec(s: &str) -> Result<u32, String> {
     match s {
         "FEC_NONE" => Ok(0),
@@ -149,6 +383,7 @@ fn parse_hierarchy(s: &str) -> Result<u32, String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::channel::{Bandwidth, Hierarchy};
 
     // --- parse_bandwidth ---
 
@@ -179,6 +414,10 @@ mod tests {
         assert_eq!(parse_modulation("QAM_128").unwrap(), 4);
         assert_eq!(parse_modulation("QAM_256").unwrap(), 5);
         assert_eq!(parse_modulation("QAM_AUTO").unwrap(), 6);
+        assert_eq!(parse_modulation("8VSB").unwrap(), 7);
+        assert_eq!(parse_modulation("16VSB").unwrap(), 8);
+        assert_eq!(parse_modulation("PSK_8").unwrap(), 9);
+        assert_eq!(parse_modulation("APSK_16").unwrap(), 10);
     }
 
     #[test]
@@ -186,6 +425,35 @@ mod tests {
         assert!(parse_modulation("INVALID").is_err());
     }
 
+    // --- parse_pilot ---
+
+    #[test]
+    fn test_parse_pilot_all_values() {
+        assert_eq!(parse_pilot("PILOT_ON").unwrap(), 0);
+        assert_eq!(parse_pilot("PILOT_OFF").unwrap(), 1);
+        assert_eq!(parse_pilot("PILOT_AUTO").unwrap(), 2);
+    }
+
+    #[test]
+    fn test_parse_pilot_unknown() {
+        assert!(parse_pilot("INVALID").is_err());
+    }
+
+    // --- parse_rolloff ---
+
+    #[test]
+    fn test_parse_rolloff_all_values() {
+        assert_eq!(parse_rolloff("ROLLOFF_35").unwrap(), 0);
+        assert_eq!(parse_rolloff("ROLLOFF_20").unwrap(), 1);
+        assert_eq!(parse_rolloff("ROLLOFF_25").unwrap(), 2);
+        assert_eq!(parse_rolloff("ROLLOFF_AUTO").unwrap(), 3);
+    }
+
+    #[test]
+    fn test_parse_rolloff_unknown() {
+        assert!(parse_rolloff("INVALID").is_err());
+    }
+
     // --- parse_fec ---
 
     #[test]
@@ -270,8 +538,351 @@ mod tests {
     fn test_parse_hierarchy_unknown() {
         assert!(parse_hierarchy("INVALID").is_err());
     }
+
+    // --- Stat::from_raw ---
+
+    #[test]
+    fn test_stat_from_raw_decibel() {
+        assert_eq!(
+            Stat::from_raw(FE_SCALE_DECIBEL, (-4_500i64) as u64),
+            Stat::Decibel(-4.5)
+        );
+    }
+
+    #[test]
+    fn test_stat_from_raw_relative() {
+        assert_eq!(Stat::from_raw(FE_SCALE_RELATIVE, 30_000), Stat::Relative(30_000));
+    }
+
+    #[test]
+    fn test_stat_from_raw_counter() {
+        assert_eq!(Stat::from_raw(FE_SCALE_COUNTER, 42), Stat::Counter(42));
+    }
+
+    #[test]
+    fn test_stat_from_raw_not_available() {
+        assert_eq!(Stat::from_raw(0, 999), Stat::Unavailable);
+    }
+
+    #[test]
+    fn test_stat_from_raw_unknown_scale() {
+        assert_eq!(Stat::from_raw(99, 1), Stat::Unavailable);
+    }
+
+    #[test]
+    fn test_stat_is_available() {
+        assert!(Stat::Relative(1).is_available());
+        assert!(!Stat::Unavailable.is_available());
+    }
+
+    // --- ber_ratio ---
+
+    #[test]
+    fn test_ber_ratio_from_counters() {
+        assert_eq!(
+            ber_ratio(Stat::Counter(5), Stat::Counter(1_000)),
+            Some(0.005)
+        );
+    }
+
+    #[test]
+    fn test_ber_ratio_unavailable_without_both_counters() {
+        assert_eq!(ber_ratio(Stat::Unavailable, Stat::Counter(1_000)), None);
+        assert_eq!(ber_ratio(Stat::Counter(5), Stat::Decibel(-1.0)), None);
+    }
+
+    #[test]
+    fn test_ber_ratio_zero_total_is_unavailable() {
+        assert_eq!(ber_ratio(Stat::Counter(0), Stat::Counter(0)), None);
+    }
+
+    // --- lnb_if_khz ---
+
+    #[test]
+    fn test_lnb_if_khz_low_band() {
+        let lnb = LnbConfig::universal();
+        let (if_khz, hiband) = lnb_if_khz(11_494_000, &lnb);
+        assert_eq!(if_khz, 1_744_000);
+        assert!(!hiband);
+    }
+
+    #[test]
+    fn test_lnb_if_khz_high_band() {
+        let lnb = LnbConfig::universal();
+        let (if_khz, hiband) = lnb_if_khz(12_515_000, &lnb);
+        assert_eq!(if_khz, 1_915_000);
+        assert!(hiband);
+    }
+
+    #[test]
+    fn test_lnb_if_khz_switchover_boundary() {
+        let lnb = LnbConfig::universal();
+        let (_, hiband) = lnb_if_khz(lnb.switch_khz, &lnb);
+        assert!(hiband);
+    }
+
+    #[test]
+    fn test_lnb_if_khz_garbles_unconverted_hz_input() {
+        // channel.frequency is Hz-scale (see channel::parse_frequency); tune_dvbs
+        // must divide by 1000 before calling lnb_if_khz, which expects kHz.
+        let lnb = LnbConfig::universal();
+        let transponder_hz: u64 = 12_515_000_000;
+        let (if_khz, hiband) = lnb_if_khz(transponder_hz / 1000, &lnb);
+        assert_eq!(if_khz, 1_915_000);
+        assert!(hiband);
+
+        // Passing the raw Hz value in unconverted is the regressed bug: it blows
+        // past any sane IF frequency and would be truncated by the u32 DTV_FREQUENCY
+        // property instead of producing a lockable intermediate frequency.
+        let (bogus_if_khz, _) = lnb_if_khz(transponder_hz, &lnb);
+        assert_ne!(bogus_if_khz, if_khz);
+        assert!(bogus_if_khz > u32::MAX as u64);
+    }
+
+    // --- diseqc_committed_switch_cmd ---
+
+    #[test]
+    fn test_diseqc_committed_switch_cmd_port0_vertical_lowband() {
+        let cmd = diseqc_committed_switch_cmd(0, true, false);
+        assert_eq!(cmd.msg_len, 4);
+        assert_eq!(&cmd.msg[..4], &[0xE0, 0x10, 0x38, 0xF1]);
+    }
+
+    #[test]
+    fn test_diseqc_committed_switch_cmd_port1_horizontal_hiband() {
+        let cmd = diseqc_committed_switch_cmd(1, false, true);
+        assert_eq!(&cmd.msg[..4], &[0xE0, 0x10, 0x38, 0xF6]);
+    }
+
+    #[test]
+    fn test_diseqc_committed_switch_cmd_port3_vertical_hiband() {
+        let cmd = diseqc_committed_switch_cmd(3, true, true);
+        assert_eq!(&cmd.msg[..4], &[0xE0, 0x10, 0x38, 0xFC]);
+    }
+
+    // --- DVBT_RETRY_STEPS ---
+
+    fn sample_dvbt_channel() -> Channel {
+        Channel {
+            name: "Test".to_string(),
+            frequency: 578000000,
+            video_pid: 256,
+            audio_pid: 257,
+            service_id: 1,
+            tuning: Tuning::DvbT {
+                inversion: Inversion::Off,
+                bandwidth: Bandwidth::Mhz8,
+                fec_hp: Fec::TwoThirds,
+                fec_lp: Fec::None,
+                modulation: Modulation::Qam64,
+                transmission_mode: TransmissionMode::K8,
+                guard_interval: GuardInterval::Eighth,
+                hierarchy: Hierarchy::None,
+            },
+            elementary_streams: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_dvbt_retry_steps_are_cumulative() {
+        let mut channel = sample_dvbt_channel();
+        for (_, downgrade) in DVBT_RETRY_STEPS {
+            downgrade(&mut channel.tuning);
+        }
+        let Tuning::DvbT {
+            inversion,
+            fec_hp,
+            fec_lp,
+            modulation,
+            transmission_mode,
+            guard_interval,
+            ..
+        } = channel.tuning
+        else {
+            unreachable!()
+        };
+        assert_eq!(inversion, Inversion::Auto);
+        assert_eq!(modulation, Modulation::QamAuto);
+        assert_eq!(fec_hp, Fec::Auto);
+        assert_eq!(fec_lp, Fec::Auto);
+        assert_eq!(guard_interval, GuardInterval::Auto);
+        assert_eq!(transmission_mode, TransmissionMode::Auto);
+    }
+
+    #[test]
+    fn test_dvbt_retry_steps_first_step_only_touches_inversion() {
+        let mut channel = sample_dvbt_channel();
+        DVBT_RETRY_STEPS[0].1(&mut channel.tuning);
+        let Tuning::DvbT {
+            inversion,
+            modulation,
+            ..
+        } = channel.tuning
+        else {
+            unreachable!()
+        };
+        assert_eq!(inversion, Inversion::Auto);
+        assert_eq!(modulation, Modulation::Qam64);
+    }
 }
 
+/// LNB local oscillator frequencies and switchover point, in kHz.
+///
+/// Defaults to a Universal (Ku-band) LNB. Non-universal LNBs (single,
+/// C-band, etc.) can be modeled by constructing this directly instead of
+/// going through [`LnbConfig::universal`].
+#[derive(Debug, Clone)]
+pub struct LnbConfig {
+    pub low_lof_khz: u64,
+    pub high_lof_khz: u64,
+    pub switch_khz: u64,
+}
+
+impl LnbConfig {
+    /// Universal Ku-band LNB: low LOF 9750 MHz, high LOF 10600 MHz,
+    /// switching to high band above 11700 MHz.
+    pub fn universal() -> Self {
+        LnbConfig {
+            low_lof_khz: 9_750_000,
+            high_lof_khz: 10_600_000,
+            switch_khz: 11_700_000,
+        }
+    }
+}
+
+/// Mix a satellite transponder frequency (kHz) down to the intermediate
+/// frequency the tuner front-end actually receives from the LNB, and
+/// report whether the high band (and therefore the 22 kHz tone) is in use.
+fn lnb_if_khz(transponder_khz: u64, lnb: &LnbConfig) -> (u64, bool) {
+    if transponder_khz < lnb.switch_khz {
+        (transponder_khz.saturating_sub(lnb.low_lof_khz), false)
+    } else {
+        (transponder_khz.saturating_sub(lnb.high_lof_khz), true)
+    }
+}
+
+/// Build a DiSEqC 1.0 committed-switch command selecting one of four LNB
+/// inputs on a multi-satellite switch, per polarization and band.
+fn diseqc_committed_switch_cmd(port: u8, vertical: bool, hiband: bool) -> DiseqcMasterCmd {
+    let data = 0xF0
+        | (port << 2)
+        | if vertical { 0 } else { 2 }
+        | if hiband { 0 } else { 1 };
+    DiseqcMasterCmd {
+        msg: [0xE0, 0x10, 0x38, data, 0, 0],
+        msg_len: 4,
+    }
+}
+
+/// Distinguishes a lock timeout (common — usually just means "no signal
+/// here" — and safe to skip past) from every other tuning failure (a
+/// failed ioctl, an unparseable property value, a channel of the wrong
+/// delivery-system type), so callers can decide whether it's worth
+/// retrying.
+#[derive(Debug)]
+pub enum TuneError {
+    /// An ioctl failed, or some other non-timeout error occurred.
+    Ioctl(String),
+    /// [`TuneConfig::tune_timeout`] elapsed without the frontend reporting
+    /// `FE_HAS_LOCK`.
+    Timeout(String),
+}
+
+impl std::fmt::Display for TuneError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TuneError::Ioctl(msg) | TuneError::Timeout(msg) => f.write_str(msg),
+        }
+    }
+}
+
+// Lets the many pre-existing `some_string_result?` call sites inside the
+// tune_* methods keep working unchanged under the new TuneError return
+// type; any such error is a non-timeout failure by construction.
+impl From<String> for TuneError {
+    fn from(msg: String) -> Self {
+        TuneError::Ioctl(msg)
+    }
+}
+
+impl From<TuneError> for String {
+    fn from(e: TuneError) -> Self {
+        e.to_string()
+    }
+}
+
+/// Per-attempt tuning timeouts, following the budgets VDR's frontend driver
+/// uses: `tune_timeout` is the overall ceiling on the whole tune attempt;
+/// `lock_timeout` is the longest single `poll(2)` slice `wait_for_lock`
+/// blocks for, so it can re-check the overall budget in between. Also
+/// carries the LNB to use for satellite channels, since which LNB is
+/// attached is a dish/setup property, not something a channel list entry
+/// (or `satellite_position`, which is purely descriptive) can tell us.
+pub struct TuneConfig {
+    pub tune_timeout: Duration,
+    pub lock_timeout: Duration,
+    pub lnb: LnbConfig,
+    /// Opt-in: when a DVB-T tune times out waiting for lock, retry it with
+    /// [`DVBT_RETRY_STEPS`]'s progressively more `*_AUTO` parameters before
+    /// giving up. Off by default since it multiplies the worst-case wait
+    /// time by the length of the retry table.
+    pub retry_auto: bool,
+}
+
+impl Default for TuneConfig {
+    /// VDR's `TUNE_TIMEOUT`/`LOCK_TIMEOUT`: 9s to tune, 2s per poll slice,
+    /// with a Universal (Ku-band) LNB and no auto-retry.
+    fn default() -> Self {
+        TuneConfig {
+            tune_timeout: Duration::from_millis(9000),
+            lock_timeout: Duration::from_millis(2000),
+            lnb: LnbConfig::universal(),
+            retry_auto: false,
+        }
+    }
+}
+
+/// Successive fallback steps [`Tuner::tune_dvbt_with_retry`] walks through
+/// after an initial DVB-T tune times out: each step forces one more
+/// parameter to its `*_AUTO` value, cumulative with every earlier step,
+/// since frontends very commonly mislabel these in a scanned
+/// channels.conf. Ordered cheapest/most-likely-wrong first, same as the
+/// kernel's own `force_auto_inversion` fallback.
+/// A retry step's label paired with the function that applies it.
+type DvbtRetryStep = (&'static str, fn(&mut Tuning));
+
+const DVBT_RETRY_STEPS: &[DvbtRetryStep] = &[
+    ("inversion", |t| {
+        if let Tuning::DvbT { inversion, .. } = t {
+            *inversion = Inversion::Auto;
+        }
+    }),
+    ("modulation", |t| {
+        if let Tuning::DvbT { modulation, .. } = t {
+            *modulation = Modulation::QamAuto;
+        }
+    }),
+    ("code_rate", |t| {
+        if let Tuning::DvbT { fec_hp, fec_lp, .. } = t {
+            *fec_hp = Fec::Auto;
+            *fec_lp = Fec::Auto;
+        }
+    }),
+    ("guard_interval", |t| {
+        if let Tuning::DvbT { guard_interval, .. } = t {
+            *guard_interval = GuardInterval::Auto;
+        }
+    }),
+    ("transmission_mode", |t| {
+        if let Tuning::DvbT {
+            transmission_mode, ..
+        } = t
+        {
+            *transmission_mode = TransmissionMode::Auto;
+        }
+    }),
+];
+
 pub struct Tuner {
     fe_file: std::fs::File,
 }
@@ -298,7 +909,200 @@ impl Tuner {
         status & FE_HAS_LOCK != 0
     }
 
-    pub fn tune(&self, channel: &Channel) -> Result<(), String> {
+    /// Query the frontend's capabilities via `FE_GET_INFO`: device name,
+    /// supported frequency/symbol-rate ranges, and the capability bitmask.
+    pub fn info(&self) -> Result<FrontendInfo, String> {
+        let fd = self.fe_file.as_raw_fd();
+        let mut raw: DvbFrontendInfo = unsafe { std::mem::zeroed() };
+        unsafe {
+            fe_get_info(fd, &mut raw).map_err(|e| format!("FE_GET_INFO failed: {e}"))?;
+        }
+
+        let name_len = raw.name.iter().position(|&b| b == 0).unwrap_or(raw.name.len());
+        let name = String::from_utf8_lossy(&raw.name[..name_len]).into_owned();
+        let fe_type = FrontendType::from_raw(raw.fe_type)
+            .ok_or_else(|| format!("Unknown frontend type: {}", raw.fe_type))?;
+
+        Ok(FrontendInfo {
+            name,
+            fe_type,
+            frequency_min: raw.frequency_min,
+            frequency_max: raw.frequency_max,
+            frequency_stepsize: raw.frequency_stepsize,
+            symbol_rate_min: raw.symbol_rate_min,
+            symbol_rate_max: raw.symbol_rate_max,
+            caps: raw.caps,
+        })
+    }
+
+    /// Read one DVB v5 stats property via `FE_GET_PROPERTY`, returning
+    /// `Stat::Unavailable` if the ioctl fails or the driver didn't
+    /// populate it, so callers can fall back to the legacy ioctls.
+    fn read_stat_property(&self, cmd: u32) -> Stat {
+        let fd = self.fe_file.as_raw_fd();
+        let mut prop = DtvStatProperty::new(cmd);
+        let mut props = DtvStatProperties {
+            num: 1,
+            props: &mut prop,
+        };
+        if unsafe { fe_get_property(fd, &mut props) }.is_err() {
+            return Stat::Unavailable;
+        }
+        let len = prop.stat_len;
+        if len == 0 {
+            return Stat::Unavailable;
+        }
+        let scale = prop.scale0;
+        let value = prop.value0;
+        Stat::from_raw(scale, value as u64)
+    }
+
+    /// Query signal strength, carrier-to-noise ratio, bit error rate, and
+    /// uncorrected block count, so callers can judge reception quality and
+    /// log it while grabbing EPG. Prefers the DVB v5 stats API and falls
+    /// back to the legacy single-value ioctls for drivers that don't
+    /// populate it.
+    pub fn stats(&self) -> SignalStats {
+        let fd = self.fe_file.as_raw_fd();
+
+        let mut signal_strength = self.read_stat_property(DTV_STAT_SIGNAL_STRENGTH);
+        if !signal_strength.is_available() {
+            let mut raw: u16 = 0;
+            if unsafe { fe_read_signal_strength(fd, &mut raw) }.is_ok() {
+                signal_strength = Stat::Relative(raw);
+            }
+        }
+
+        let mut cnr = self.read_stat_property(DTV_STAT_CNR);
+        if !cnr.is_available() {
+            let mut raw: u16 = 0;
+            if unsafe { fe_read_snr(fd, &mut raw) }.is_ok() {
+                cnr = Stat::Relative(raw);
+            }
+        }
+
+        let error_bits = self.read_stat_property(DTV_STAT_PRE_ERROR_BIT_COUNT);
+        let total_bits = self.read_stat_property(DTV_STAT_PRE_TOTAL_BIT_COUNT);
+        let ber = ber_ratio(error_bits, total_bits).or_else(|| {
+            let mut raw: u32 = 0;
+            unsafe { fe_read_ber(fd, &mut raw) }.ok().map(|_| raw as f64)
+        });
+
+        let mut uncorrected_blocks = self.read_stat_property(DTV_STAT_ERROR_BLOCK_COUNT);
+        if !uncorrected_blocks.is_available() {
+            let mut raw: u32 = 0;
+            if unsafe { fe_read_uncorrected_blocks(fd, &mut raw) }.is_ok() {
+                uncorrected_blocks = Stat::Counter(raw as u64);
+            }
+        }
+
+        SignalStats {
+            signal_strength,
+            cnr,
+            ber,
+            uncorrected_blocks,
+        }
+    }
+
+    /// Reject a tuning request the frontend has already told us it can't
+    /// satisfy, rather than spending ten seconds polling for a lock that
+    /// was never going to happen. `frequency` is whatever the front-end
+    /// will actually be tuned to (the satellite IF for DVB-S, the raw
+    /// transponder frequency otherwise).
+    fn check_capable(&self, expected: FrontendType, frequency: u64) -> Result<(), String> {
+        let info = self.info()?;
+        if info.fe_type != expected {
+            return Err(format!(
+                "frontend {} is a {} tuner, not {expected}",
+                info.name, info.fe_type
+            ));
+        }
+        // A frontend that reports 0/0 genuinely has no range restriction to
+        // check (some drivers don't bother filling these in).
+        let has_range = info.frequency_min != 0 || info.frequency_max != 0;
+        let out_of_range =
+            frequency < info.frequency_min as u64 || frequency > info.frequency_max as u64;
+        if has_range && out_of_range {
+            // Every frontend type is checked against a raw Hz transponder
+            // frequency except DVB-S/S2, where tune_dvbs passes the
+            // post-LNB intermediate frequency in kHz instead.
+            let unit = if expected == FrontendType::Qpsk { "kHz" } else { "Hz" };
+            return Err(format!(
+                "frequency {frequency} {unit} is outside {}'s supported range ({}-{} {unit})",
+                info.name, info.frequency_min, info.frequency_max
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn tune(&self, channel: &Channel, config: &TuneConfig) -> Result<(), TuneError> {
+        if !matches!(channel.tuning, Tuning::DvbT { .. }) {
+            return match &channel.tuning {
+                Tuning::DvbS { .. } => self.tune_dvbs(channel, &config.lnb, config),
+                Tuning::DvbC { .. } => self.tune_dvbc(channel, config),
+                Tuning::Atsc { .. } => self.tune_atsc(channel, config),
+                Tuning::DvbT { .. } => unreachable!(),
+            };
+        }
+
+        let result = self.tune_dvbt(channel, config);
+        if !config.retry_auto {
+            return result;
+        }
+        match result {
+            Err(TuneError::Timeout(_)) => self.tune_dvbt_with_retry(channel, config),
+            other => other,
+        }
+    }
+
+    /// Retry a timed-out DVB-T tune, walking [`DVBT_RETRY_STEPS`] and
+    /// cumulatively forcing one more parameter to `*_AUTO` per step until
+    /// one locks or the table is exhausted. Mirrors the kernel's own
+    /// `force_auto_inversion` fallback, extended to the other parameters a
+    /// scanned channels.conf commonly gets wrong.
+    fn tune_dvbt_with_retry(&self, channel: &Channel, config: &TuneConfig) -> Result<(), TuneError> {
+        let mut retry_channel = channel.clone();
+        let mut applied = Vec::new();
+
+        for (label, downgrade) in DVBT_RETRY_STEPS {
+            downgrade(&mut retry_channel.tuning);
+            applied.push(*label);
+
+            match self.tune_dvbt(&retry_channel, config) {
+                Ok(()) => {
+                    info!("locked after forcing {} to AUTO", applied.join(", "));
+                    return Ok(());
+                }
+                Err(TuneError::Timeout(_)) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+
+        Err(TuneError::Timeout(format!(
+            "no lock after retrying with {} forced to AUTO",
+            applied.join(", ")
+        )))
+    }
+
+    /// Tune a DVB-T channel: frequency, bandwidth, constellation, code
+    /// rates, guard interval, transmission mode, and hierarchy.
+    fn tune_dvbt(&self, channel: &Channel, config: &TuneConfig) -> Result<(), TuneError> {
+        let Tuning::DvbT {
+            inversion,
+            bandwidth,
+            fec_hp,
+            fec_lp,
+            modulation,
+            transmission_mode,
+            guard_interval,
+            hierarchy,
+        } = &channel.tuning
+        else {
+            return Err(TuneError::Ioctl("tune_dvbt called with a non-DVB-T channel".to_string()));
+        };
+
+        self.check_capable(FrontendType::Ofdm, channel.frequency)?;
+
         let fd = self.fe_file.as_raw_fd();
 
         // Clear previous tuning
@@ -316,14 +1120,14 @@ impl Tuner {
         let mut props = [
             DtvProperty::new(DTV_DELIVERY_SYSTEM, SYS_DVBT),
             DtvProperty::new(DTV_FREQUENCY, channel.frequency as u32),
-            DtvProperty::new(DTV_BANDWIDTH_HZ, parse_bandwidth(&channel.bandwidth)?),
-            DtvProperty::new(DTV_MODULATION, parse_modulation(&channel.modulation)?),
-            DtvProperty::new(DTV_CODE_RATE_HP, parse_fec(&channel.fec_hp)?),
-            DtvProperty::new(DTV_CODE_RATE_LP, parse_fec(&channel.fec_lp)?),
-            DtvProperty::new(DTV_INVERSION, parse_inversion(&channel.inversion)?),
-            DtvProperty::new(DTV_TRANSMISSION_MODE, parse_transmission_mode(&channel.transmission_mode)?),
-            DtvProperty::new(DTV_GUARD_INTERVAL, parse_guard_interval(&channel.guard_interval)?),
-            DtvProperty::new(DTV_HIERARCHY, parse_hierarchy(&channel.hierarchy)?),
+            DtvProperty::new(DTV_BANDWIDTH_HZ, parse_bandwidth(&bandwidth.to_string())?),
+            DtvProperty::new(DTV_MODULATION, parse_modulation(&modulation.to_string())?),
+            DtvProperty::new(DTV_CODE_RATE_HP, parse_fec(&fec_hp.to_string())?),
+            DtvProperty::new(DTV_CODE_RATE_LP, parse_fec(&fec_lp.to_string())?),
+            DtvProperty::new(DTV_INVERSION, parse_inversion(&inversion.to_string())?),
+            DtvProperty::new(DTV_TRANSMISSION_MODE, parse_transmission_mode(&transmission_mode.to_string())?),
+            DtvProperty::new(DTV_GUARD_INTERVAL, parse_guard_interval(&guard_interval.to_string())?),
+            DtvProperty::new(DTV_HIERARCHY, parse_hierarchy(&hierarchy.to_string())?),
             DtvProperty::new(DTV_TUNE, 0),
         ];
 
@@ -337,20 +1141,245 @@ impl Tuner {
                 .map_err(|e| format!("FE_SET_PROPERTY failed: {e}"))?;
         }
 
-        // Poll for lock (up to 10 seconds)
-        for i in 0..100 {
-            let mut status: u32 = 0;
+        self.wait_for_lock(config)
+    }
+
+    /// Tune a DVB-S/S2 channel: select the LNB input via a DiSEqC 1.0
+    /// committed switch command, drive polarization via the 13V/18V
+    /// voltage setting and band via the 22 kHz tone, then tune the
+    /// front-end to the intermediate frequency the LNB delivers.
+    pub fn tune_dvbs(
+        &self,
+        channel: &Channel,
+        lnb: &LnbConfig,
+        config: &TuneConfig,
+    ) -> Result<(), TuneError> {
+        let Tuning::DvbS {
+            polarization,
+            symbol_rate,
+            fec,
+            modulation,
+            diseqc_port,
+            ..
+        } = &channel.tuning
+        else {
+            return Err(TuneError::Ioctl("tune_dvbs called with a non-DVB-S channel".to_string()));
+        };
+
+        let vertical = matches!(polarization.as_str(), "V" | "R");
+        // channel.frequency is normalized to Hz (see channel::parse_frequency);
+        // lnb_if_khz and the LNB LOF constants operate in kHz.
+        let (if_khz, hiband) = lnb_if_khz(channel.frequency / 1000, lnb);
+        self.check_capable(FrontendType::Qpsk, if_khz)?;
+
+        let fd = self.fe_file.as_raw_fd();
+
+        // Voltage selects polarization; must settle before the switch command.
+        unsafe {
+            fe_set_voltage(
+                fd,
+                if vertical { SEC_VOLTAGE_13 } else { SEC_VOLTAGE_18 },
+            )
+            .map_err(|e| format!("FE_SET_VOLTAGE failed: {e}"))?;
+        }
+        thread::sleep(Duration::from_millis(15));
+
+        // Tone must be off while the DiSEqC command is sent.
+        unsafe {
+            fe_set_tone(fd, SEC_TONE_OFF).map_err(|e| format!("FE_SET_TONE failed: {e}"))?;
+        }
+        thread::sleep(Duration::from_millis(15));
+
+        let mut cmd = diseqc_committed_switch_cmd(*diseqc_port, vertical, hiband);
+        unsafe {
+            fe_diseqc_send_master_cmd(fd, &mut cmd)
+                .map_err(|e| format!("FE_DISEQC_SEND_MASTER_CMD failed: {e}"))?;
+        }
+        thread::sleep(Duration::from_millis(150));
+
+        // Tone selects band (on = high band, off = low band).
+        unsafe {
+            fe_set_tone(fd, if hiband { SEC_TONE_ON } else { SEC_TONE_OFF })
+                .map_err(|e| format!("FE_SET_TONE failed: {e}"))?;
+        }
+
+        // Clear previous tuning
+        let mut clear_prop = DtvProperty::new(DTV_CLEAR, 0);
+        let mut clear_props = DtvProperties {
+            num: 1,
+            props: &mut clear_prop,
+        };
+        unsafe {
+            fe_set_property(fd, &mut clear_props)
+                .map_err(|e| format!("DTV_CLEAR failed: {e}"))?;
+        }
+
+        let delivery = if modulation == "PSK_8" {
+            SYS_DVBS2
+        } else {
+            SYS_DVBS
+        };
+
+        let mut props = [
+            DtvProperty::new(DTV_DELIVERY_SYSTEM, delivery),
+            DtvProperty::new(DTV_FREQUENCY, if_khz as u32),
+            DtvProperty::new(DTV_SYMBOL_RATE, *symbol_rate as u32),
+            DtvProperty::new(DTV_MODULATION, parse_modulation(modulation)?),
+            DtvProperty::new(DTV_INNER_FEC, parse_fec(fec)?),
+            // DvbS carries no inversion field (DVB-S receivers near-universally
+            // auto-detect spectral inversion), so always request AUTO here.
+            DtvProperty::new(DTV_INVERSION, parse_inversion("INVERSION_AUTO")?),
+            // Likewise pilot tones and roll-off are not exposed on Tuning::DvbS
+            // (zap-style channel lists never carry them either); AUTO lets the
+            // front-end figure both out during lock.
+            DtvProperty::new(DTV_PILOT, parse_pilot("PILOT_AUTO")?),
+            DtvProperty::new(DTV_ROLLOFF, parse_rolloff("ROLLOFF_AUTO")?),
+            DtvProperty::new(DTV_TUNE, 0),
+        ];
+
+        let mut dtv_props = DtvProperties {
+            num: props.len() as u32,
+            props: props.as_mut_ptr(),
+        };
+
+        unsafe {
+            fe_set_property(fd, &mut dtv_props)
+                .map_err(|e| format!("FE_SET_PROPERTY failed: {e}"))?;
+        }
+
+        self.wait_for_lock(config)
+    }
+
+    /// Tune a DVB-C channel. Cable delivery has no LNB or DiSEqC stage: the
+    /// transponder frequency is fed to the front-end directly.
+    pub fn tune_dvbc(&self, channel: &Channel, config: &TuneConfig) -> Result<(), TuneError> {
+        let Tuning::DvbC {
+            symbol_rate,
+            fec,
+            modulation,
+            ..
+        } = &channel.tuning
+        else {
+            return Err(TuneError::Ioctl("tune_dvbc called with a non-DVB-C channel".to_string()));
+        };
+
+        self.check_capable(FrontendType::Qam, channel.frequency)?;
+
+        let fd = self.fe_file.as_raw_fd();
+
+        // Clear previous tuning
+        let mut clear_prop = DtvProperty::new(DTV_CLEAR, 0);
+        let mut clear_props = DtvProperties {
+            num: 1,
+            props: &mut clear_prop,
+        };
+        unsafe {
+            fe_set_property(fd, &mut clear_props)
+                .map_err(|e| format!("DTV_CLEAR failed: {e}"))?;
+        }
+
+        let mut props = [
+            DtvProperty::new(DTV_DELIVERY_SYSTEM, SYS_DVBC_ANNEX_A),
+            DtvProperty::new(DTV_FREQUENCY, channel.frequency as u32),
+            DtvProperty::new(DTV_SYMBOL_RATE, *symbol_rate as u32),
+            DtvProperty::new(DTV_MODULATION, parse_modulation(modulation)?),
+            DtvProperty::new(DTV_INNER_FEC, parse_fec(fec)?),
+            DtvProperty::new(DTV_TUNE, 0),
+        ];
+
+        let mut dtv_props = DtvProperties {
+            num: props.len() as u32,
+            props: props.as_mut_ptr(),
+        };
+
+        unsafe {
+            fe_set_property(fd, &mut dtv_props)
+                .map_err(|e| format!("FE_SET_PROPERTY failed: {e}"))?;
+        }
+
+        self.wait_for_lock(config)
+    }
+
+    /// Tune an ATSC (8-VSB/16-VSB) channel: frequency plus modulation is
+    /// all the front-end needs, no symbol rate or FEC involved.
+    pub fn tune_atsc(&self, channel: &Channel, config: &TuneConfig) -> Result<(), TuneError> {
+        let Tuning::Atsc { modulation } = &channel.tuning else {
+            return Err(TuneError::Ioctl("tune_atsc called with a non-ATSC channel".to_string()));
+        };
+
+        self.check_capable(FrontendType::Atsc, channel.frequency)?;
+
+        let fd = self.fe_file.as_raw_fd();
+
+        // Clear previous tuning
+        let mut clear_prop = DtvProperty::new(DTV_CLEAR, 0);
+        let mut clear_props = DtvProperties {
+            num: 1,
+            props: &mut clear_prop,
+        };
+        unsafe {
+            fe_set_property(fd, &mut clear_props)
+                .map_err(|e| format!("DTV_CLEAR failed: {e}"))?;
+        }
+
+        let mut props = [
+            DtvProperty::new(DTV_DELIVERY_SYSTEM, SYS_ATSC),
+            DtvProperty::new(DTV_FREQUENCY, channel.frequency as u32),
+            DtvProperty::new(DTV_MODULATION, parse_modulation(modulation)?),
+            DtvProperty::new(DTV_TUNE, 0),
+        ];
+
+        let mut dtv_props = DtvProperties {
+            num: props.len() as u32,
+            props: props.as_mut_ptr(),
+        };
+
+        unsafe {
+            fe_set_property(fd, &mut dtv_props)
+                .map_err(|e| format!("FE_SET_PROPERTY failed: {e}"))?;
+        }
+
+        self.wait_for_lock(config)
+    }
+
+    /// Wait for the frontend to report `FE_HAS_LOCK`, the way mpv and the
+    /// kernel's own tuning thread do it: block in `poll(2)` for events on
+    /// the frontend fd, then drain `FE_GET_EVENT` to read the status
+    /// transition, rather than busy-polling `FE_READ_STATUS`.
+    fn wait_for_lock(&self, config: &TuneConfig) -> Result<(), TuneError> {
+        let fd = self.fe_file.as_fd();
+        let raw_fd = self.fe_file.as_raw_fd();
+        let start = Instant::now();
+
+        loop {
+            let elapsed = start.elapsed();
+            if elapsed >= config.tune_timeout {
+                return Err(TuneError::Timeout(format!(
+                    "Tuning timed out: no lock after {}ms",
+                    config.tune_timeout.as_millis()
+                )));
+            }
+
+            let slice = config.lock_timeout.min(config.tune_timeout - elapsed);
+            let timeout = PollTimeout::try_from(slice).unwrap_or(PollTimeout::MAX);
+
+            let mut pollfds = [PollFd::new(fd, PollFlags::POLLIN)];
+            let ready =
+                poll(&mut pollfds, timeout).map_err(|e| TuneError::Ioctl(format!("poll failed: {e}")))?;
+            if ready == 0 {
+                continue;
+            }
+
+            let mut event: DvbFrontendEvent = unsafe { std::mem::zeroed() };
             unsafe {
-                fe_read_status(fd, &mut status)
-                    .map_err(|e| format!("FE_READ_STATUS failed: {e}"))?;
+                fe_get_event(raw_fd, &mut event)
+                    .map_err(|e| TuneError::Ioctl(format!("FE_GET_EVENT failed: {e}")))?;
             }
-            if status & FE_HAS_LOCK != 0 {
-                println!("Frontend locked after {}ms", (i + 1) * 100);
+            debug!("Frontend event: status={:#x}", event.status);
+            if event.status & FE_HAS_LOCK != 0 {
+                debug!("Frontend locked after {}ms", elapsed.as_millis());
                 return Ok(());
             }
-            thread::sleep(Duration::from_millis(100));
         }
-
-        Err("Tuning timed out: no lock after 10 seconds".to_string())
     }
 }
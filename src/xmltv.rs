@@ -0,0 +1,422 @@
+/// How to interpret and emit XMLTV `start`/`stop` timestamps
+/// (`YYYYMMDDHHMMSS +ZZZZ`), independent of the box's own `TZ`.
+///
+/// - `Auto` trusts whatever offset is embedded in the timestamp when
+///   parsing, and emits the machine's local offset (`localtime_r`) when
+///   formatting — this is the historical `format_xmltv_time` behavior.
+/// - `None` treats the naive date/time fields as already being in the
+///   target zone, ignoring any embedded offset on parse and emitting
+///   `+0000` on format without touching the calendar fields.
+/// - `Explicit` reinterprets the naive date/time fields as wall-clock time
+///   in the given zone, regardless of what offset (if any) is embedded in
+///   an imported guide or what the machine's `TZ` is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimeOffset {
+    Auto,
+    None,
+    Explicit(i32),
+}
+
+impl TimeOffset {
+    /// Parse a `--tz`-style value: `"auto"`, `"none"`, or an explicit
+    /// `+HHMM`/`-HHMM` offset.
+    pub fn parse(s: &str) -> Result<TimeOffset, String> {
+        match s {
+            "auto" => Ok(TimeOffset::Auto),
+            "none" => Ok(TimeOffset::None),
+            _ => Ok(TimeOffset::Explicit(parse_offset_seconds(s)?)),
+        }
+    }
+}
+
+/// Parse a `+HHMM`/`-HHMM` UTC offset string into signed seconds.
+fn parse_offset_seconds(s: &str) -> Result<i32, String> {
+    if s.len() != 5 || (!s.starts_with('+') && !s.starts_with('-')) {
+        return Err(format!("invalid UTC offset '{s}' (expected +HHMM or -HHMM)"));
+    }
+    let sign = if s.starts_with('-') { -1 } else { 1 };
+    let hh: i32 = s[1..3]
+        .parse()
+        .map_err(|_| format!("invalid UTC offset '{s}'"))?;
+    let mm: i32 = s[3..5]
+        .parse()
+        .map_err(|_| format!("invalid UTC offset '{s}'"))?;
+    Ok(sign * (hh * 3600 + mm * 60))
+}
+
+fn format_offset_seconds(offset_secs: i32) -> String {
+    let sign = if offset_secs < 0 { '-' } else { '+' };
+    let abs = offset_secs.abs();
+    format!("{sign}{:02}{:02}", abs / 3600, (abs % 3600) / 60)
+}
+
+/// Format a Unix timestamp as an XMLTV `start`/`stop` value, following
+/// `offset`'s rules (see [`TimeOffset`]).
+pub fn format_xmltv_time(ts: i64, offset: &TimeOffset) -> String {
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    let offset_secs = match offset {
+        TimeOffset::Auto => {
+            let time_t = ts as libc::time_t;
+            unsafe { libc::localtime_r(&time_t, &mut tm) };
+            tm.tm_gmtoff as i32
+        }
+        TimeOffset::None => {
+            let time_t = ts as libc::time_t;
+            unsafe { libc::gmtime_r(&time_t, &mut tm) };
+            0
+        }
+        TimeOffset::Explicit(secs) => {
+            let time_t = (ts + *secs as i64) as libc::time_t;
+            unsafe { libc::gmtime_r(&time_t, &mut tm) };
+            *secs
+        }
+    };
+
+    format!(
+        "{:04}{:02}{:02}{:02}{:02}{:02} {}",
+        tm.tm_year + 1900,
+        tm.tm_mon + 1,
+        tm.tm_mday,
+        tm.tm_hour,
+        tm.tm_min,
+        tm.tm_sec,
+        format_offset_seconds(offset_secs),
+    )
+}
+
+/// Parse an XMLTV `start`/`stop` value (`YYYYMMDDHHMMSS` optionally followed
+/// by ` +ZZZZ`) into a Unix timestamp, following `offset`'s rules (see
+/// [`TimeOffset`]). This is the inverse of [`format_xmltv_time`].
+pub fn parse_xmltv_time(s: &str, offset: &TimeOffset) -> Result<i64, String> {
+    let s = s.trim();
+    if s.len() < 14 || !s.is_char_boundary(14) {
+        return Err(format!("invalid XMLTV timestamp '{s}'"));
+    }
+    let digits = &s[..14];
+    if !digits.is_ascii() {
+        return Err(format!("invalid XMLTV timestamp '{s}'"));
+    }
+    let embedded_offset = s[14..].trim();
+
+    let field = |range: std::ops::Range<usize>| -> Result<i32, String> {
+        digits[range]
+            .parse()
+            .map_err(|_| format!("invalid XMLTV timestamp '{s}'"))
+    };
+    let year = field(0..4)?;
+    let month = field(4..6)?;
+    let day = field(6..8)?;
+    let hour = field(8..10)?;
+    let minute = field(10..12)?;
+    let second = field(12..14)?;
+
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    tm.tm_year = year - 1900;
+    tm.tm_mon = month - 1;
+    tm.tm_mday = day;
+    tm.tm_hour = hour;
+    tm.tm_min = minute;
+    tm.tm_sec = second;
+    let naive_epoch = unsafe { libc::timegm(&mut tm) } as i64;
+
+    let applied_offset = match offset {
+        TimeOffset::Auto => {
+            if embedded_offset.is_empty() {
+                0
+            } else {
+                parse_offset_seconds(embedded_offset)?
+            }
+        }
+        TimeOffset::None => 0,
+        TimeOffset::Explicit(secs) => *secs,
+    };
+
+    Ok(naive_epoch - applied_offset as i64)
+}
+
+/// One programme entry parsed out of an imported XMLTV guide.
+pub struct XmltvProgramme {
+    pub channel: String,
+    pub start: i64,
+    pub stop: i64,
+    pub title: String,
+    pub description: String,
+    pub language: String,
+}
+
+fn xml_unescape(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Pull the value of `attr="..."` out of a start tag's attribute text.
+fn extract_attr(tag: &str, attr: &str) -> Option<String> {
+    let needle = format!("{attr}=\"");
+    let start = tag.find(&needle)? + needle.len();
+    let end = start + tag[start..].find('"')?;
+    Some(xml_unescape(&tag[start..end]))
+}
+
+/// Pull the text between `<tag ...>` and `</tag>`, starting the search at
+/// `from`, returning the text and the byte offset right after the close
+/// tag.
+fn extract_element(content: &str, tag: &str, from: usize) -> Option<(String, usize)> {
+    let open_needle = format!("<{tag}");
+    let open_start = content[from..].find(&open_needle)? + from;
+    let open_end = content[open_start..].find('>')? + open_start;
+    let is_self_closing = content[open_start..open_end].ends_with('/');
+    if is_self_closing {
+        return Some((String::new(), open_end + 1));
+    }
+
+    let close_needle = format!("</{tag}>");
+    let text_start = open_end + 1;
+    let text_end = content[text_start..].find(&close_needle)? + text_start;
+    Some((
+        xml_unescape(content[text_start..text_end].trim()),
+        text_end + close_needle.len(),
+    ))
+}
+
+/// Parse the `<channel>`/`<programme>` elements out of an XMLTV document as
+/// produced by `generate_xmltv` (the inverse operation), resolving each
+/// programme's channel id to the display-name `generate_xmltv` uses as both.
+pub fn parse_xmltv(content: &str, offset: &TimeOffset) -> Result<Vec<XmltvProgramme>, String> {
+    let mut channel_names: std::collections::BTreeMap<String, String> = std::collections::BTreeMap::new();
+    let mut pos = 0;
+    while let Some(tag_start) = content[pos..].find("<channel ").map(|i| i + pos) {
+        let tag_end = content[tag_start..]
+            .find('>')
+            .ok_or_else(|| "Unterminated <channel> tag".to_string())?
+            + tag_start;
+        let id = extract_attr(&content[tag_start..=tag_end], "id")
+            .ok_or_else(|| "<channel> missing id attribute".to_string())?;
+        let (display_name, next) = extract_element(content, "display-name", tag_end)
+            .ok_or_else(|| format!("<channel id=\"{id}\"> missing <display-name>"))?;
+        channel_names.insert(id, display_name);
+        pos = next;
+    }
+
+    let mut programmes = Vec::new();
+    let mut pos = 0;
+    while let Some(tag_start) = content[pos..].find("<programme ").map(|i| i + pos) {
+        let tag_end = content[tag_start..]
+            .find('>')
+            .ok_or_else(|| "Unterminated <programme> tag".to_string())?
+            + tag_start;
+        let tag = &content[tag_start..=tag_end];
+
+        let start_str = extract_attr(tag, "start")
+            .ok_or_else(|| "<programme> missing start attribute".to_string())?;
+        let stop_str = extract_attr(tag, "stop")
+            .ok_or_else(|| "<programme> missing stop attribute".to_string())?;
+        let channel_id = extract_attr(tag, "channel")
+            .ok_or_else(|| "<programme> missing channel attribute".to_string())?;
+
+        let start = parse_xmltv_time(&start_str, offset)?;
+        let stop = parse_xmltv_time(&stop_str, offset)?;
+
+        let (title, after_title) = extract_element(content, "title", tag_end)
+            .ok_or_else(|| format!("<programme channel=\"{channel_id}\"> missing <title>"))?;
+
+        let close_needle = "</programme>";
+        let programme_end = content[after_title..]
+            .find(close_needle)
+            .ok_or_else(|| "Unterminated <programme> element".to_string())?
+            + after_title;
+
+        let description = extract_element(&content[..programme_end], "desc", after_title)
+            .map(|(text, _)| text)
+            .unwrap_or_default();
+
+        let channel = channel_names.get(&channel_id).cloned().unwrap_or(channel_id);
+
+        programmes.push(XmltvProgramme {
+            channel,
+            start,
+            stop,
+            title,
+            description,
+            language: String::new(),
+        });
+
+        pos = programme_end + close_needle.len();
+    }
+
+    Ok(programmes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // --- TimeOffset::parse ---
+
+    #[test]
+    fn test_time_offset_parse_auto_and_none() {
+        assert_eq!(TimeOffset::parse("auto").unwrap(), TimeOffset::Auto);
+        assert_eq!(TimeOffset::parse("none").unwrap(), TimeOffset::None);
+    }
+
+    #[test]
+    fn test_time_offset_parse_explicit_positive() {
+        assert_eq!(TimeOffset::parse("+0200").unwrap(), TimeOffset::Explicit(7200));
+    }
+
+    #[test]
+    fn test_time_offset_parse_explicit_negative() {
+        assert_eq!(TimeOffset::parse("-0530").unwrap(), TimeOffset::Explicit(-19800));
+    }
+
+    #[test]
+    fn test_time_offset_parse_invalid() {
+        assert!(TimeOffset::parse("garbage").is_err());
+    }
+
+    // --- format_xmltv_time / parse_xmltv_time roundtrip ---
+
+    #[test]
+    fn test_roundtrip_explicit_offset() {
+        let offset = TimeOffset::Explicit(3600);
+        let ts = 1_700_000_000i64;
+        let formatted = format_xmltv_time(ts, &offset);
+        assert_eq!(parse_xmltv_time(&formatted, &offset).unwrap(), ts);
+    }
+
+    #[test]
+    fn test_roundtrip_none_offset() {
+        let offset = TimeOffset::None;
+        let ts = 1_700_000_000i64;
+        let formatted = format_xmltv_time(ts, &offset);
+        assert!(formatted.ends_with("+0000"));
+        assert_eq!(parse_xmltv_time(&formatted, &offset).unwrap(), ts);
+    }
+
+    #[test]
+    fn test_format_explicit_offset_known_value() {
+        // 2023-11-14 22:13:20 UTC
+        let formatted = format_xmltv_time(1_700_000_000, &TimeOffset::Explicit(9 * 3600));
+        assert_eq!(formatted, "20231115071320 +0900");
+    }
+
+    #[test]
+    fn test_parse_ignores_embedded_offset_when_explicit() {
+        // Embedded offset says +0000, but we force +0200 interpretation.
+        let ts = parse_xmltv_time("20240101120000 +0000", &TimeOffset::Explicit(7200)).unwrap();
+        let naive = parse_xmltv_time("20240101120000 +0000", &TimeOffset::None).unwrap();
+        assert_eq!(naive - ts, 7200);
+    }
+
+    #[test]
+    fn test_parse_auto_uses_embedded_offset() {
+        let a = parse_xmltv_time("20240101120000 +0200", &TimeOffset::Auto).unwrap();
+        let b = parse_xmltv_time("20240101100000 +0000", &TimeOffset::Auto).unwrap();
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_parse_rejects_too_short_timestamp() {
+        assert!(parse_xmltv_time("2024010112", &TimeOffset::None).is_err());
+    }
+
+    #[test]
+    fn test_parse_rejects_non_ascii_timestamp_without_panicking() {
+        // A multi-byte char within the first 14 bytes used to panic on the
+        // raw byte-offset slice instead of returning an error.
+        assert!(parse_xmltv_time("2024010112000é000000 +0000", &TimeOffset::None).is_err());
+    }
+
+    // --- parse_xmltv ---
+
+    fn sample_xmltv() -> String {
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <!DOCTYPE tv SYSTEM \"xmltv.dtd\">\n\
+         <tv generator-info-name=\"epgrab\">\n\
+         \x20 <channel id=\"BBC One\">\n\
+         \x20\x20\x20<display-name>BBC One</display-name>\n\
+         \x20 </channel>\n\
+         \x20 <programme start=\"20240101120000 +0000\" stop=\"20240101123000 +0000\" channel=\"BBC One\">\n\
+         \x20\x20\x20<title>The News</title>\n\
+         \x20\x20\x20<desc>Today's headlines</desc>\n\
+         \x20 </programme>\n\
+         </tv>\n".to_string()
+    }
+
+    #[test]
+    fn test_parse_xmltv_basic() {
+        let programmes = parse_xmltv(&sample_xmltv(), &TimeOffset::None).unwrap();
+        assert_eq!(programmes.len(), 1);
+        assert_eq!(programmes[0].channel, "BBC One");
+        assert_eq!(programmes[0].title, "The News");
+        assert_eq!(programmes[0].description, "Today's headlines");
+        assert_eq!(programmes[0].stop - programmes[0].start, 1800);
+    }
+
+    #[test]
+    fn test_parse_xmltv_unescapes_entities() {
+        let xml = "<tv>\n \
+                    <channel id=\"c1\"><display-name>Tom &amp; Jerry</display-name></channel>\n \
+                    <programme start=\"20240101120000 +0000\" stop=\"20240101123000 +0000\" channel=\"c1\">\n \
+                    <title>Rock &amp; Roll &lt;Live&gt;</title>\n \
+                    </programme>\n</tv>";
+        let programmes = parse_xmltv(xml, &TimeOffset::None).unwrap();
+        assert_eq!(programmes[0].channel, "Tom & Jerry");
+        assert_eq!(programmes[0].title, "Rock & Roll <Live>");
+    }
+
+    #[test]
+    fn test_parse_xmltv_missing_description_is_empty() {
+        let xml = "<tv>\n \
+                    <channel id=\"c1\"><display-name>C1</display-name></channel>\n \
+                    <programme start=\"20240101120000 +0000\" stop=\"20240101123000 +0000\" channel=\"c1\">\n \
+                    <title>No Desc</title>\n \
+                    </programme>\n</tv>";
+        let programmes = parse_xmltv(xml, &TimeOffset::None).unwrap();
+        assert_eq!(programmes[0].description, "");
+    }
+
+    #[test]
+    fn test_parse_xmltv_multiple_programmes() {
+        let xml = "<tv>\n \
+                    <channel id=\"c1\"><display-name>C1</display-name></channel>\n \
+                    <programme start=\"20240101120000 +0000\" stop=\"20240101123000 +0000\" channel=\"c1\">\n \
+                    <title>First</title>\n \
+                    </programme>\n \
+                    <programme start=\"20240101123000 +0000\" stop=\"20240101130000 +0000\" channel=\"c1\">\n \
+                    <title>Second</title>\n \
+                    </programme>\n</tv>";
+        let programmes = parse_xmltv(xml, &TimeOffset::None).unwrap();
+        assert_eq!(programmes.len(), 2);
+        assert_eq!(programmes[0].title, "First");
+        assert_eq!(programmes[1].title, "Second");
+    }
+
+    #[test]
+    fn test_parse_xmltv_falls_back_to_channel_id_when_unmatched() {
+        let xml = "<tv>\n \
+                    <programme start=\"20240101120000 +0000\" stop=\"20240101123000 +0000\" channel=\"Mystery Channel\">\n \
+                    <title>Unknown</title>\n \
+                    </programme>\n</tv>";
+        let programmes = parse_xmltv(xml, &TimeOffset::None).unwrap();
+        assert_eq!(programmes[0].channel, "Mystery Channel");
+    }
+
+    #[test]
+    fn test_generate_then_parse_roundtrip() {
+        // Mirrors main.rs's generate_xmltv output shape closely enough to
+        // exercise the parser against the real writer's format.
+        let xml = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!DOCTYPE tv SYSTEM \"xmltv.dtd\">\n<tv generator-info-name=\"epgrab\">\n  <channel id=\"My Channel\">\n    <display-name>My Channel</display-name>\n  </channel>\n  <programme start=\"{}\" stop=\"{}\" channel=\"My Channel\">\n    <title>Show</title>\n    <desc>Description</desc>\n  </programme>\n</tv>\n",
+            format_xmltv_time(1_700_000_000, &TimeOffset::None),
+            format_xmltv_time(1_700_003_600, &TimeOffset::None),
+        );
+        let programmes = parse_xmltv(&xml, &TimeOffset::None).unwrap();
+        assert_eq!(programmes.len(), 1);
+        assert_eq!(programmes[0].channel, "My Channel");
+        assert_eq!(programmes[0].start, 1_700_000_000);
+        assert_eq!(programmes[0].stop, 1_700_003_600);
+    }
+}